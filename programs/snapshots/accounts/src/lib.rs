@@ -0,0 +1,75 @@
+//! Anchor-independent decoders for [snapshots] accounts.
+//!
+//! Indexers and programs built against other Anchor versions can use this crate to validate
+//! the account discriminator and read a [LockerHistory], [EscrowHistory] or [CompactEscrowHistory]
+//! from raw account data.
+//! Balances of periods that have not been materialized are computed with [snapshots_math].
+//!
+//! [snapshots]: https://docs.rs/snapshots
+#![deny(rustdoc::all)]
+#![allow(rustdoc::missing_doc_code_examples)]
+#![deny(clippy::unwrap_used, clippy::integer_arithmetic)]
+#![deny(missing_docs)]
+
+mod state;
+
+pub use state::*;
+
+use bytemuck::Pod;
+use thiserror::Error;
+
+/// Number of bytes of the discriminator at the start of every account.
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// Errors returned when decoding an account.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The account data is smaller than the account.
+    #[error("account data too small")]
+    AccountTooSmall,
+    /// The account data does not start with the discriminator of the account.
+    #[error("invalid account discriminator")]
+    InvalidDiscriminator,
+    /// The account data is not aligned for the account. Use [read] instead.
+    #[error("account data is not aligned")]
+    Unaligned,
+}
+
+/// An account stored by the snapshots program.
+pub trait AccountData: Pod {
+    /// The Anchor discriminator of the account, i.e. the first 8 bytes of
+    /// `sha256("account:<AccountName>")`.
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN];
+}
+
+/// Checks the discriminator and returns the bytes of the account, excluding the discriminator.
+fn account_bytes<T: AccountData>(data: &[u8]) -> Result<&[u8], DecodeError> {
+    let len = DISCRIMINATOR_LEN
+        .checked_add(std::mem::size_of::<T>())
+        .ok_or(DecodeError::AccountTooSmall)?;
+    if data.len() < len {
+        return Err(DecodeError::AccountTooSmall);
+    }
+    let (discriminator, rest) = data.split_at(DISCRIMINATOR_LEN);
+    if discriminator != T::DISCRIMINATOR {
+        return Err(DecodeError::InvalidDiscriminator);
+    }
+    Ok(rest.split_at(std::mem::size_of::<T>()).0)
+}
+
+/// Borrows an account from its raw data without copying it.
+///
+/// This is what on-chain programs should use, since the accounts are too large to copy onto the stack.
+/// The data must be aligned for `T`. On-chain, where `u128` is 8-byte aligned, this is always the
+/// case for account data passed to a program. On hosts where `u128` is 16-byte aligned, a
+/// [LockerHistory] starting 8 bytes into an aligned buffer is not, so this returns
+/// [DecodeError::Unaligned]; use [read] there instead.
+pub fn load<T: AccountData>(data: &[u8]) -> Result<&T, DecodeError> {
+    bytemuck::try_from_bytes(account_bytes::<T>(data)?).map_err(|_| DecodeError::Unaligned)
+}
+
+/// Copies an account out of its raw data, which may have any alignment.
+pub fn read<T: AccountData>(data: &[u8]) -> Result<T, DecodeError> {
+    bytemuck::try_pod_read_unaligned(account_bytes::<T>(data)?)
+        .map_err(|_| DecodeError::AccountTooSmall)
+}
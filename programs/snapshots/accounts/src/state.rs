@@ -0,0 +1,460 @@
+//! Layouts of the snapshots accounts.
+//!
+//! These must match the accounts defined in the `snapshots` program byte for byte.
+
+use crate::{AccountData, DISCRIMINATOR_LEN};
+use bytemuck::{Pod, Zeroable};
+use snapshots_math::{calculate_voter_power_for_period, EraGeometry, PeriodId};
+
+/// Number of periods in an era.
+pub const ERA_NUM_PERIODS: usize = 256;
+
+/// Stores the total number of veTokens in circulation for each period.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockerHistory {
+    /// The `Locker` being tracked.
+    pub locker: [u8; 32],
+    /// The era.
+    pub era: u16,
+    /// Bump seed.
+    pub bump: u8,
+    /// The `max_stake_vote_multiplier` that balances in this history are computed with.
+    pub max_stake_vote_multiplier: u8,
+    /// Number of times this history has been reset after a change to the `LockerParams`.
+    pub params_version: u16,
+    /// `1` while lockups are being resynced after the last reset, `0` otherwise.
+    pub resyncing: u8,
+    /// Padding.
+    pub _padding: [u8; 1],
+    /// The sum of all tracked historical vote escrow balances.
+    ///
+    /// Only periods before [LockerHistory::materialized_periods] are complete.
+    pub ve_balances: [u64; ERA_NUM_PERIODS],
+    /// Number of voters with active lockups at each period.
+    pub ve_counts: [u64; ERA_NUM_PERIODS],
+    /// The `max_stake_duration` that balances in this history are computed with.
+    pub max_stake_duration: u64,
+    /// Sum of `power_if_max_lockup * end_period` of every lockup tracked by slope.
+    pub bias: u128,
+    /// Sum of the voting power at max lockup of every lockup tracked by slope.
+    pub slope: u64,
+    /// Voting power lost to lockups tracked by slope ending partway through a period.
+    pub end_offset: u64,
+    /// Number of lockups tracked by slope.
+    pub voters: u64,
+    /// Number of periods, starting from period 0, that have been materialized.
+    pub materialized_periods: u16,
+    /// First period whose totals are computed with the current parameters.
+    pub resync_period: u16,
+    /// Padding.
+    pub _padding2: [u8; 4],
+    /// Amount to subtract from the [LockerHistory::slope] at the start of each period.
+    pub slope_changes: [u64; ERA_NUM_PERIODS],
+    /// Amount to subtract from the [LockerHistory::end_offset] at the start of each period.
+    pub end_offset_changes: [u64; ERA_NUM_PERIODS],
+    /// Amount to subtract from the [LockerHistory::voters] at the start of each period.
+    pub voter_changes: [u32; ERA_NUM_PERIODS],
+    /// Number of shards merged into each period.
+    pub merged_shards: [u16; ERA_NUM_PERIODS],
+    /// Number of shards created for this history.
+    pub num_shards: u16,
+    /// Padding.
+    pub _padding3: [u8; 2],
+    /// Number of lockups that still need to be resynced after the history was last reset.
+    pub pending_resyncs: u32,
+    /// Number of seconds after the end of the era before this history may be closed.
+    pub retention_seconds: u64,
+    /// Account that paid for this history.
+    pub payer: [u8; 32],
+    /// Number of seconds in a period, or `0` for the default of 3 days.
+    pub period_seconds: u64,
+    /// The Unix timestamp of the start of era 0. Ignored if [LockerHistory::period_seconds] is `0`.
+    pub origin_ts: u64,
+}
+
+// Safety: the struct is `repr(C)`, every field is `Pod`, and fields are ordered so that there is no padding.
+unsafe impl Zeroable for LockerHistory {}
+unsafe impl Pod for LockerHistory {}
+
+impl AccountData for LockerHistory {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [0x52, 0x94, 0xe4, 0x68, 0x84, 0x90, 0x65, 0x81];
+}
+
+impl LockerHistory {
+    /// Returns true if the balance of the period has been materialized,
+    /// every shard has been merged into it, and it is not being resynced.
+    pub fn is_period_final(&self, period: u8) -> bool {
+        usize::from(period) < usize::from(self.materialized_periods)
+            && self.merged_shards[usize::from(period)] == self.num_shards
+            && !(self.resyncing != 0 && u16::from(period) >= self.resync_period)
+    }
+}
+
+/// Stores the veToken balance of an `Escrow` for each period.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EscrowHistory {
+    /// The `Escrow` being tracked.
+    pub escrow: [u8; 32],
+    /// The era.
+    pub era: u16,
+    /// Bump seed.
+    pub bump: u8,
+    /// Padding.
+    pub _padding: [u8; 5],
+    /// Materialized vote escrow balances.
+    ///
+    /// Only periods before [EscrowHistory::materialized_periods] are stored; later
+    /// balances are computed from the recorded lockup by [EscrowHistory::calculate_ve_balance].
+    pub ve_balances: [u64; ERA_NUM_PERIODS],
+    /// Voting power at max lockup of the `Escrow` when it was last synced.
+    pub power: u64,
+    /// `escrow_started_at` when the escrow was last synced.
+    pub started_at: u64,
+    /// `escrow_ends_at` when the escrow was last synced.
+    pub ends_at: u64,
+    /// Number of periods, starting from period 0, whose balances have been materialized.
+    pub materialized_periods: u16,
+    /// `0` if the lockup is tracked in the [LockerHistory], or `n` for the shard with index `n - 1`.
+    pub shard: u16,
+    /// [LockerHistory::params_version] when the escrow was last synced.
+    pub params_version: u16,
+    /// [LockerHistory::max_stake_vote_multiplier] when the escrow was last synced.
+    pub max_stake_vote_multiplier: u8,
+    /// Padding.
+    pub _padding2: [u8; 1],
+    /// Number of seconds after the end of the era before this history may be closed.
+    pub retention_seconds: u64,
+    /// Account that paid for this history.
+    pub payer: [u8; 32],
+    /// [LockerHistory::max_stake_duration] when the escrow was last synced.
+    pub max_stake_duration: u64,
+    /// [LockerHistory::period_seconds] when the escrow was last synced.
+    pub period_seconds: u64,
+    /// [LockerHistory::origin_ts] when the escrow was last synced.
+    pub origin_ts: u64,
+    /// Padding.
+    pub _padding3: [u8; 8],
+}
+
+// Safety: the struct is `repr(C)`, every field is `Pod`, and fields are ordered so that there is no padding.
+unsafe impl Zeroable for EscrowHistory {}
+unsafe impl Pod for EscrowHistory {}
+
+impl AccountData for EscrowHistory {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [0xaa, 0xa0, 0xad, 0x64, 0x5e, 0x77, 0x6b, 0x51];
+}
+
+impl EscrowHistory {
+    /// The [EraGeometry] of the periods of this history.
+    pub fn geometry(&self) -> EraGeometry {
+        stored_geometry(self.period_seconds, self.origin_ts)
+    }
+
+    /// Calculates the veBalance of the `Escrow` at the given period.
+    ///
+    /// Periods that have not been materialized are computed from the recorded lockup, as the program does.
+    pub fn calculate_ve_balance(&self, period: u8) -> Option<u64> {
+        if u16::from(period) < self.materialized_periods {
+            return Some(self.ve_balances[usize::from(period)]);
+        }
+        calculate_voter_power_for_period(
+            self.power,
+            self.geometry()
+                .period_start_ts(PeriodId::from((self.era, period)))?,
+            self.started_at,
+            self.ends_at,
+            self.max_stake_duration,
+        )
+    }
+}
+
+/// Number of lockups that a [CompactEscrowHistory] can record.
+pub const COMPACT_ESCROW_HISTORY_CHECKPOINTS: usize = 8;
+
+/// Stores the lockups of an `Escrow` over an era as checkpoints, from which its veToken balance
+/// at each period is computed.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompactEscrowHistory {
+    /// The `Escrow` being tracked.
+    pub escrow: [u8; 32],
+    /// The era.
+    pub era: u16,
+    /// Bump seed.
+    pub bump: u8,
+    /// Number of checkpoints that have been recorded.
+    pub num_checkpoints: u8,
+    /// Number of periods, starting from period 0, whose balances can no longer change.
+    pub materialized_periods: u16,
+    /// `0` if the lockup is tracked in the [LockerHistory], or `n` for the shard with index `n - 1`.
+    pub shard: u16,
+    /// [LockerHistory::params_version] when the escrow was last synced.
+    pub params_version: u16,
+    /// [LockerHistory::max_stake_vote_multiplier] when the escrow was last synced.
+    pub max_stake_vote_multiplier: u8,
+    /// Padding.
+    pub _padding: [u8; 5],
+    /// Number of seconds after the end of the era before this history may be closed.
+    pub retention_seconds: u64,
+    /// Account that paid for this history.
+    pub payer: [u8; 32],
+    /// [LockerHistory::period_seconds] when the escrow was last synced.
+    pub period_seconds: u64,
+    /// [LockerHistory::origin_ts] when the escrow was last synced.
+    pub origin_ts: u64,
+    /// First period of each checkpoint.
+    pub checkpoint_periods: [u16; COMPACT_ESCROW_HISTORY_CHECKPOINTS],
+    /// Voting power at max lockup of each checkpoint.
+    pub checkpoint_powers: [u64; COMPACT_ESCROW_HISTORY_CHECKPOINTS],
+    /// `escrow_started_at` of each checkpoint.
+    pub checkpoint_started_at: [u64; COMPACT_ESCROW_HISTORY_CHECKPOINTS],
+    /// `escrow_ends_at` of each checkpoint.
+    pub checkpoint_ends_at: [u64; COMPACT_ESCROW_HISTORY_CHECKPOINTS],
+    /// [LockerHistory::max_stake_duration] of each checkpoint.
+    pub checkpoint_max_stake_durations: [u64; COMPACT_ESCROW_HISTORY_CHECKPOINTS],
+}
+
+// Safety: the struct is `repr(C)`, every field is `Pod`, and fields are ordered so that there is no padding.
+unsafe impl Zeroable for CompactEscrowHistory {}
+unsafe impl Pod for CompactEscrowHistory {}
+
+impl AccountData for CompactEscrowHistory {
+    const DISCRIMINATOR: [u8; DISCRIMINATOR_LEN] = [0x7d, 0xb4, 0x62, 0x6d, 0x57, 0x4d, 0xaf, 0x74];
+}
+
+impl CompactEscrowHistory {
+    /// The [EraGeometry] of the periods of this history.
+    pub fn geometry(&self) -> EraGeometry {
+        stored_geometry(self.period_seconds, self.origin_ts)
+    }
+
+    /// Calculates the veBalance of the `Escrow` at the given period, from the last checkpoint
+    /// starting at or before it, as the program does.
+    pub fn get_balance(&self, period: u8) -> Option<u64> {
+        let num_checkpoints =
+            usize::from(self.num_checkpoints).min(COMPACT_ESCROW_HISTORY_CHECKPOINTS);
+        let index = match (0..num_checkpoints)
+            .rev()
+            .find(|&index| self.checkpoint_periods[index] <= u16::from(period))
+        {
+            Some(index) => index,
+            None => return Some(0),
+        };
+        calculate_voter_power_for_period(
+            self.checkpoint_powers[index],
+            self.geometry()
+                .period_start_ts(PeriodId::from((self.era, period)))?,
+            self.checkpoint_started_at[index],
+            self.checkpoint_ends_at[index],
+            self.checkpoint_max_stake_durations[index],
+        )
+    }
+}
+
+/// Returns the [EraGeometry] stored in a history, where a `period_seconds` of `0` stands for the default.
+fn stored_geometry(period_seconds: u64, origin_ts: u64) -> EraGeometry {
+    if period_seconds == 0 {
+        EraGeometry::DEFAULT
+    } else {
+        EraGeometry {
+            period_seconds,
+            origin_ts,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
+mod tests {
+    use super::*;
+    use crate::{load, read, DecodeError};
+    use anchor_lang::Discriminator;
+    use std::mem::size_of;
+
+    fn serialize<T: Pod + Discriminator>(account: &T) -> Vec<u8> {
+        let mut data = T::discriminator().to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(account));
+        data
+    }
+
+    #[test]
+    fn test_layouts_match_program() {
+        assert_eq!(size_of::<LockerHistory>(), snapshots::LockerHistory::LEN);
+        assert_eq!(size_of::<EscrowHistory>(), snapshots::EscrowHistory::LEN);
+        assert_eq!(
+            LockerHistory::DISCRIMINATOR,
+            snapshots::LockerHistory::discriminator()
+        );
+        assert_eq!(
+            EscrowHistory::DISCRIMINATOR,
+            snapshots::EscrowHistory::discriminator()
+        );
+        assert_eq!(
+            size_of::<CompactEscrowHistory>(),
+            snapshots::CompactEscrowHistory::LEN
+        );
+        assert_eq!(
+            CompactEscrowHistory::DISCRIMINATOR,
+            snapshots::CompactEscrowHistory::discriminator()
+        );
+    }
+
+    #[test]
+    fn test_decode_locker_history() {
+        let mut history = snapshots::LockerHistory {
+            locker: anchor_lang::prelude::Pubkey::new_unique(),
+            era: 3,
+            materialized_periods: 2,
+            bias: u128::from(u64::MAX) * 7,
+            pending_resyncs: 9,
+            payer: anchor_lang::prelude::Pubkey::new_unique(),
+            period_seconds: 86_400 * 7,
+            origin_ts: 1_000,
+            ..Default::default()
+        };
+        history.ve_balances[1] = 42;
+        history.merged_shards[255] = 4;
+        let data = serialize(&history);
+
+        let decoded: LockerHistory = read(&data).unwrap();
+        assert_eq!(decoded.locker, history.locker.to_bytes());
+        assert_eq!(decoded.era, 3);
+        assert_eq!(decoded.materialized_periods, 2);
+        assert_eq!(decoded.bias, history.bias);
+        assert_eq!(decoded.pending_resyncs, 9);
+        assert_eq!(decoded.ve_balances[1], 42);
+        assert_eq!(decoded.merged_shards[255], 4);
+        assert_eq!(decoded.payer, history.payer.to_bytes());
+        assert_eq!(decoded.period_seconds, 86_400 * 7);
+        assert_eq!(decoded.origin_ts, 1_000);
+        assert!(decoded.is_period_final(1));
+        assert!(!decoded.is_period_final(2));
+
+        // unaligned data can still be read
+        let mut unaligned = vec![0];
+        unaligned.extend_from_slice(&data);
+        assert_eq!(read::<LockerHistory>(&unaligned[1..]).unwrap(), decoded);
+    }
+
+    #[test]
+    fn test_decode_escrow_history() {
+        let mut history = snapshots::EscrowHistory {
+            escrow: anchor_lang::prelude::Pubkey::new_unique(),
+            era: 1,
+            power: 100,
+            ends_at: 1_000,
+            shard: 2,
+            max_stake_duration: 5,
+            period_seconds: 86_400,
+            ..Default::default()
+        };
+        history.ve_balances[7] = 11;
+        let data = serialize(&history);
+
+        let decoded: EscrowHistory = read(&data).unwrap();
+        assert_eq!(decoded.escrow, history.escrow.to_bytes());
+        assert_eq!(decoded.era, 1);
+        assert_eq!(decoded.power, 100);
+        assert_eq!(decoded.ends_at, 1_000);
+        assert_eq!(decoded.shard, 2);
+        assert_eq!(decoded.max_stake_duration, 5);
+        assert_eq!(decoded.period_seconds, 86_400);
+        assert_eq!(decoded.ve_balances[7], 11);
+    }
+
+    #[test]
+    fn test_calculate_ve_balance_matches_program() {
+        let mut history = snapshots::EscrowHistory {
+            era: 2,
+            power: 1_000_000,
+            started_at: 1_600_000_000,
+            ends_at: 1_800_000_000,
+            materialized_periods: 3,
+            max_stake_duration: 150_000_000,
+            ..Default::default()
+        };
+        history.ve_balances[1] = 77;
+        let decoded: EscrowHistory = read(&serialize(&history)).unwrap();
+        for period in 0..=u8::MAX {
+            assert_eq!(
+                decoded.calculate_ve_balance(period),
+                history.calculate_ve_balance(period),
+                "period {}",
+                period
+            );
+        }
+        assert_eq!(decoded.calculate_ve_balance(1), Some(77));
+        assert_ne!(decoded.calculate_ve_balance(3), Some(0));
+    }
+
+    #[test]
+    fn test_get_balance_matches_program() {
+        let mut history = snapshots::CompactEscrowHistory {
+            era: 2,
+            num_checkpoints: 2,
+            materialized_periods: 40,
+            period_seconds: 86_400,
+            origin_ts: 1_500_000_000,
+            ..Default::default()
+        };
+        history.checkpoint_periods[..2].copy_from_slice(&[3, 30]);
+        history.checkpoint_powers[..2].copy_from_slice(&[1_000_000, 3_000_000]);
+        history.checkpoint_started_at[..2].copy_from_slice(&[1_500_000_000, 1_500_000_000]);
+        history.checkpoint_ends_at[..2].copy_from_slice(&[1_600_000_000, 1_700_000_000]);
+        history.checkpoint_max_stake_durations[..2].copy_from_slice(&[150_000_000, 150_000_000]);
+        let decoded: CompactEscrowHistory = read(&serialize(&history)).unwrap();
+        assert_eq!(decoded.escrow, history.escrow.to_bytes());
+        assert_eq!(decoded.num_checkpoints, 2);
+        for period in 0..=u8::MAX {
+            assert_eq!(
+                decoded.get_balance(period),
+                history.get_balance(period),
+                "period {}",
+                period
+            );
+        }
+        assert_eq!(decoded.get_balance(2), Some(0));
+        assert_ne!(decoded.get_balance(3), Some(0));
+        assert!(decoded.get_balance(30) > decoded.get_balance(29));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let data = serialize(&snapshots::EscrowHistory::default());
+        assert_eq!(
+            read::<LockerHistory>(&data).unwrap_err(),
+            DecodeError::AccountTooSmall
+        );
+        assert_eq!(
+            read::<EscrowHistory>(&data[..data.len() - 1]).unwrap_err(),
+            DecodeError::AccountTooSmall
+        );
+
+        let mut data = data;
+        data[0] ^= 1;
+        assert_eq!(
+            read::<EscrowHistory>(&data).unwrap_err(),
+            DecodeError::InvalidDiscriminator
+        );
+    }
+
+    #[test]
+    fn test_load_aligned() {
+        let history = snapshots::EscrowHistory {
+            era: 5,
+            ..Default::default()
+        };
+        let data = serialize(&history);
+        // an EscrowHistory has no u128 fields, so 8 bytes into an aligned buffer is aligned for it
+        let mut aligned = vec![0_u128; data.len() / 16 + 1];
+        bytemuck::cast_slice_mut::<u128, u8>(&mut aligned)[..data.len()].copy_from_slice(&data);
+        let bytes = bytemuck::cast_slice::<u128, u8>(&aligned);
+        assert_eq!(load::<EscrowHistory>(bytes).unwrap().era, 5);
+        assert_eq!(
+            load::<EscrowHistory>(&bytes[8..]).unwrap_err(),
+            DecodeError::InvalidDiscriminator
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Splitting an airdrop between claimants in proportion to their veToken balances.
+
+use crate::merkle::{compute_leaf, MerkleTree, Node};
+use anchor_lang::prelude::Pubkey;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Errors returned when building a [Distribution].
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum DistributionError {
+    /// The locker total is zero, so there is nothing to weigh the claims by.
+    #[error("locker balance is zero")]
+    ZeroLockerBalance,
+    /// The balances of the claimants sum up to more than the locker total.
+    #[error("claimant balances exceed the locker balance")]
+    BalancesExceedLocker,
+    /// No claimant receives any tokens.
+    #[error("no claimant receives tokens")]
+    NoClaims,
+    /// An amount does not fit in a `u64`.
+    #[error("amount overflow")]
+    Overflow,
+}
+
+/// A claimant and the veToken balance of their escrow at the snapshot period.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Balance {
+    /// Account that may claim the tokens, i.e. the owner of the escrow.
+    pub claimant: Pubkey,
+    /// veToken balance at the period.
+    pub ve_balance: u64,
+}
+
+/// A claim in a [Distribution].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Claim {
+    /// Index of the claim in the distribution.
+    pub index: u64,
+    /// Account that may claim the tokens.
+    pub claimant: Pubkey,
+    /// veToken balance the claim is weighted by.
+    pub ve_balance: u64,
+    /// Number of tokens that may be claimed.
+    pub amount: u64,
+    /// Proof that the claim is part of the [Distribution::root].
+    pub proof: Vec<Node>,
+}
+
+/// Reconciles the amount distributed with the amount of the airdrop.
+///
+/// `total_amount == distributed_amount + unsynced_amount + rounding_dust`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Number of tokens being airdropped.
+    pub total_amount: u64,
+    /// Sum of the amounts of all claims.
+    pub distributed_amount: u64,
+    /// veToken total of the locker at the period, used as the denominator of every share.
+    pub locker_ve_balance: u64,
+    /// Sum of the veToken balances of all claimants.
+    pub claimants_ve_balance: u64,
+    /// Tokens corresponding to veTokens in the locker total that do not belong to any claimant,
+    /// such as escrows that were left out or per-lockup rounding of the total.
+    pub unsynced_amount: u64,
+    /// Tokens left over from rounding each claim down.
+    pub rounding_dust: u64,
+}
+
+/// A Merkle airdrop weighted by veToken balances.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Distribution {
+    /// Root of the Merkle tree of the claims.
+    pub root: Node,
+    /// Claims, ordered by index.
+    pub claims: Vec<Claim>,
+    /// Reconciliation of the distributed amounts.
+    pub report: Report,
+}
+
+/// Computes `amount * numerator / denominator`, rounded down.
+fn calculate_share(amount: u64, numerator: u64, denominator: u64) -> Option<u64> {
+    u128::from(amount)
+        .checked_mul(numerator.into())?
+        .checked_div(denominator.into())?
+        .try_into()
+        .ok()
+}
+
+/// Splits `total_amount` tokens between the claimants in proportion to their share of the
+/// `locker_ve_balance`.
+///
+/// Balances of the same claimant are combined. Claimants are indexed in the order of their
+/// base58 address, and claimants whose share rounds down to zero are left out.
+pub fn build_distribution(
+    balances: &[Balance],
+    locker_ve_balance: u64,
+    total_amount: u64,
+) -> Result<Distribution, DistributionError> {
+    if locker_ve_balance == 0 {
+        return Err(DistributionError::ZeroLockerBalance);
+    }
+    let mut claimants: BTreeMap<String, (Pubkey, u64)> = BTreeMap::new();
+    for balance in balances {
+        let (_, ve_balance) = claimants
+            .entry(balance.claimant.to_string())
+            .or_insert((balance.claimant, 0));
+        *ve_balance = ve_balance
+            .checked_add(balance.ve_balance)
+            .ok_or(DistributionError::Overflow)?;
+    }
+    let claimants_ve_balance = claimants
+        .values()
+        .try_fold(0_u64, |sum, (_, ve_balance)| sum.checked_add(*ve_balance))
+        .ok_or(DistributionError::Overflow)?;
+    let unsynced_ve_balance = locker_ve_balance
+        .checked_sub(claimants_ve_balance)
+        .ok_or(DistributionError::BalancesExceedLocker)?;
+
+    let mut claims = vec![];
+    for (claimant, ve_balance) in claimants.into_values() {
+        let amount = calculate_share(total_amount, ve_balance, locker_ve_balance)
+            .ok_or(DistributionError::Overflow)?;
+        if amount == 0 {
+            continue;
+        }
+        claims.push(Claim {
+            index: claims.len() as u64,
+            claimant,
+            ve_balance,
+            amount,
+            proof: vec![],
+        });
+    }
+
+    let tree = MerkleTree::new(
+        claims
+            .iter()
+            .map(|claim| compute_leaf(claim.index, &claim.claimant, claim.amount))
+            .collect(),
+    );
+    let root = tree.root().ok_or(DistributionError::NoClaims)?;
+    for claim in claims.iter_mut() {
+        claim.proof = tree
+            .proof(&compute_leaf(claim.index, &claim.claimant, claim.amount))
+            .ok_or(DistributionError::NoClaims)?;
+    }
+
+    let distributed_amount = claims
+        .iter()
+        .try_fold(0_u64, |sum, claim| sum.checked_add(claim.amount))
+        .ok_or(DistributionError::Overflow)?;
+    let unsynced_amount = calculate_share(total_amount, unsynced_ve_balance, locker_ve_balance)
+        .ok_or(DistributionError::Overflow)?;
+    let rounding_dust = total_amount
+        .checked_sub(distributed_amount)
+        .and_then(|dust| dust.checked_sub(unsynced_amount))
+        .ok_or(DistributionError::Overflow)?;
+
+    Ok(Distribution {
+        root,
+        claims,
+        report: Report {
+            total_amount,
+            distributed_amount,
+            locker_ve_balance,
+            claimants_ve_balance,
+            unsynced_amount,
+            rounding_dust,
+        },
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
+mod tests {
+    use super::*;
+    use crate::merkle::verify_proof;
+
+    #[test]
+    fn test_build_distribution() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let c = Pubkey::new_unique();
+        let balances = [
+            Balance {
+                claimant: a,
+                ve_balance: 100,
+            },
+            Balance {
+                claimant: b,
+                ve_balance: 200,
+            },
+            Balance {
+                claimant: a,
+                ve_balance: 33,
+            },
+            Balance {
+                claimant: c,
+                ve_balance: 1,
+            },
+        ];
+        let distribution = build_distribution(&balances, 1_000, 1_000_000).unwrap();
+
+        let amounts: BTreeMap<Pubkey, u64> = distribution
+            .claims
+            .iter()
+            .map(|claim| (claim.claimant, claim.amount))
+            .collect();
+        assert_eq!(amounts[&a], 133_000);
+        assert_eq!(amounts[&b], 200_000);
+        assert_eq!(amounts[&c], 1_000);
+        assert_eq!(
+            distribution.report,
+            Report {
+                total_amount: 1_000_000,
+                distributed_amount: 334_000,
+                locker_ve_balance: 1_000,
+                claimants_ve_balance: 334,
+                unsynced_amount: 666_000,
+                rounding_dust: 0,
+            }
+        );
+        for (i, claim) in distribution.claims.iter().enumerate() {
+            assert_eq!(claim.index, i as u64);
+            let leaf = compute_leaf(claim.index, &claim.claimant, claim.amount);
+            assert!(verify_proof(&claim.proof, &distribution.root, &leaf));
+        }
+    }
+
+    #[test]
+    fn test_rounding_dust() {
+        let balances: Vec<Balance> = (0..3)
+            .map(|_| Balance {
+                claimant: Pubkey::new_unique(),
+                ve_balance: 1,
+            })
+            .collect();
+        let distribution = build_distribution(&balances, 3, 100).unwrap();
+        assert!(distribution.claims.iter().all(|claim| claim.amount == 33));
+        assert_eq!(distribution.report.distributed_amount, 99);
+        assert_eq!(distribution.report.unsynced_amount, 0);
+        assert_eq!(distribution.report.rounding_dust, 1);
+
+        // shares that round down to zero are left out
+        let big = Balance {
+            claimant: Pubkey::new_unique(),
+            ve_balance: 2,
+        };
+        let distribution = build_distribution(&[balances[0], big], 3, 2).unwrap();
+        assert_eq!(distribution.claims.len(), 1);
+        assert_eq!(distribution.claims[0].claimant, big.claimant);
+        assert_eq!(distribution.report.distributed_amount, 1);
+        assert_eq!(distribution.report.rounding_dust, 1);
+    }
+
+    #[test]
+    fn test_invalid_distributions() {
+        let balances = [Balance {
+            claimant: Pubkey::new_unique(),
+            ve_balance: 10,
+        }];
+        assert_eq!(
+            build_distribution(&balances, 0, 100),
+            Err(DistributionError::ZeroLockerBalance)
+        );
+        assert_eq!(
+            build_distribution(&balances, 9, 100),
+            Err(DistributionError::BalancesExceedLocker)
+        );
+        assert_eq!(
+            build_distribution(&balances, 1_000, 10),
+            Err(DistributionError::NoClaims)
+        );
+    }
+}
@@ -0,0 +1,15 @@
+//! Merkle airdrops weighted by voting escrow snapshot balances.
+//!
+//! Given the veToken balance of each claimant at a period and the `LockerHistory` total of that
+//! period, this crate splits an amount of tokens in proportion to the balances and builds a Merkle
+//! tree in the format of the [Saber merkle-distributor](https://github.com/saber-hq/merkle-distributor).
+#![deny(rustdoc::all)]
+#![allow(rustdoc::missing_doc_code_examples)]
+#![deny(clippy::unwrap_used, clippy::integer_arithmetic)]
+#![deny(missing_docs)]
+
+mod distribution;
+mod merkle;
+
+pub use distribution::*;
+pub use merkle::*;
@@ -0,0 +1,119 @@
+//! Merkle trees of claims.
+//!
+//! Trees are built like the Uniswap and Saber merkle distributors: leaves are sorted, pairs of
+//! nodes are sorted before they are hashed, and the last node of a layer with an odd number of
+//! nodes is carried up to the next layer. A proof can therefore be verified without knowing the
+//! position of the leaf.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::keccak::hashv;
+
+/// A node of a [MerkleTree].
+pub type Node = [u8; 32];
+
+/// Computes the leaf of a claim of `amount` tokens by `claimant`.
+pub fn compute_leaf(index: u64, claimant: &Pubkey, amount: u64) -> Node {
+    hashv(&[
+        &index.to_le_bytes(),
+        &claimant.to_bytes(),
+        &amount.to_le_bytes(),
+    ])
+    .0
+}
+
+/// Hashes two nodes into their parent.
+fn hash_pair(a: &Node, b: &Node) -> Node {
+    if a <= b {
+        hashv(&[a, b]).0
+    } else {
+        hashv(&[b, a]).0
+    }
+}
+
+/// Returns true if the proof shows that the leaf is part of the tree with the given root.
+pub fn verify_proof(proof: &[Node], root: &Node, leaf: &Node) -> bool {
+    let computed = proof
+        .iter()
+        .fold(*leaf, |node, sibling| hash_pair(&node, sibling));
+    computed == *root
+}
+
+/// A Merkle tree of claims.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTree {
+    /// Layers of the tree, from the sorted leaves to the root.
+    layers: Vec<Vec<Node>>,
+}
+
+impl MerkleTree {
+    /// Builds a tree from its leaves. Duplicate leaves are removed.
+    pub fn new(mut leaves: Vec<Node>) -> Self {
+        leaves.sort_unstable();
+        leaves.dedup();
+        let mut layers = vec![leaves];
+        while let Some(layer) = layers.last().filter(|layer| layer.len() > 1) {
+            let next = layer
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+        Self { layers }
+    }
+
+    /// The root of the tree, or [None] if the tree has no leaves.
+    pub fn root(&self) -> Option<Node> {
+        self.layers.last()?.first().copied()
+    }
+
+    /// Computes the proof that a leaf is part of the tree, or [None] if it is not.
+    pub fn proof(&self, leaf: &Node) -> Option<Vec<Node>> {
+        let mut index = self.layers.first()?.binary_search(leaf).ok()?;
+        let mut proof = vec![];
+        for layer in &self.layers {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index = index.checked_div(2)?;
+        }
+        Some(proof)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proofs_verify() {
+        for num_leaves in 1..20_u64 {
+            let leaves: Vec<Node> = (0..num_leaves)
+                .map(|i| compute_leaf(i, &Pubkey::new_unique(), i * 100))
+                .collect();
+            let tree = MerkleTree::new(leaves.clone());
+            let root = tree.root().unwrap();
+            for leaf in &leaves {
+                let proof = tree.proof(leaf).unwrap();
+                assert!(verify_proof(&proof, &root, leaf));
+                assert!(!verify_proof(&proof, &root, &[0; 32]));
+            }
+        }
+        assert_eq!(MerkleTree::new(vec![]).root(), None);
+        assert_eq!(MerkleTree::new(vec![[1; 32]]).root(), Some([1; 32]));
+    }
+
+    #[test]
+    fn test_two_leaves() {
+        let a = compute_leaf(0, &Pubkey::new_unique(), 1);
+        let b = compute_leaf(1, &Pubkey::new_unique(), 2);
+        let tree = MerkleTree::new(vec![a, b]);
+        assert_eq!(tree.root(), Some(hash_pair(&a, &b)));
+        assert_eq!(tree.proof(&a), Some(vec![b]));
+        assert_eq!(tree.proof(&[0; 32]), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Generation of Merkle airdrops from a snapshot period.
+
+use crate::dump::Dump;
+use crate::table::{build_table, Kind};
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Map, Value};
+use snapshots_airdrop::{build_distribution, Balance, Distribution};
+
+/// Splits `amount` tokens between the owners of the escrows of a locker
+/// in proportion to their balance at a period.
+pub fn build_airdrop(
+    dump: &Dump,
+    locker: Pubkey,
+    era: u16,
+    period: u8,
+    amount: u64,
+    allow_non_final: bool,
+) -> Result<Distribution> {
+    let table = build_table(dump, Some(locker), Some(era))?;
+    if table.unknown_escrows > 0 {
+        bail!(
+            "{} escrow histories have no escrow account in the dumps",
+            table.unknown_escrows
+        );
+    }
+    let rows = table.rows.iter().filter(|row| row.period == period);
+    let locker_row = rows
+        .clone()
+        .find(|row| row.kind == Kind::Locker)
+        .ok_or_else(|| {
+            anyhow!(
+                "locker history of era {} has not materialized period {}",
+                era,
+                period
+            )
+        })?;
+    if !locker_row.is_final && !allow_non_final {
+        bail!("period {} of era {} is not final", period, era);
+    }
+    let balances = rows
+        .filter(|row| row.kind == Kind::Escrow)
+        .map(|row| {
+            Ok(Balance {
+                claimant: row.owner.ok_or_else(|| anyhow!("unknown owner"))?,
+                ve_balance: row.ve_balance,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(build_distribution(
+        &balances,
+        locker_row.ve_balance,
+        amount,
+    )?)
+}
+
+/// Serializes a distribution, along with the arguments needed to create its merkle distributor.
+pub fn distribution_to_json(
+    locker: Pubkey,
+    era: u16,
+    period: u8,
+    distribution: &Distribution,
+) -> Value {
+    let claims: Map<String, Value> = distribution
+        .claims
+        .iter()
+        .map(|claim| {
+            (
+                claim.claimant.to_string(),
+                json!({
+                    "index": claim.index,
+                    "amount": claim.amount.to_string(),
+                    "veBalance": claim.ve_balance.to_string(),
+                    "proof": claim.proof.iter().map(hex::encode).collect::<Vec<_>>(),
+                }),
+            )
+        })
+        .collect();
+    let report = &distribution.report;
+    json!({
+        "locker": locker.to_string(),
+        "era": era,
+        "period": period,
+        "merkleRoot": hex::encode(distribution.root),
+        "maxTotalClaim": report.distributed_amount.to_string(),
+        "maxNumNodes": distribution.claims.len(),
+        "claims": claims,
+        "report": {
+            "totalAmount": report.total_amount.to_string(),
+            "distributedAmount": report.distributed_amount.to_string(),
+            "lockerVeBalance": report.locker_ve_balance.to_string(),
+            "claimantsVeBalance": report.claimants_ve_balance.to_string(),
+            "unsyncedAmount": report.unsynced_amount.to_string(),
+            "roundingDust": report.rounding_dust.to_string(),
+        },
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
+mod tests {
+    use super::*;
+    use locked_voter::Escrow;
+    use snapshots::{calculate_era_start_ts, EscrowHistory, LockerHistory, PERIOD_SECONDS};
+    use snapshots_airdrop::{compute_leaf, verify_proof};
+
+    #[test]
+    fn test_build_airdrop() {
+        let locker = Pubkey::new_unique();
+        let era_start = calculate_era_start_ts(1).unwrap();
+        let period_seconds = u64::from(PERIOD_SECONDS);
+
+        let mut dump = Dump::default();
+        let mut locker_history = LockerHistory {
+            locker,
+            era: 1,
+            materialized_periods: 1,
+            ..Default::default()
+        };
+        locker_history.ve_balances[0] = 400;
+        dump.locker_histories
+            .insert(Pubkey::new_unique(), locker_history);
+        for power in [100, 300] {
+            let escrow = Pubkey::new_unique();
+            dump.escrows.insert(
+                escrow,
+                Escrow {
+                    locker,
+                    owner: Pubkey::new_unique(),
+                    ..Default::default()
+                },
+            );
+            dump.escrow_histories.insert(
+                Pubkey::new_unique(),
+                EscrowHistory {
+                    escrow,
+                    era: 1,
+                    power,
+                    started_at: era_start,
+                    ends_at: era_start + period_seconds,
+                    max_stake_duration: period_seconds,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let distribution = build_airdrop(&dump, locker, 1, 0, 1_000, false).unwrap();
+        let mut amounts: Vec<u64> = distribution
+            .claims
+            .iter()
+            .map(|claim| claim.amount)
+            .collect();
+        amounts.sort_unstable();
+        assert_eq!(amounts, vec![250, 750]);
+        for claim in &distribution.claims {
+            let leaf = compute_leaf(claim.index, &claim.claimant, claim.amount);
+            assert!(verify_proof(&claim.proof, &distribution.root, &leaf));
+        }
+        let json = distribution_to_json(locker, 1, 0, &distribution);
+        assert_eq!(json["maxTotalClaim"], "1000");
+        assert_eq!(json["maxNumNodes"], 2);
+
+        // period 1 has not been materialized
+        assert!(build_airdrop(&dump, locker, 1, 1, 1_000, true).is_err());
+
+        // period 0 is not final while it is being resynced
+        let locker_history = dump.locker_histories.values_mut().next().unwrap();
+        locker_history.resyncing = 1;
+        assert!(build_airdrop(&dump, locker, 1, 0, 1_000, false).is_err());
+        assert!(build_airdrop(&dump, locker, 1, 0, 1_000, true).is_ok());
+    }
+}
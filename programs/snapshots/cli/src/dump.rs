@@ -0,0 +1,274 @@
+//! Loading of dumped accounts.
+
+use anchor_lang::prelude::*;
+use anchor_lang::{Discriminator, ZeroCopy};
+use anyhow::{anyhow, bail, Context, Result};
+use locked_voter::Escrow;
+use serde::Deserialize;
+use snapshots::{CompactEscrowHistory, EraGeometry, EscrowHistory, LockerHistory};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::mem::size_of;
+use std::path::Path;
+use std::str::FromStr;
+
+/// An account as serialized by `solana account --output json` and the JSON RPC.
+#[derive(Deserialize)]
+struct KeyedAccount {
+    pubkey: String,
+    account: UiAccount,
+}
+
+/// Account contents, with the data encoded as `[data, encoding]`.
+#[derive(Deserialize)]
+struct UiAccount {
+    data: (String, String),
+    owner: String,
+}
+
+/// Contents of a JSON dump.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonDump {
+    /// A single account.
+    Account(KeyedAccount),
+    /// A list of accounts.
+    Accounts(Vec<KeyedAccount>),
+    /// A `getProgramAccounts` response.
+    Response { result: Vec<KeyedAccount> },
+}
+
+/// The snapshots and escrow accounts found in a set of dumps.
+#[derive(Default)]
+pub struct Dump {
+    /// [LockerHistory] accounts by address.
+    pub locker_histories: BTreeMap<Pubkey, LockerHistory>,
+    /// [EscrowHistory] accounts by address.
+    pub escrow_histories: BTreeMap<Pubkey, EscrowHistory>,
+    /// [CompactEscrowHistory] accounts by address.
+    pub compact_escrow_histories: BTreeMap<Pubkey, CompactEscrowHistory>,
+    /// [Escrow] accounts by address.
+    pub escrows: BTreeMap<Pubkey, Escrow>,
+}
+
+/// An [EscrowHistory] or a [CompactEscrowHistory] of a [Dump].
+#[derive(Clone, Copy)]
+pub enum AnyEscrowHistory<'a> {
+    /// An [EscrowHistory].
+    Full(&'a EscrowHistory),
+    /// A [CompactEscrowHistory].
+    Compact(&'a CompactEscrowHistory),
+}
+
+impl AnyEscrowHistory<'_> {
+    /// The [Escrow] being tracked.
+    pub fn escrow(&self) -> Pubkey {
+        match self {
+            Self::Full(history) => history.escrow,
+            Self::Compact(history) => history.escrow,
+        }
+    }
+
+    /// The era.
+    pub fn era(&self) -> u16 {
+        match self {
+            Self::Full(history) => history.era,
+            Self::Compact(history) => history.era,
+        }
+    }
+
+    /// The [EraGeometry] of the periods of the history.
+    pub fn geometry(&self) -> EraGeometry {
+        match self {
+            Self::Full(history) => history.geometry(),
+            Self::Compact(history) => history.geometry(),
+        }
+    }
+
+    /// Calculates the veBalance of the [Escrow] at the given period.
+    pub fn calculate_ve_balance(&self, period: u8) -> Option<u64> {
+        match self {
+            Self::Full(history) => history.calculate_ve_balance(period),
+            Self::Compact(history) => history.get_balance(period),
+        }
+    }
+}
+
+/// Decodes a zero copy account if the data starts with its discriminator.
+fn decode_zero_copy<T: ZeroCopy>(data: &[u8]) -> Option<T> {
+    let account = data.get(8..)?.get(..size_of::<T>())?;
+    if data[..8] != T::discriminator() {
+        return None;
+    }
+    Some(bytemuck::pod_read_unaligned(account))
+}
+
+impl Dump {
+    /// Every [EscrowHistory] and [CompactEscrowHistory] of the dump.
+    pub fn all_escrow_histories(&self) -> impl Iterator<Item = AnyEscrowHistory<'_>> {
+        self.escrow_histories
+            .values()
+            .map(AnyEscrowHistory::Full)
+            .chain(
+                self.compact_escrow_histories
+                    .values()
+                    .map(AnyEscrowHistory::Compact),
+            )
+    }
+
+    /// Loads a dump file, or every file in a directory.
+    pub fn load_path(&mut self, path: &Path) -> Result<()> {
+        if !path.is_dir() {
+            return self
+                .load_file(path)
+                .with_context(|| format!("failed to load {}", path.display()));
+        }
+        let mut entries = fs::read_dir(path)?
+            .map(|entry| Ok(entry?.path()))
+            .collect::<Result<Vec<_>>>()?;
+        entries.sort();
+        for entry in entries.iter().filter(|entry| entry.is_file()) {
+            self.load_file(entry)
+                .with_context(|| format!("failed to load {}", entry.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Loads a JSON dump, or raw account data from a file named after the address of the account.
+    fn load_file(&mut self, path: &Path) -> Result<()> {
+        let contents = fs::read(path)?;
+        if path.extension() == Some(OsStr::new("json")) {
+            return self.load_json(&contents);
+        }
+        let stem = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow!("file name is not an address"))?;
+        let address = Pubkey::from_str(stem).map_err(|_| anyhow!("invalid address {}", stem))?;
+        self.insert(address, None, &contents)
+    }
+
+    /// Loads the accounts of a JSON dump.
+    pub fn load_json(&mut self, contents: &[u8]) -> Result<()> {
+        let accounts = match serde_json::from_slice(contents)? {
+            JsonDump::Account(account) => vec![account],
+            JsonDump::Accounts(accounts) | JsonDump::Response { result: accounts } => accounts,
+        };
+        for KeyedAccount { pubkey, account } in accounts {
+            let address =
+                Pubkey::from_str(&pubkey).map_err(|_| anyhow!("invalid address {}", pubkey))?;
+            let owner = Pubkey::from_str(&account.owner)
+                .map_err(|_| anyhow!("invalid owner {}", account.owner))?;
+            let (data, encoding) = account.data;
+            if encoding != "base64" {
+                bail!("unsupported encoding {} of {}", encoding, address);
+            }
+            self.insert(address, Some(owner), &base64::decode(data)?)?;
+        }
+        Ok(())
+    }
+
+    /// Decodes an account and adds it to the dump. Accounts of other types are ignored.
+    pub fn insert(&mut self, address: Pubkey, owner: Option<Pubkey>, data: &[u8]) -> Result<()> {
+        let check_owner = |program: Pubkey| match owner {
+            Some(owner) if owner != program => Err(anyhow!(
+                "{} is owned by {} instead of {}",
+                address,
+                owner,
+                program
+            )),
+            _ => Ok(()),
+        };
+        if let Some(history) = decode_zero_copy::<LockerHistory>(data) {
+            check_owner(snapshots::ID)?;
+            self.locker_histories.insert(address, history);
+        } else if let Some(history) = decode_zero_copy::<EscrowHistory>(data) {
+            check_owner(snapshots::ID)?;
+            self.escrow_histories.insert(address, history);
+        } else if let Some(history) = decode_zero_copy::<CompactEscrowHistory>(data) {
+            check_owner(snapshots::ID)?;
+            self.compact_escrow_histories.insert(address, history);
+        } else if data.starts_with(&Escrow::discriminator()) {
+            check_owner(locked_voter::ID)?;
+            self.escrows
+                .insert(address, Escrow::try_deserialize(&mut &data[..])?);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn keyed_account_json(address: Pubkey, owner: Pubkey, data: &[u8]) -> String {
+        format!(
+            r#"{{"pubkey":"{}","account":{{"lamports":1,"data":["{}","base64"],"owner":"{}","executable":false,"rentEpoch":0}}}}"#,
+            address,
+            base64::encode(data),
+            owner
+        )
+    }
+
+    #[test]
+    fn test_load_json() {
+        let escrow_key = Pubkey::new_unique();
+        let escrow = Escrow {
+            locker: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            ..Default::default()
+        };
+        let mut escrow_data = vec![];
+        escrow.try_serialize(&mut escrow_data).unwrap();
+
+        let history_key = Pubkey::new_unique();
+        let history = EscrowHistory {
+            escrow: escrow_key,
+            era: 2,
+            ..Default::default()
+        };
+        let mut history_data = EscrowHistory::discriminator().to_vec();
+        history_data.extend_from_slice(bytemuck::bytes_of(&history));
+
+        let compact_key = Pubkey::new_unique();
+        let compact = CompactEscrowHistory {
+            escrow: escrow_key,
+            era: 3,
+            num_checkpoints: 1,
+            ..Default::default()
+        };
+        let mut compact_data = CompactEscrowHistory::discriminator().to_vec();
+        compact_data.extend_from_slice(bytemuck::bytes_of(&compact));
+
+        let mut dump = Dump::default();
+        let json = format!(
+            r#"{{"jsonrpc":"2.0","result":[{},{},{}],"id":1}}"#,
+            keyed_account_json(escrow_key, locked_voter::ID, &escrow_data),
+            keyed_account_json(history_key, snapshots::ID, &history_data),
+            keyed_account_json(compact_key, snapshots::ID, &compact_data),
+        );
+        dump.load_json(json.as_bytes()).unwrap();
+        assert_eq!(dump.escrows[&escrow_key].owner, escrow.owner);
+        assert_eq!(dump.escrow_histories[&history_key], history);
+        assert_eq!(dump.compact_escrow_histories[&compact_key], compact);
+        assert_eq!(
+            dump.all_escrow_histories()
+                .map(|history| history.era())
+                .collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert!(dump.locker_histories.is_empty());
+
+        // the owner must match the program of the account
+        let json = keyed_account_json(history_key, locked_voter::ID, &history_data);
+        assert!(Dump::default().load_json(json.as_bytes()).is_err());
+
+        // unrelated accounts are ignored
+        let json = keyed_account_json(history_key, snapshots::ID, &[0; 16]);
+        let mut dump = Dump::default();
+        dump.load_json(json.as_bytes()).unwrap();
+        assert!(dump.escrow_histories.is_empty());
+    }
+}
@@ -0,0 +1,82 @@
+//! Writing the balance table in the supported formats.
+
+use crate::table::BalanceRow;
+use anchor_lang::prelude::Pubkey;
+use anyhow::{bail, Result};
+use std::io::Write;
+use std::path::Path;
+
+/// Output format of an export.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Comma separated values with a header row.
+    Csv,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+impl Format {
+    /// Parses a format name.
+    pub fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "csv" => Format::Csv,
+            "jsonl" => Format::JsonLines,
+            _ => bail!("unknown format {}", name),
+        })
+    }
+
+    /// Determines the format from the extension of an output path.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::parse(path.extension()?.to_str()?).ok()
+    }
+}
+
+/// Names of the columns of the balance table.
+const COLUMNS: [&str; 9] = [
+    "kind",
+    "locker",
+    "escrow",
+    "owner",
+    "era",
+    "period",
+    "period_start_ts",
+    "ve_balance",
+    "is_final",
+];
+
+/// Writes the rows in the given format.
+pub fn write_rows<W: Write>(writer: &mut W, rows: &[BalanceRow], format: Format) -> Result<()> {
+    match format {
+        Format::Csv => write_csv(writer, rows),
+        Format::JsonLines => write_json_lines(writer, rows),
+    }
+}
+
+fn write_csv<W: Write>(writer: &mut W, rows: &[BalanceRow]) -> Result<()> {
+    let key = |key: Option<Pubkey>| key.map(|key| key.to_string()).unwrap_or_default();
+    writeln!(writer, "{}", COLUMNS.join(","))?;
+    for row in rows {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{}",
+            row.kind.as_str(),
+            key(row.locker),
+            key(row.escrow),
+            key(row.owner),
+            row.era,
+            row.period,
+            row.period_start_ts,
+            row.ve_balance,
+            row.is_final
+        )?;
+    }
+    Ok(())
+}
+
+fn write_json_lines<W: Write>(writer: &mut W, rows: &[BalanceRow]) -> Result<()> {
+    for row in rows {
+        serde_json::to_writer(&mut *writer, row)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
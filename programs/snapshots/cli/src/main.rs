@@ -0,0 +1,213 @@
+//! Command line tools for voting escrow snapshots.
+//!
+//! ```text
+//! snapshots-cli export [--locker <LOCKER>] [--era <ERA>] [--format csv|jsonl] [--output <FILE>] <PATH>...
+//! snapshots-cli airdrop --locker <LOCKER> --era <ERA> --period <PERIOD> --amount <AMOUNT> [--allow-non-final] [--output <FILE>] <PATH>...
+//! ```
+//!
+//! `export` decodes every [snapshots::LockerHistory], [snapshots::EscrowHistory] and
+//! [snapshots::CompactEscrowHistory] in a set of dumped accounts and writes the balance of each
+//! period to a table.
+//!
+//! `airdrop` splits an amount of tokens between the escrow owners of a locker in proportion to
+//! their balance at a period, and writes the Merkle root, proofs and a reconciliation report.
+#![deny(rustdoc::all)]
+#![allow(rustdoc::missing_doc_code_examples)]
+#![deny(clippy::unwrap_used)]
+
+mod airdrop;
+mod dump;
+mod export;
+mod table;
+
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Context, Result};
+use dump::Dump;
+use export::{write_rows, Format};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const USAGE: &str = "\
+Usage: snapshots-cli export [OPTIONS] <PATH>...
+       snapshots-cli airdrop [OPTIONS] <PATH>...
+
+Each path is a `solana account --output json` file, a raw account data file named
+after its address, or a directory of such files.
+
+export: writes the balance of every period of the LockerHistory, EscrowHistory and
+CompactEscrowHistory accounts in the dumps.
+
+    --locker <LOCKER>    Only export balances of this locker
+    --era <ERA>          Only export balances of this era
+    --format <FORMAT>    csv or jsonl [default: from the output extension, or csv]
+    --output <FILE>      File to write to [default: stdout]
+
+airdrop: splits tokens between the escrow owners of a locker in proportion to their
+balance at a period, and writes a Merkle distribution as JSON. The dumps must contain
+the Escrow account of every EscrowHistory and CompactEscrowHistory.
+
+    --locker <LOCKER>    The locker
+    --era <ERA>          Era of the snapshot period
+    --period <PERIOD>    Period within the era
+    --amount <AMOUNT>    Number of tokens to distribute, in native units
+    --allow-non-final    Allow periods whose total may still change
+    --output <FILE>      File to write to [default: stdout]
+";
+
+/// Command line arguments: options with a value, flags, and paths.
+#[derive(Debug, Default)]
+struct Args {
+    options: BTreeMap<String, String>,
+    flags: Vec<String>,
+    inputs: Vec<PathBuf>,
+}
+
+impl Args {
+    /// Parses arguments, given the names of the options that are flags without a value.
+    fn parse(args: impl IntoIterator<Item = String>, flags: &[&str]) -> Result<Self> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if flags.contains(&arg.as_str()) {
+                parsed.flags.push(arg);
+            } else if arg.starts_with("--") {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow!("missing value for {}", arg))?;
+                parsed.options.insert(arg, value);
+            } else {
+                parsed.inputs.push(arg.into());
+            }
+        }
+        if parsed.inputs.is_empty() {
+            bail!("no dumps given");
+        }
+        Ok(parsed)
+    }
+
+    /// Removes an option and parses its value.
+    fn take<T: FromStr>(&mut self, name: &str) -> Result<Option<T>> {
+        self.options
+            .remove(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| anyhow!("invalid value {} for {}", value, name))
+            })
+            .transpose()
+    }
+
+    /// Removes an option that must be given and parses its value.
+    fn take_required<T: FromStr>(&mut self, name: &str) -> Result<T> {
+        self.take(name)?
+            .ok_or_else(|| anyhow!("missing required option {}", name))
+    }
+
+    fn has_flag(&self, name: &str) -> bool {
+        self.flags.iter().any(|flag| flag == name)
+    }
+
+    /// Fails if any option was not used by the command.
+    fn finish(self) -> Result<Vec<PathBuf>> {
+        if let Some(name) = self.options.keys().next() {
+            bail!("unknown option {}", name);
+        }
+        Ok(self.inputs)
+    }
+}
+
+/// Loads every dump.
+fn load_dumps(inputs: &[PathBuf]) -> Result<Dump> {
+    let mut dump = Dump::default();
+    for input in inputs {
+        dump.load_path(input)?;
+    }
+    Ok(dump)
+}
+
+/// Opens the output file, or stdout if there is none.
+fn create_output(output: Option<&Path>) -> Result<Box<dyn Write>> {
+    Ok(match output {
+        Some(path) => {
+            Box::new(BufWriter::new(File::create(path).with_context(|| {
+                format!("failed to create {}", path.display())
+            })?))
+        }
+        None => Box::new(BufWriter::new(io::stdout())),
+    })
+}
+
+fn export(mut args: Args) -> Result<()> {
+    let locker: Option<Pubkey> = args.take("--locker")?;
+    let era: Option<u16> = args.take("--era")?;
+    let format = args
+        .options
+        .remove("--format")
+        .map(|format| Format::parse(&format))
+        .transpose()?;
+    let output: Option<PathBuf> = args.take("--output")?;
+    let dump = load_dumps(&args.finish()?)?;
+
+    let table = table::build_table(&dump, locker, era)?;
+    if table.unknown_escrows > 0 {
+        eprintln!(
+            "warning: skipped {} escrow histories whose escrow account is not in the dumps",
+            table.unknown_escrows
+        );
+    }
+    let format = format
+        .or_else(|| output.as_deref().and_then(Format::from_path))
+        .unwrap_or(Format::Csv);
+    let mut writer = create_output(output.as_deref())?;
+    write_rows(&mut writer, &table.rows, format)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn generate_airdrop(mut args: Args) -> Result<()> {
+    let locker: Pubkey = args.take_required("--locker")?;
+    let era: u16 = args.take_required("--era")?;
+    let period: u8 = args.take_required("--period")?;
+    let amount: u64 = args.take_required("--amount")?;
+    let allow_non_final = args.has_flag("--allow-non-final");
+    let output: Option<PathBuf> = args.take("--output")?;
+    let dump = load_dumps(&args.finish()?)?;
+
+    let distribution = airdrop::build_airdrop(&dump, locker, era, period, amount, allow_non_final)?;
+    let report = &distribution.report;
+    eprintln!(
+        "{} claims totalling {} of {} tokens; {} for veTokens without a claimant, {} of rounding dust",
+        distribution.claims.len(),
+        report.distributed_amount,
+        report.total_amount,
+        report.unsynced_amount,
+        report.rounding_dust
+    );
+    let mut writer = create_output(output.as_deref())?;
+    serde_json::to_writer_pretty(
+        &mut writer,
+        &airdrop::distribution_to_json(locker, era, period, &distribution),
+    )?;
+    writeln!(writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    match args.next().as_deref() {
+        Some("export") => export(Args::parse(args, &[])?),
+        Some("airdrop") => generate_airdrop(Args::parse(args, &["--allow-non-final"])?),
+        Some("-h" | "--help") => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        _ => {
+            eprint!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Per-period balance table.
+
+use crate::dump::Dump;
+use anchor_lang::prelude::*;
+use anyhow::{anyhow, Result};
+use serde::{Serialize, Serializer};
+use snapshots::{LockerHistory, PeriodId, ERA_NUM_PERIODS};
+use std::collections::BTreeMap;
+
+/// Kind of account a [BalanceRow] is the balance of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    /// Total balance of a [locked_voter::Locker].
+    Locker,
+    /// Balance of a [locked_voter::Escrow].
+    Escrow,
+}
+
+impl Kind {
+    /// Name of the kind in exports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Locker => "locker",
+            Kind::Escrow => "escrow",
+        }
+    }
+}
+
+/// The balance of a locker or escrow at a period.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct BalanceRow {
+    /// Kind of balance.
+    pub kind: Kind,
+    /// The [locked_voter::Locker], if known.
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub locker: Option<Pubkey>,
+    /// The [locked_voter::Escrow], for escrow balances.
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub escrow: Option<Pubkey>,
+    /// Owner of the [locked_voter::Escrow], if known.
+    #[serde(serialize_with = "serialize_pubkey")]
+    pub owner: Option<Pubkey>,
+    /// The era.
+    pub era: u16,
+    /// The period within the era.
+    pub period: u8,
+    /// When the period starts.
+    pub period_start_ts: u64,
+    /// The veToken balance at the start of the period.
+    pub ve_balance: u64,
+    /// Whether the [LockerHistory] total of the period can no longer change.
+    pub is_final: bool,
+}
+
+fn serialize_pubkey<S: Serializer>(key: &Option<Pubkey>, serializer: S) -> Result<S::Ok, S::Error> {
+    match key {
+        Some(key) => serializer.serialize_str(&key.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// The balance table of a [Dump].
+pub struct Table {
+    /// Rows, ordered by era, period, kind, locker and escrow.
+    pub rows: Vec<BalanceRow>,
+    /// Number of escrow histories that were left out because their [locked_voter::Escrow]
+    /// was not in the dump, so their locker could not be determined.
+    pub unknown_escrows: usize,
+}
+
+/// Builds the balance table of the histories in a [Dump], optionally restricted to a locker and an era.
+pub fn build_table(dump: &Dump, locker: Option<Pubkey>, era: Option<u16>) -> Result<Table> {
+    let in_filter = |history_locker: Option<Pubkey>, history_era: u16| {
+        locker.iter().all(|locker| history_locker == Some(*locker))
+            && era.iter().all(|era| *era == history_era)
+    };
+    let locker_histories: BTreeMap<(Pubkey, u16), &LockerHistory> = dump
+        .locker_histories
+        .values()
+        .map(|history| ((history.locker, history.era), history))
+        .collect();
+    let overflow = || anyhow!("balance overflow");
+
+    let mut rows = vec![];
+    for history in locker_histories.values() {
+        if !in_filter(Some(history.locker), history.era) {
+            continue;
+        }
+        for period in 0..usize::from(history.materialized_periods).min(ERA_NUM_PERIODS) {
+            let period = u8::try_from(period)?;
+            rows.push(BalanceRow {
+                kind: Kind::Locker,
+                locker: Some(history.locker),
+                escrow: None,
+                owner: None,
+                era: history.era,
+                period,
+                period_start_ts: history
+                    .geometry()
+                    .period_start_ts(PeriodId::from((history.era, period)))
+                    .ok_or_else(overflow)?,
+                ve_balance: history.ve_balances[usize::from(period)],
+                is_final: history.is_period_final(period),
+            });
+        }
+    }
+
+    let mut unknown_escrows = 0;
+    for history in dump.all_escrow_histories() {
+        let escrow = dump.escrows.get(&history.escrow());
+        if locker.is_some() && escrow.is_none() {
+            unknown_escrows += 1;
+            continue;
+        }
+        let history_locker = escrow.map(|escrow| escrow.locker);
+        if !in_filter(history_locker, history.era()) {
+            continue;
+        }
+        let locker_history = history_locker
+            .and_then(|history_locker| locker_histories.get(&(history_locker, history.era())));
+        for period in 0..=u8::MAX {
+            let ve_balance = history.calculate_ve_balance(period).ok_or_else(overflow)?;
+            if ve_balance == 0 {
+                continue;
+            }
+            rows.push(BalanceRow {
+                kind: Kind::Escrow,
+                locker: history_locker,
+                escrow: Some(history.escrow()),
+                owner: escrow.map(|escrow| escrow.owner),
+                era: history.era(),
+                period,
+                period_start_ts: history
+                    .geometry()
+                    .period_start_ts(PeriodId::from((history.era(), period)))
+                    .ok_or_else(overflow)?,
+                ve_balance,
+                is_final: matches!(locker_history, Some(locker_history) if locker_history.is_period_final(period)),
+            });
+        }
+    }
+
+    rows.sort_by(|a, b| {
+        (a.era, a.period, a.kind, a.locker, a.escrow)
+            .cmp(&(b.era, b.period, b.kind, b.locker, b.escrow))
+    });
+    Ok(Table {
+        rows,
+        unknown_escrows,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
+mod tests {
+    use super::*;
+    use locked_voter::Escrow;
+    use snapshots::{calculate_era_start_ts, CompactEscrowHistory, EscrowHistory, PERIOD_SECONDS};
+
+    #[test]
+    fn test_build_table() {
+        let locker = Pubkey::new_unique();
+        let escrow_key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let era_start = calculate_era_start_ts(0).unwrap();
+        let period_seconds = u64::from(PERIOD_SECONDS);
+
+        let mut locker_history = LockerHistory {
+            locker,
+            materialized_periods: 2,
+            ..Default::default()
+        };
+        locker_history.ve_balances[0] = 1_000;
+        locker_history.ve_balances[1] = 900;
+        let escrow_history = EscrowHistory {
+            escrow: escrow_key,
+            power: 1_000,
+            started_at: era_start,
+            ends_at: era_start + 10 * period_seconds,
+            max_stake_duration: 10 * period_seconds,
+            ..Default::default()
+        };
+
+        let mut dump = Dump::default();
+        dump.locker_histories
+            .insert(Pubkey::new_unique(), locker_history);
+        dump.escrow_histories
+            .insert(Pubkey::new_unique(), escrow_history);
+
+        // the locker of the escrow is unknown
+        let table = build_table(&dump, Some(locker), None).unwrap();
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.unknown_escrows, 1);
+
+        dump.escrows.insert(
+            escrow_key,
+            Escrow {
+                locker,
+                owner,
+                ..Default::default()
+            },
+        );
+        let table = build_table(&dump, Some(locker), Some(0)).unwrap();
+        assert_eq!(table.unknown_escrows, 0);
+        assert_eq!(table.rows.len(), 2 + 10);
+        assert_eq!(
+            table.rows[1],
+            BalanceRow {
+                kind: Kind::Escrow,
+                locker: Some(locker),
+                escrow: Some(escrow_key),
+                owner: Some(owner),
+                era: 0,
+                period: 0,
+                period_start_ts: era_start,
+                ve_balance: 1_000,
+                is_final: true,
+            }
+        );
+        let last = table.rows.last().unwrap();
+        assert_eq!(
+            (last.period, last.ve_balance, last.is_final),
+            (9, 100, false)
+        );
+
+        assert!(build_table(&dump, Some(locker), Some(1))
+            .unwrap()
+            .rows
+            .is_empty());
+    }
+
+    #[test]
+    fn test_build_table_compact() {
+        let locker = Pubkey::new_unique();
+        let escrow_key = Pubkey::new_unique();
+        let era_start = calculate_era_start_ts(0).unwrap();
+        let period_seconds = u64::from(PERIOD_SECONDS);
+
+        let mut compact = CompactEscrowHistory {
+            escrow: escrow_key,
+            num_checkpoints: 1,
+            ..Default::default()
+        };
+        compact.checkpoint_periods[0] = 2;
+        compact.checkpoint_powers[0] = 1_000;
+        compact.checkpoint_started_at[0] = era_start;
+        compact.checkpoint_ends_at[0] = era_start + 10 * period_seconds;
+        compact.checkpoint_max_stake_durations[0] = 10 * period_seconds;
+
+        let mut dump = Dump::default();
+        dump.compact_escrow_histories
+            .insert(Pubkey::new_unique(), compact);
+        dump.escrows.insert(
+            escrow_key,
+            Escrow {
+                locker,
+                ..Default::default()
+            },
+        );
+        let table = build_table(&dump, Some(locker), Some(0)).unwrap();
+        // periods before the first checkpoint have no balance
+        assert_eq!(table.rows.len(), 8);
+        assert_eq!((table.rows[0].period, table.rows[0].ve_balance), (2, 800));
+        assert!(table.rows.iter().all(|row| row.kind == Kind::Escrow));
+    }
+}
@@ -0,0 +1,76 @@
+//! Eras that an [Escrow] should be synced into.
+
+use locked_voter::Escrow;
+use num_traits::ToPrimitive;
+use snapshots::{Era, EraGeometry};
+
+/// Calculates the eras whose histories an [Escrow] should be synced into at `now`,
+/// for a locker with the given [EraGeometry].
+///
+/// These are the current era and every later era in which the lockup still has voting power.
+pub fn calculate_escrow_eras(
+    geometry: &EraGeometry,
+    escrow: &Escrow,
+    now: i64,
+) -> Option<Vec<u16>> {
+    let Era(current_era) = geometry.period_of_ts(now.to_u64()?)?.era;
+    if escrow.escrow_ends_at <= now {
+        return Some(vec![current_era]);
+    }
+    // the lockup has no voting power at the moment it ends
+    let last_active_ts = escrow.escrow_ends_at.checked_sub(1)?.to_u64()?;
+    let Era(last_era) = geometry.period_of_ts(last_active_ts)?.era;
+    Some((current_era..=last_era).collect())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
+mod tests {
+    use super::*;
+    use snapshots::{calculate_era_start_ts, PERIOD_SECONDS};
+
+    fn escrow_ending_at(escrow_ends_at: u64) -> Escrow {
+        Escrow {
+            escrow_started_at: 1,
+            escrow_ends_at: escrow_ends_at as i64,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ended_escrow_syncs_current_era() {
+        let now = calculate_era_start_ts(2).unwrap() + 10;
+        let escrow = escrow_ending_at(now - 1);
+        assert_eq!(
+            calculate_escrow_eras(&EraGeometry::DEFAULT, &escrow, now as i64),
+            Some(vec![2])
+        );
+    }
+
+    #[test]
+    fn test_escrow_ending_on_era_boundary() {
+        let now = calculate_era_start_ts(2).unwrap() + u64::from(PERIOD_SECONDS);
+        let escrow = escrow_ending_at(calculate_era_start_ts(4).unwrap());
+        assert_eq!(
+            calculate_escrow_eras(&EraGeometry::DEFAULT, &escrow, now as i64),
+            Some(vec![2, 3])
+        );
+
+        let escrow = escrow_ending_at(calculate_era_start_ts(4).unwrap() + 1);
+        assert_eq!(
+            calculate_escrow_eras(&EraGeometry::DEFAULT, &escrow, now as i64),
+            Some(vec![2, 3, 4])
+        );
+    }
+
+    #[test]
+    fn test_weekly_periods() {
+        let geometry = EraGeometry::new(86_400 * 7, 0).unwrap();
+        let era_seconds = geometry.seconds_per_era().unwrap();
+        let escrow = escrow_ending_at(era_seconds * 3 + 1);
+        assert_eq!(
+            calculate_escrow_eras(&geometry, &escrow, era_seconds as i64),
+            Some(vec![1, 2, 3])
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Builders for [snapshots] instructions.
+
+use crate::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, system_program};
+use anchor_lang::InstructionData;
+
+/// Builds a [snapshots::snapshots::create_locker_history] instruction.
+pub fn create_locker_history(
+    locker: Pubkey,
+    era: u16,
+    retention_seconds: u64,
+    payer: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: snapshots::ID,
+        accounts: snapshots::accounts::CreateLockerHistory {
+            locker,
+            snapshot_config: find_snapshot_config_address(&locker).0,
+            locker_history: find_locker_history_address(&locker, era).0,
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: snapshots::instruction::CreateLockerHistory {
+            era,
+            retention_seconds,
+        }
+        .data(),
+    }
+}
+
+/// Builds a [snapshots::snapshots::create_escrow_history] instruction.
+pub fn create_escrow_history(
+    locker: Pubkey,
+    escrow: Pubkey,
+    era: u16,
+    retention_seconds: u64,
+    payer: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: snapshots::ID,
+        accounts: snapshots::accounts::CreateEscrowHistory {
+            escrow,
+            snapshot_config: find_snapshot_config_address(&locker).0,
+            compact_escrow_history: find_compact_escrow_history_address(&escrow, era).0,
+            escrow_history: find_escrow_history_address(&escrow, era).0,
+            payer,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: snapshots::instruction::CreateEscrowHistory {
+            era,
+            retention_seconds,
+        }
+        .data(),
+    }
+}
+
+/// Builds a [snapshots::snapshots::create_escrow_histories] instruction creating the histories
+/// of `num_eras` eras starting from `first_era` for each of the given escrows of a locker.
+pub fn create_escrow_histories(
+    locker: Pubkey,
+    escrows: &[Pubkey],
+    first_era: u16,
+    num_eras: u8,
+    retention_seconds: u64,
+    payer: Pubkey,
+) -> Instruction {
+    let mut accounts = snapshots::accounts::CreateEscrowHistories {
+        locker,
+        snapshot_config: find_snapshot_config_address(&locker).0,
+        payer,
+        system_program: system_program::ID,
+    }
+    .to_account_metas(None);
+    for escrow in escrows {
+        accounts.push(AccountMeta::new_readonly(*escrow, false));
+        for era in (first_era..).take(usize::from(num_eras)) {
+            accounts.push(AccountMeta::new(
+                find_escrow_history_address(escrow, era).0,
+                false,
+            ));
+            accounts.push(AccountMeta::new_readonly(
+                find_compact_escrow_history_address(escrow, era).0,
+                false,
+            ));
+        }
+    }
+    Instruction {
+        program_id: snapshots::ID,
+        accounts,
+        data: snapshots::instruction::CreateEscrowHistories {
+            first_era,
+            num_eras,
+            retention_seconds,
+        }
+        .data(),
+    }
+}
+
+/// Builds a [snapshots::snapshots::sync] instruction.
+pub fn sync(locker: Pubkey, escrow: Pubkey, era: u16) -> Instruction {
+    Instruction {
+        program_id: snapshots::ID,
+        accounts: snapshots::accounts::Sync {
+            locker,
+            escrow,
+            locker_history: find_locker_history_address(&locker, era).0,
+            escrow_history: find_escrow_history_address(&escrow, era).0,
+        }
+        .to_account_metas(None),
+        data: snapshots::instruction::Sync {}.data(),
+    }
+}
+
+/// Builds a [snapshots::snapshots::sync_all_eras] instruction for the given eras.
+pub fn sync_all_eras(locker: Pubkey, escrow: Pubkey, eras: &[u16]) -> Instruction {
+    let mut accounts = snapshots::accounts::SyncAllEras { locker, escrow }.to_account_metas(None);
+    for era in eras {
+        accounts.push(AccountMeta::new(
+            find_locker_history_address(&locker, *era).0,
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            find_escrow_history_address(&escrow, *era).0,
+            false,
+        ));
+    }
+    Instruction {
+        program_id: snapshots::ID,
+        accounts,
+        data: snapshots::instruction::SyncAllEras {}.data(),
+    }
+}
+
+/// Builds a [snapshots::snapshots::sync_many] instruction for the given escrows.
+pub fn sync_many(locker: Pubkey, era: u16, escrows: &[Pubkey]) -> Instruction {
+    let mut accounts = snapshots::accounts::SyncMany {
+        locker,
+        locker_history: find_locker_history_address(&locker, era).0,
+    }
+    .to_account_metas(None);
+    for escrow in escrows {
+        accounts.push(AccountMeta::new_readonly(*escrow, false));
+        accounts.push(AccountMeta::new(
+            find_escrow_history_address(escrow, era).0,
+            false,
+        ));
+    }
+    Instruction {
+        program_id: snapshots::ID,
+        accounts,
+        data: snapshots::instruction::SyncMany {}.data(),
+    }
+}
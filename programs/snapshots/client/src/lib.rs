@@ -0,0 +1,15 @@
+//! Instruction builders and PDA helpers for the [snapshots] program.
+//!
+//! Keepers and other off-chain services can use this crate to derive history addresses
+//! and build instructions without depending on the account layouts directly.
+#![deny(rustdoc::all)]
+#![allow(rustdoc::missing_doc_code_examples)]
+#![deny(clippy::unwrap_used, clippy::integer_arithmetic)]
+#![deny(missing_docs)]
+
+mod eras;
+pub mod instructions;
+mod pda;
+
+pub use eras::*;
+pub use pda::*;
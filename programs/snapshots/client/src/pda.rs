@@ -0,0 +1,119 @@
+//! Program-derived addresses.
+
+use anchor_lang::prelude::Pubkey;
+
+/// Finds the address of the [locked_voter::Escrow] of an owner.
+pub fn find_escrow_address(locker: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"Escrow".as_ref(), locker.as_ref(), owner.as_ref()],
+        &locked_voter::ID,
+    )
+}
+
+/// Finds the address of the [snapshots::SnapshotConfig] of a [locked_voter::Locker].
+pub fn find_snapshot_config_address(locker: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"SnapshotConfig".as_ref(), locker.as_ref()],
+        &snapshots::ID,
+    )
+}
+
+/// Finds the address of the [snapshots::LockerHistory] of an era.
+pub fn find_locker_history_address(locker: &Pubkey, era: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"LockerHistory".as_ref(),
+            locker.as_ref(),
+            era.to_le_bytes().as_ref(),
+        ],
+        &snapshots::ID,
+    )
+}
+
+/// Finds the address of the [snapshots::EscrowHistory] of an era.
+pub fn find_escrow_history_address(escrow: &Pubkey, era: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"EscrowHistory".as_ref(),
+            escrow.as_ref(),
+            era.to_le_bytes().as_ref(),
+        ],
+        &snapshots::ID,
+    )
+}
+
+/// Finds the address of the [snapshots::CompactEscrowHistory] of an era.
+pub fn find_compact_escrow_history_address(escrow: &Pubkey, era: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"CompactEscrowHistory".as_ref(),
+            escrow.as_ref(),
+            era.to_le_bytes().as_ref(),
+        ],
+        &snapshots::ID,
+    )
+}
+
+/// Finds the address of the [snapshots::CumulativeBalances] of a
+/// [snapshots::LockerHistory] or [snapshots::EscrowHistory].
+pub fn find_cumulative_balances_address(history: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"CumulativeBalances".as_ref(), history.as_ref()],
+        &snapshots::ID,
+    )
+}
+
+/// Finds the address of the [snapshots::VoterWeightRegistrar] of an SPL Governance realm.
+pub fn find_voter_weight_registrar_address(realm: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"VoterWeightRegistrar".as_ref(), realm.as_ref()],
+        &snapshots::ID,
+    )
+}
+
+/// Finds the address of the [snapshots::ProposalSnapshot] of an SPL Governance proposal.
+pub fn find_proposal_snapshot_address(registrar: &Pubkey, proposal: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"ProposalSnapshot".as_ref(),
+            registrar.as_ref(),
+            proposal.as_ref(),
+        ],
+        &snapshots::ID,
+    )
+}
+
+/// Finds the address of the [snapshots::VoterWeightRecord] of a governing token owner.
+pub fn find_voter_weight_record_address(
+    registrar: &Pubkey,
+    governing_token_owner: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"VoterWeightRecord".as_ref(),
+            registrar.as_ref(),
+            governing_token_owner.as_ref(),
+        ],
+        &snapshots::ID,
+    )
+}
+
+/// Finds the address of the [snapshots::GovernProposalSnapshot] of a Govern proposal.
+pub fn find_govern_proposal_snapshot_address(proposal: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"GovernProposalSnapshot".as_ref(), proposal.as_ref()],
+        &snapshots::ID,
+    )
+}
+
+/// Finds the address of the [snapshots::VoteWeightReceipt] of a [locked_voter::Escrow].
+pub fn find_vote_weight_receipt_address(snapshot: &Pubkey, escrow: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"VoteWeightReceipt".as_ref(),
+            snapshot.as_ref(),
+            escrow.as_ref(),
+        ],
+        &snapshots::ID,
+    )
+}
@@ -0,0 +1,91 @@
+//! Time-weighted averages of period balances.
+
+use num_traits::ToPrimitive;
+
+/// Accumulates the balances of consecutive periods to compute their time-weighted average.
+///
+/// All periods of a locker have the same length, so the time-weighted average is the mean
+/// of the balances. Balances may be added one era at a time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TimeWeightedAverage {
+    /// Sum of the balances added so far.
+    pub sum: u128,
+    /// Number of periods added so far.
+    pub num_periods: u64,
+}
+
+impl TimeWeightedAverage {
+    /// Adds the balance of the next period.
+    pub fn add(&mut self, ve_balance: u64) -> Option<()> {
+        self.sum = self.sum.checked_add(ve_balance.into())?;
+        self.num_periods = self.num_periods.checked_add(1)?;
+        Some(())
+    }
+
+    /// Adds the balances of the next `num_periods` periods, given their sum.
+    pub fn add_sum(&mut self, sum: u128, num_periods: u64) -> Option<()> {
+        self.sum = self.sum.checked_add(sum)?;
+        self.num_periods = self.num_periods.checked_add(num_periods)?;
+        Some(())
+    }
+
+    /// Calculates the average of the balances added so far, rounded down.
+    ///
+    /// Returns [None] if no balances have been added.
+    pub fn average(&self) -> Option<u64> {
+        self.sum.checked_div(self.num_periods.into())?.to_u64()
+    }
+}
+
+/// Calculates the time-weighted average of the balances of consecutive periods, rounded down.
+///
+/// Returns [None] if there are no balances.
+pub fn calculate_average_ve_balance(ve_balances: impl IntoIterator<Item = u64>) -> Option<u64> {
+    let mut average = TimeWeightedAverage::default();
+    for ve_balance in ve_balances {
+        average.add(ve_balance)?;
+    }
+    average.average()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average() {
+        assert_eq!(calculate_average_ve_balance([]), None);
+        assert_eq!(calculate_average_ve_balance([7]), Some(7));
+        assert_eq!(calculate_average_ve_balance([1, 2, 4]), Some(2));
+        assert_eq!(
+            calculate_average_ve_balance([u64::MAX, u64::MAX, u64::MAX - 3]),
+            Some(u64::MAX - 1)
+        );
+    }
+
+    #[test]
+    fn test_late_lockup_is_diluted() {
+        // a lockup made just before the last period only counts for that period
+        let mut ve_balances = [0_u64; 512];
+        ve_balances[511] = 1_000_000;
+        assert_eq!(calculate_average_ve_balance(ve_balances), Some(1_953));
+
+        let mut average = TimeWeightedAverage::default();
+        for ve_balance in ve_balances.chunks(256).flatten() {
+            average.add(*ve_balance).unwrap();
+        }
+        assert_eq!(average.num_periods, 512);
+        assert_eq!(average.average(), Some(1_953));
+    }
+
+    #[test]
+    fn test_add_sum() {
+        let mut average = TimeWeightedAverage::default();
+        average.add(1).unwrap();
+        average.add_sum(2 + 4, 2).unwrap();
+        assert_eq!(average.num_periods, 3);
+        assert_eq!(average.average(), Some(2));
+        assert_eq!(average.add_sum(u128::MAX, 1), None);
+    }
+}
@@ -0,0 +1,128 @@
+//! Length of periods and start of the first era.
+
+use crate::{Era, PeriodId, COMMON_ERA_UNIX_TS, ERA_NUM_PERIODS, PERIOD_SECONDS};
+use num_traits::ToPrimitive;
+
+/// Length of the periods of a locker and the Unix timestamp at which its era 0 starts.
+///
+/// Every era has [ERA_NUM_PERIODS] periods. Lockers without their own geometry use [EraGeometry::DEFAULT].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EraGeometry {
+    /// Number of seconds in a period.
+    pub period_seconds: u64,
+    /// The Unix timestamp of the start of era 0.
+    pub origin_ts: u64,
+}
+
+impl Default for EraGeometry {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl EraGeometry {
+    /// Periods of [PERIOD_SECONDS], starting at [COMMON_ERA_UNIX_TS].
+    pub const DEFAULT: EraGeometry = EraGeometry {
+        period_seconds: PERIOD_SECONDS as u64,
+        origin_ts: COMMON_ERA_UNIX_TS,
+    };
+
+    /// Creates a geometry, or returns [None] if periods are empty or the last era does not fit in a `u64` timestamp.
+    pub fn new(period_seconds: u64, origin_ts: u64) -> Option<Self> {
+        let geometry = Self {
+            period_seconds,
+            origin_ts,
+        };
+        if period_seconds == 0 {
+            return None;
+        }
+        geometry.era_end_ts(Era(u16::MAX))?;
+        Some(geometry)
+    }
+
+    /// Number of seconds in an era.
+    pub fn seconds_per_era(&self) -> Option<u64> {
+        self.period_seconds.checked_mul(ERA_NUM_PERIODS.to_u64()?)
+    }
+
+    /// Calculates the Unix timestamp at which the era starts.
+    pub fn era_start_ts(&self, era: Era) -> Option<u64> {
+        self.origin_ts
+            .checked_add(self.seconds_per_era()?.checked_mul(era.0.into())?)
+    }
+
+    /// Calculates the Unix timestamp at which the era ends, which is the start of the next era.
+    pub fn era_end_ts(&self, era: Era) -> Option<u64> {
+        self.era_start_ts(era)?.checked_add(self.seconds_per_era()?)
+    }
+
+    /// Calculates the Unix timestamp at which the period starts.
+    pub fn period_start_ts(&self, id: PeriodId) -> Option<u64> {
+        self.era_start_ts(id.era)?
+            .checked_add(u64::from(id.period.0).checked_mul(self.period_seconds)?)
+    }
+
+    /// Calculates the Unix timestamp at which the period ends, which is the start of the next period.
+    pub fn period_end_ts(&self, id: PeriodId) -> Option<u64> {
+        self.period_start_ts(id)?.checked_add(self.period_seconds)
+    }
+
+    /// Calculates the period that contains the Unix timestamp.
+    pub fn period_of_ts(&self, ts: u64) -> Option<PeriodId> {
+        PeriodId::from_index(
+            ts.checked_sub(self.origin_ts)?
+                .checked_div(self.period_seconds)?,
+        )
+    }
+
+    /// A period is `elapsed` if its start time has passed.
+    ///
+    /// Elapsed periods cannot increase their locker veToken balance.
+    pub fn has_period_elapsed(&self, id: PeriodId, now: i64) -> Option<bool> {
+        // `>` instead of `>=` to prevent potential off-by-one errors
+        // by programmers that are not aware of the definition of elapsed.
+        // one second isn't a big deal.
+        Some(now.to_u64()? > self.period_start_ts(id)?)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_matches_constants() {
+        let id = PeriodId::from((3, 17));
+        let geometry = EraGeometry::default();
+        assert_eq!(geometry.period_start_ts(id), id.start_ts());
+        assert_eq!(geometry.seconds_per_era(), Some(crate::SECONDS_PER_ERA));
+        assert_eq!(
+            EraGeometry::new(PERIOD_SECONDS.into(), COMMON_ERA_UNIX_TS),
+            Some(geometry)
+        );
+    }
+
+    #[test]
+    fn test_weekly_periods() {
+        let geometry = EraGeometry::new(86_400 * 7, 1_000).unwrap();
+        let id = PeriodId::from((1, 2));
+        let start = 1_000 + 256 * 86_400 * 7 + 2 * 86_400 * 7;
+        assert_eq!(geometry.period_start_ts(id), Some(start));
+        assert_eq!(geometry.period_of_ts(start), Some(id));
+        assert_eq!(geometry.period_of_ts(start - 1), id.prev());
+        assert_eq!(geometry.period_of_ts(999), None);
+        assert_eq!(geometry.has_period_elapsed(id, start as i64), Some(false));
+        assert_eq!(
+            geometry.has_period_elapsed(id, start as i64 + 1),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_invalid_geometry() {
+        assert_eq!(EraGeometry::new(0, COMMON_ERA_UNIX_TS), None);
+        assert_eq!(EraGeometry::new(u64::MAX / 256, COMMON_ERA_UNIX_TS), None);
+        assert_eq!(EraGeometry::new(1, u64::MAX), None);
+    }
+}
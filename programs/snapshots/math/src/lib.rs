@@ -7,76 +7,65 @@
 #![deny(clippy::unwrap_used, clippy::integer_arithmetic)]
 #![deny(missing_docs)]
 
-use num_traits::cast::ToPrimitive;
+mod average;
+mod geometry;
+mod period;
+mod voting_power;
+
+pub use average::*;
+pub use geometry::*;
+pub use period::*;
+pub use voting_power::*;
 
 /// Number of periods in an era.
 pub const ERA_NUM_PERIODS: usize = (u8::MAX as usize) + 1;
 
-/// Number of seconds in a period.
+/// Number of seconds in a period of the [EraGeometry::DEFAULT].
 pub const PERIOD_SECONDS: u32 = 86_400 * 3;
 
-/// Number of seconds in an era.
+/// Number of seconds in an era of the [EraGeometry::DEFAULT].
 pub const SECONDS_PER_ERA: u64 = (ERA_NUM_PERIODS as u64) * (PERIOD_SECONDS as u64);
 
-/// The Unix timestamp of the start of the first era.
+/// The Unix timestamp of the start of the first era of the [EraGeometry::DEFAULT].
 pub const COMMON_ERA_UNIX_TS: u64 = 1640995200;
 
 /// Calculates the start timestamp of an era.
 pub fn calculate_era_start_ts(era: u16) -> Option<u64> {
-    COMMON_ERA_UNIX_TS.checked_add(SECONDS_PER_ERA.checked_mul(era.into())?)
+    Era(era).start_ts()
 }
 
 /// Calculates the start timestamp of a period of an era.
 pub fn calculate_period_start_ts(era: u16, period: u8) -> Option<u64> {
-    calculate_era_start_ts(era)?
-        .checked_add(period.to_u64()?.checked_mul(PERIOD_SECONDS.to_u64()?)?)
+    PeriodId::from((era, period)).start_ts()
 }
 
 /// A period is `elapsed` if its start time has passed.
 ///
 /// Elapsed periods cannot increase their locker veToken balance.
 pub fn has_period_elapsed(era: u16, period: u8, now: i64) -> Option<bool> {
-    let start = calculate_period_start_ts(era, period)?;
-    let now = now.to_u64()?;
-    // `>` instead of `>=` to prevent potential off-by-one errors
-    // by programmers that are not aware of the definition of elapsed.
-    // one second isn't a big deal.
-    Some(now > start)
+    PeriodId::from((era, period)).has_elapsed(now)
 }
 
 /// Calculates the era and period of the given Unix timestamp.
 pub fn calculate_era_and_period_of_ts(now: u64) -> Option<(u16, u8)> {
-    let current_era: u16 = now
-        .checked_sub(COMMON_ERA_UNIX_TS)?
-        .checked_div(SECONDS_PER_ERA)?
-        .to_u16()?;
-    let current_era_start_ts = calculate_era_start_ts(current_era)?;
-    let current_period: u8 = now
-        .checked_sub(current_era_start_ts)?
-        .checked_div(PERIOD_SECONDS.into())?
-        .to_u8()?;
-    Some((current_era, current_period))
+    Some(PeriodId::of_ts(now)?.into())
 }
 
 /// Calculates the next era and period of the given period.
 pub fn calculate_next_era_and_period(era: u16, period: u8) -> Option<(u16, u8)> {
-    Some(if period == u8::MAX {
-        (era.checked_add(1)?, 0_u8)
-    } else {
-        (era, period.checked_add(1)?)
-    })
+    Some(PeriodId::from((era, period)).next()?.into())
 }
 
 /// Calculates the next era and period of the given Unix timestamp.
 pub fn calculate_next_era_and_period_of_ts(now: u64) -> Option<(u16, u8)> {
-    let (current_era, current_period) = calculate_era_and_period_of_ts(now)?;
-    calculate_next_era_and_period(current_era, current_period)
+    Some(PeriodId::of_ts(now)?.next()?.into())
 }
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
 mod tests {
     use super::*;
+    use num_traits::ToPrimitive;
 
     #[test]
     fn test_has_period_elapsed() {
@@ -0,0 +1,302 @@
+//! Typed eras and periods.
+//!
+//! A [PeriodId] identifies a period by its [Era] and its [Period] within the era. Period ids are
+//! ordered chronologically and can be converted to and from Unix timestamps with an [EraGeometry].
+
+use crate::{EraGeometry, ERA_NUM_PERIODS};
+use num_traits::ToPrimitive;
+use std::fmt;
+
+/// An era, which spans [ERA_NUM_PERIODS] periods.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Era(pub u16);
+
+/// The index of a period within its [Era].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Period(pub u8);
+
+/// A period, identified by its [Era] and its [Period] within the era.
+///
+/// Ordering compares the era first, so period ids are ordered chronologically.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PeriodId {
+    /// The era.
+    pub era: Era,
+    /// The period within the era.
+    pub period: Period,
+}
+
+impl Era {
+    /// Calculates the Unix timestamp at which the era starts with the [EraGeometry::DEFAULT].
+    pub fn start_ts(self) -> Option<u64> {
+        EraGeometry::DEFAULT.era_start_ts(self)
+    }
+
+    /// Calculates the Unix timestamp at which the era ends with the [EraGeometry::DEFAULT],
+    /// which is the start of the next era.
+    pub fn end_ts(self) -> Option<u64> {
+        EraGeometry::DEFAULT.era_end_ts(self)
+    }
+
+    /// Calculates the era that contains the Unix timestamp with the [EraGeometry::DEFAULT].
+    pub fn of_ts(ts: u64) -> Option<Self> {
+        Some(PeriodId::of_ts(ts)?.era)
+    }
+
+    /// Adds a number of eras.
+    pub fn checked_add(self, eras: u16) -> Option<Self> {
+        Some(Era(self.0.checked_add(eras)?))
+    }
+
+    /// Subtracts a number of eras.
+    pub fn checked_sub(self, eras: u16) -> Option<Self> {
+        Some(Era(self.0.checked_sub(eras)?))
+    }
+
+    /// The first period of the era.
+    pub fn first_period(self) -> PeriodId {
+        PeriodId::new(self, Period(0))
+    }
+
+    /// The last period of the era.
+    pub fn last_period(self) -> PeriodId {
+        PeriodId::new(self, Period(u8::MAX))
+    }
+}
+
+impl Period {
+    /// Index of the period within the era, e.g. for indexing balances.
+    pub fn index(self) -> usize {
+        self.0.into()
+    }
+
+    /// Adds a number of periods within the same era.
+    pub fn checked_add(self, periods: u8) -> Option<Self> {
+        Some(Period(self.0.checked_add(periods)?))
+    }
+
+    /// Subtracts a number of periods within the same era.
+    pub fn checked_sub(self, periods: u8) -> Option<Self> {
+        Some(Period(self.0.checked_sub(periods)?))
+    }
+}
+
+impl PeriodId {
+    /// Creates a period id.
+    pub fn new(era: Era, period: Period) -> Self {
+        Self { era, period }
+    }
+
+    /// Number of periods between the start of era 0 and this period.
+    pub fn index(self) -> u64 {
+        (u64::from(self.era.0) << 8) | u64::from(self.period.0)
+    }
+
+    /// Creates the period id with the given [PeriodId::index].
+    pub fn from_index(index: u64) -> Option<Self> {
+        let num_periods = ERA_NUM_PERIODS.to_u64()?;
+        Some(Self::new(
+            Era(index.checked_div(num_periods)?.to_u16()?),
+            Period(index.checked_rem(num_periods)?.to_u8()?),
+        ))
+    }
+
+    /// Calculates the Unix timestamp at which the period starts with the [EraGeometry::DEFAULT].
+    pub fn start_ts(self) -> Option<u64> {
+        EraGeometry::DEFAULT.period_start_ts(self)
+    }
+
+    /// Calculates the Unix timestamp at which the period ends with the [EraGeometry::DEFAULT],
+    /// which is the start of the next period.
+    pub fn end_ts(self) -> Option<u64> {
+        EraGeometry::DEFAULT.period_end_ts(self)
+    }
+
+    /// Calculates the period that contains the Unix timestamp with the [EraGeometry::DEFAULT].
+    pub fn of_ts(ts: u64) -> Option<Self> {
+        EraGeometry::DEFAULT.period_of_ts(ts)
+    }
+
+    /// Returns true if the period has elapsed with the [EraGeometry::DEFAULT].
+    /// See [EraGeometry::has_period_elapsed].
+    pub fn has_elapsed(self, now: i64) -> Option<bool> {
+        EraGeometry::DEFAULT.has_period_elapsed(self, now)
+    }
+
+    /// Adds a number of periods, moving to later eras if needed.
+    pub fn checked_add(self, periods: u64) -> Option<Self> {
+        Self::from_index(self.index().checked_add(periods)?)
+    }
+
+    /// Subtracts a number of periods, moving to earlier eras if needed.
+    pub fn checked_sub(self, periods: u64) -> Option<Self> {
+        Self::from_index(self.index().checked_sub(periods)?)
+    }
+
+    /// The next period.
+    pub fn next(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+
+    /// The previous period.
+    pub fn prev(self) -> Option<Self> {
+        self.checked_sub(1)
+    }
+
+    /// Iterates over the periods from this period through `last`, inclusive.
+    pub fn through(self, last: PeriodId) -> PeriodRange {
+        PeriodRange {
+            next: self.index(),
+            end: last.index().saturating_add(1),
+        }
+    }
+}
+
+impl From<u16> for Era {
+    fn from(era: u16) -> Self {
+        Era(era)
+    }
+}
+
+impl From<Era> for u16 {
+    fn from(era: Era) -> Self {
+        era.0
+    }
+}
+
+impl From<u8> for Period {
+    fn from(period: u8) -> Self {
+        Period(period)
+    }
+}
+
+impl From<Period> for u8 {
+    fn from(period: Period) -> Self {
+        period.0
+    }
+}
+
+impl From<(u16, u8)> for PeriodId {
+    fn from((era, period): (u16, u8)) -> Self {
+        Self::new(Era(era), Period(period))
+    }
+}
+
+impl From<PeriodId> for (u16, u8) {
+    fn from(id: PeriodId) -> Self {
+        (id.era.0, id.period.0)
+    }
+}
+
+impl fmt::Display for Era {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for PeriodId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.era, self.period)
+    }
+}
+
+/// An iterator over consecutive periods, which may span several eras.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeriodRange {
+    /// [PeriodId::index] of the next period to yield.
+    next: u64,
+    /// [PeriodId::index] after the last period to yield.
+    end: u64,
+}
+
+impl Iterator for PeriodRange {
+    type Item = PeriodId;
+
+    fn next(&mut self) -> Option<PeriodId> {
+        if self.next >= self.end {
+            return None;
+        }
+        let id = PeriodId::from_index(self.next)?;
+        self.next = self.next.checked_add(1)?;
+        Some(id)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end.saturating_sub(self.next).to_usize();
+        (len.unwrap_or(usize::MAX), len)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
+mod tests {
+    use super::*;
+    use crate::COMMON_ERA_UNIX_TS;
+
+    #[test]
+    fn test_period_id_ordering() {
+        let a = PeriodId::from((1, 255));
+        let b = PeriodId::from((2, 0));
+        assert!(a < b);
+        assert_eq!(a.next(), Some(b));
+        assert_eq!(b.prev(), Some(a));
+        assert_eq!(a.index() + 1, b.index());
+        assert_eq!(PeriodId::from_index(b.index()), Some(b));
+        assert_eq!(b.to_string(), "2/0");
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let id = PeriodId::from((3, 10));
+        assert_eq!(id.checked_add(256 * 2 + 250), Some(PeriodId::from((6, 4))));
+        assert_eq!(id.checked_sub(11), Some(PeriodId::from((2, 255))));
+        assert_eq!(PeriodId::default().prev(), None);
+        assert_eq!(PeriodId::from((u16::MAX, u8::MAX)).next(), None);
+        assert_eq!(Period(250).checked_add(6), None);
+        assert_eq!(Era(1).checked_sub(2), None);
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let id = PeriodId::from((2, 7));
+        let start = id.start_ts().unwrap();
+        assert_eq!(PeriodId::of_ts(start), Some(id));
+        assert_eq!(PeriodId::of_ts(start - 1), id.prev());
+        assert_eq!(PeriodId::of_ts(id.end_ts().unwrap()), id.next());
+        assert_eq!(Era::of_ts(start), Some(Era(2)));
+        assert_eq!(Era(2).end_ts(), Era(3).start_ts());
+        assert_eq!(PeriodId::of_ts(COMMON_ERA_UNIX_TS - 1), None);
+    }
+
+    #[test]
+    fn test_range_across_eras() {
+        let ids: Vec<PeriodId> = PeriodId::from((0, 254))
+            .through(PeriodId::from((1, 1)))
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                PeriodId::from((0, 254)),
+                PeriodId::from((0, 255)),
+                PeriodId::from((1, 0)),
+                PeriodId::from((1, 1)),
+            ]
+        );
+        assert_eq!(
+            Era(4).first_period().through(Era(4).last_period()).count(),
+            256
+        );
+        assert_eq!(
+            PeriodId::from((1, 0))
+                .through(PeriodId::from((0, 0)))
+                .count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! Voting power of a lockup at each period.
+//!
+//! These reproduce the balances that the snapshots program writes to an `EscrowHistory`,
+//! so off-chain tools can predict them without depending on the program.
+
+use crate::{EraGeometry, PeriodId, ERA_NUM_PERIODS};
+use ::u128::mul_div_u64;
+use num_traits::ToPrimitive;
+
+/// Calculates the voting power of a lockup if it was at the maximum duration.
+///
+/// This is the `amount` of tokens locked multiplied by the `max_stake_vote_multiplier` of the locker.
+pub fn calculate_power_if_max_lockup(amount: u64, max_stake_vote_multiplier: u8) -> Option<u64> {
+    amount.checked_mul(max_stake_vote_multiplier.into())
+}
+
+/// Calculates voter power at the start of a period.
+///
+/// - `power_if_max_lockup`: Voting power if the user's lockup was at the maximum amount
+/// - `period_start_ts`: When the period started.
+/// - `escrow_started_at`: When the user's escrow started.
+/// - `escrow_ends_at`: When the user's escrow ends.
+/// - `max_stake_duration`: The maximum duration of a lockup. This is also the duration used to compute the `power_if_max_lockup`.
+pub fn calculate_voter_power_for_period(
+    power_if_max_lockup: u64,
+    period_start_ts: u64,
+    escrow_started_at: u64,
+    escrow_ends_at: u64,
+    max_stake_duration: u64,
+) -> Option<u64> {
+    // invalid `now` argument, should never happen.
+    if period_start_ts == 0 {
+        return None;
+    }
+    if escrow_started_at == 0 {
+        return Some(0);
+    }
+    // Lockup had zero power before the start time.
+    // at the end time, lockup also has zero power.
+    if period_start_ts < escrow_started_at || period_start_ts >= escrow_ends_at {
+        return Some(0);
+    }
+
+    // multiply the max lockup power by the fraction of the max stake duration
+    let seconds_until_lockup_expiry = escrow_ends_at.checked_sub(period_start_ts)?.to_u64()?;
+    // elapsed seconds, clamped to the maximum duration
+    let relevant_seconds_until_lockup_expiry = seconds_until_lockup_expiry.min(max_stake_duration);
+
+    // multiply the max lockup power by the fraction of the max stake duration
+    let power = mul_div_u64(
+        power_if_max_lockup,
+        relevant_seconds_until_lockup_expiry,
+        max_stake_duration,
+    )?;
+
+    Some(power)
+}
+
+/// Calculates the balance of every period of an era with the given geometry for an escrow locking `amount` tokens.
+///
+/// This is what a sync writes to the `EscrowHistory` of the era if the escrow is synced
+/// before the era starts and is not modified afterwards.
+pub fn project_escrow_history(
+    amount: u64,
+    max_stake_vote_multiplier: u8,
+    escrow_started_at: u64,
+    escrow_ends_at: u64,
+    max_stake_duration: u64,
+    geometry: &EraGeometry,
+    era: u16,
+) -> Option<[u64; ERA_NUM_PERIODS]> {
+    let power_if_max_lockup = calculate_power_if_max_lockup(amount, max_stake_vote_multiplier)?;
+    let mut ve_balances = [0; ERA_NUM_PERIODS];
+    for (period, ve_balance) in (0..=u8::MAX).zip(ve_balances.iter_mut()) {
+        *ve_balance = calculate_voter_power_for_period(
+            power_if_max_lockup,
+            geometry.period_start_ts(PeriodId::from((era, period)))?,
+            escrow_started_at,
+            escrow_ends_at,
+            max_stake_duration,
+        )?;
+    }
+    Some(ve_balances)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::integer_arithmetic)]
+mod tests {
+    use super::*;
+    use crate::{Era, COMMON_ERA_UNIX_TS};
+    use locked_voter::{Escrow, LockerParams};
+    use proptest::prelude::*;
+
+    fn run_test_identical_to_locked_voter(
+        escrow_amount: u64,
+        max_stake_vote_multiplier: u8,
+        period_start_ts: i64,
+        escrow_started_at: i64,
+        escrow_ends_at: i64,
+        max_stake_duration: u64,
+    ) {
+        let reference_locker = LockerParams {
+            max_stake_duration,
+            max_stake_vote_multiplier,
+            ..Default::default()
+        };
+        let reference_escrow = Escrow {
+            amount: escrow_amount,
+            escrow_started_at,
+            escrow_ends_at,
+            ..Default::default()
+        };
+        let power_if_max_lockup =
+            calculate_power_if_max_lockup(escrow_amount, max_stake_vote_multiplier).unwrap();
+        let reference_power =
+            reference_locker.calculate_voter_power(&reference_escrow, period_start_ts);
+        let new_power = calculate_voter_power_for_period(
+            power_if_max_lockup,
+            period_start_ts as u64,
+            escrow_started_at as u64,
+            escrow_ends_at as u64,
+            max_stake_duration,
+        );
+        assert_eq!(reference_power, new_power);
+    }
+
+    proptest! {
+        #[test]
+        fn test_identical_to_locked_voter(
+            escrow_amount in 0..=(u64::MAX >> 8),
+            max_stake_vote_multiplier: u8,
+            period_start_ts in 0..=i64::MAX,
+            escrow_started_at in 0..=i64::MAX,
+            escrow_ends_at in 0..=i64::MAX,
+            max_stake_duration: u64,
+        ) {
+            run_test_identical_to_locked_voter(
+                escrow_amount,
+                max_stake_vote_multiplier,
+                period_start_ts,
+                escrow_started_at,
+                escrow_ends_at,
+                max_stake_duration
+            );
+        }
+    }
+
+    #[test]
+    fn test_project_escrow_history() {
+        let geometry = EraGeometry::new(86_400 * 7, COMMON_ERA_UNIX_TS).unwrap();
+        let period_seconds = geometry.period_seconds;
+        let era_start = geometry.era_start_ts(Era(2)).unwrap();
+        // locked for 10 periods starting halfway through period 3, at the maximum duration
+        let started_at = era_start + 3 * period_seconds + period_seconds / 2;
+        let ends_at = started_at + 10 * period_seconds;
+        let ve_balances = project_escrow_history(
+            1_000,
+            10,
+            started_at,
+            ends_at,
+            10 * period_seconds,
+            &geometry,
+            2,
+        )
+        .unwrap();
+
+        assert_eq!(ve_balances[..4], [0; 4]);
+        // 9.5 periods of 10 remaining
+        assert_eq!(ve_balances[4], 9_500);
+        assert_eq!(ve_balances[13], 500);
+        assert!(ve_balances[14..].iter().all(|balance| *balance == 0));
+        assert!(ve_balances[4..14].windows(2).all(|pair| pair[0] > pair[1]));
+
+        assert_eq!(
+            project_escrow_history(
+                u64::MAX,
+                2,
+                started_at,
+                ends_at,
+                period_seconds,
+                &geometry,
+                2
+            ),
+            None
+        );
+    }
+}
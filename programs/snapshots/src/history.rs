@@ -0,0 +1,1510 @@
+//! Balance tracking for [LockerHistory], [EscrowHistory] and [CompactEscrowHistory].
+//!
+//! Before its final `max_stake_duration`, a lockup's voting power decays linearly towards zero.
+//! The total voting power of all such lockups at the start of a period `p` can therefore be
+//! computed from a slope and a bias:
+//!
+//! ```text
+//! ve_balance(p) = period_seconds * (bias - p * slope) / max_stake_duration - end_offset
+//! ```
+//!
+//! where `slope` is the sum of each lockup's voting power at max lockup, `bias` is the sum of
+//! that power multiplied by the period in which the lockup ends, and `end_offset` accounts for
+//! lockups ending partway through a period. A lockup ending within the era schedules the removal
+//! of its slope at the period it ends, so syncing an [locked_voter::Escrow] only touches a
+//! constant number of fields regardless of the length of its lockup.
+//!
+//! Period totals are written to [LockerHistory::ve_balances] lazily, once the period has started.
+//! Every lockup is rounded down separately, so a materialized total may exceed the sum of the
+//! [EscrowHistory] balances of that period by less than two per lockup, but it is never lower.
+//!
+//! Lockups that are longer than `max_stake_duration` (which is only possible if the
+//! [locked_voter::Locker] parameters changed after the lockup was created) do not decay
+//! linearly. These are written to every future period directly.
+//!
+//! When the [locked_voter::Locker] parameters change, the [LockerHistory] is reset: the
+//! contributions of all lockups to periods that have not been materialized are discarded and
+//! the new parameters are recorded. Each escrow that was synced before the reset is moved to the
+//! new parameters the next time it is synced, including in any periods that were materialized in
+//! the meantime. Until every lockup that was still active has been resynced, the totals of those
+//! periods are incomplete.
+
+use crate::*;
+use ::u128::{mul_div_u64, U128};
+use anchor_lang::Discriminator;
+use num_traits::ToPrimitive;
+use std::cell::Ref;
+
+/// A lockup of an [locked_voter::Escrow], as recorded in an [EscrowHistory].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Lockup {
+    /// Voting power if the lockup was at the maximum duration.
+    pub power: u64,
+    /// When the lockup started.
+    pub started_at: u64,
+    /// When the lockup ends.
+    pub ends_at: u64,
+}
+
+/// How a [Lockup] contributes to a [LockerHistory].
+enum Tracking {
+    /// The lockup has no voting power in any of the remaining periods of the era.
+    None,
+    /// The lockup decays linearly and is tracked by slope.
+    Slope {
+        /// First period (relative to the start of the era) at which the lockup has ended.
+        end_period: u64,
+        /// Voting power lost from the lockup ending partway through the period before `end_period`.
+        end_offset: u64,
+    },
+    /// The lockup is written to each period directly.
+    Direct,
+}
+
+/// The lockup of an [locked_voter::Escrow] as synced into a [LockerHistory],
+/// which is stored in either an [EscrowHistory] or a [CompactEscrowHistory].
+pub(crate) trait EscrowLockups {
+    /// The [locked_voter::Escrow] being tracked.
+    fn escrow(&self) -> Pubkey;
+    /// The era.
+    fn era(&self) -> u16;
+    /// Where the lockup is tracked, as in [EscrowHistory::shard].
+    fn shard(&self) -> u16;
+    /// Sets where the lockup is tracked.
+    fn set_shard(&mut self, shard: u16);
+    /// [LockerHistory::params_version] of the history the lockup is tracked in when it was last synced.
+    fn params_version(&self) -> u16;
+    /// Number of periods, starting from period 0, whose balances can no longer change.
+    fn materialized_periods(&self) -> u16;
+    /// [LockerHistory::max_stake_vote_multiplier] when the escrow was last synced.
+    fn max_stake_vote_multiplier(&self) -> u8;
+    /// [LockerHistory::max_stake_duration] when the escrow was last synced.
+    fn max_stake_duration(&self) -> u64;
+    /// The [Lockup] recorded when the escrow was last synced.
+    fn lockup(&self) -> Lockup;
+    /// Returns true if no more lockups can be recorded, in which case
+    /// [EscrowLockups::record_lockup] may fail.
+    fn is_full(&self) -> bool;
+    /// Records the [Lockup] of the escrow and the parameters of the [LockerHistory] it is tracked in,
+    /// which determine the balances of all periods that have not been materialized.
+    fn record_lockup(&mut self, lockup: &Lockup, locker_history: &LockerHistory) -> Option<()>;
+    /// Fixes the balances of all periods before `num_periods` to those of the recorded lockup.
+    fn materialize(&mut self, num_periods: usize) -> Option<()>;
+}
+
+/// Calculates the start timestamp of a period, which may be [ERA_NUM_PERIODS] for the end of the era.
+fn calculate_period_start_ts_of_era(
+    geometry: &EraGeometry,
+    era: u16,
+    period: usize,
+) -> Option<u64> {
+    geometry
+        .era_start_ts(Era(era))?
+        .checked_add(period.to_u64()?.checked_mul(geometry.period_seconds)?)
+}
+
+/// Calculates the number of periods of the era that have started at `now`.
+pub(crate) fn calculate_started_periods(
+    geometry: &EraGeometry,
+    era: u16,
+    now: u64,
+) -> Option<usize> {
+    let era_start_ts = geometry.era_start_ts(Era(era))?;
+    if now < era_start_ts {
+        return Some(0);
+    }
+    let started = now
+        .checked_sub(era_start_ts)?
+        .checked_div(geometry.period_seconds)?
+        .checked_add(1)?
+        .min(ERA_NUM_PERIODS.to_u64()?);
+    started.to_usize()
+}
+
+/// Returns true if the era has ended and `retention_seconds` have passed since, at `now`.
+pub(crate) fn has_retention_expired(
+    geometry: &EraGeometry,
+    era: u16,
+    retention_seconds: u64,
+    now: u64,
+) -> Option<bool> {
+    let era_end_ts = geometry.era_start_ts(Era(era).checked_add(1)?)?;
+    Some(now >= era_end_ts.checked_add(retention_seconds)?)
+}
+
+impl Lockup {
+    /// First period of the era that starts once this lockup has ended, or [ERA_NUM_PERIODS]
+    /// if it ends after the era. Lockups without voting power end at period 0.
+    pub(crate) fn calculate_end_period(&self, geometry: &EraGeometry, era: u16) -> Option<usize> {
+        let era_start_ts = geometry.era_start_ts(Era(era))?;
+        if self.power == 0 || self.ends_at <= era_start_ts {
+            return Some(0);
+        }
+        let period_seconds = geometry.period_seconds;
+        self.ends_at
+            .checked_sub(era_start_ts)?
+            .checked_add(period_seconds.checked_sub(1)?)?
+            .checked_div(period_seconds)?
+            .min(ERA_NUM_PERIODS.to_u64()?)
+            .to_usize()
+    }
+
+    /// Calculates the voting power of this lockup at the start of a period.
+    fn calculate_power(
+        &self,
+        geometry: &EraGeometry,
+        era: u16,
+        period: usize,
+        max_stake_duration: u64,
+    ) -> Option<u64> {
+        calculate_voter_power_for_period(
+            self.power,
+            calculate_period_start_ts_of_era(geometry, era, period)?,
+            self.started_at,
+            self.ends_at,
+            max_stake_duration,
+        )
+    }
+}
+
+impl LockerHistory {
+    /// Determines how a [Lockup] contributes to this history from the given period onwards,
+    /// if its balances are computed with the given `max_stake_duration`.
+    ///
+    /// The lockup must have started before `since_period`.
+    fn tracking(
+        &self,
+        lockup: &Lockup,
+        since_period: usize,
+        max_stake_duration: u64,
+    ) -> Option<Tracking> {
+        if lockup.power == 0 || since_period >= ERA_NUM_PERIODS {
+            return Some(Tracking::None);
+        }
+        let geometry = self.geometry();
+        let since_ts = calculate_period_start_ts_of_era(&geometry, self.era, since_period)?;
+        if lockup.ends_at <= since_ts {
+            return Some(Tracking::None);
+        }
+        if lockup.ends_at.checked_sub(since_ts)? > max_stake_duration {
+            return Some(Tracking::Direct);
+        }
+
+        let era_start_ts = geometry.era_start_ts(Era(self.era))?;
+        let period_seconds = geometry.period_seconds;
+        let seconds_until_end = lockup.ends_at.checked_sub(era_start_ts)?;
+        // round up to the first period that starts after the lockup ends
+        let end_period = seconds_until_end
+            .checked_add(period_seconds.checked_sub(1)?)?
+            .checked_div(period_seconds)?;
+        let seconds_after_end = end_period
+            .checked_mul(period_seconds)?
+            .checked_sub(seconds_until_end)?;
+        Some(Tracking::Slope {
+            end_period,
+            end_offset: mul_div_u64(lockup.power, seconds_after_end, max_stake_duration)?,
+        })
+    }
+
+    /// Calculates the balance of all lockups tracked by slope at the given period.
+    fn calculate_slope_ve_balance(&self, period: usize) -> Option<u64> {
+        if self.slope == 0 {
+            return Some(0);
+        }
+        let remaining = self
+            .bias
+            .checked_sub(period.to_u128()?.checked_mul(self.slope.into())?)?
+            .checked_mul(self.geometry().period_seconds.into())?;
+        let ve_balance = U128::from(remaining).checked_div(self.max_stake_duration.into())?;
+        if ve_balance.0[1] != 0 {
+            return None;
+        }
+        ve_balance.0[0].checked_sub(self.end_offset)
+    }
+
+    /// Materializes the totals of all periods before `num_periods`.
+    pub fn materialize(&mut self, num_periods: usize) -> Option<()> {
+        let start = self.materialized_periods.to_usize()?;
+        for period in start..num_periods.min(ERA_NUM_PERIODS) {
+            // remove lockups that ended before this period started
+            let ended_slope = self.slope_changes[period];
+            self.slope = self.slope.checked_sub(ended_slope)?;
+            self.bias = self
+                .bias
+                .checked_sub(period.to_u128()?.checked_mul(ended_slope.into())?)?;
+            self.end_offset = self
+                .end_offset
+                .checked_sub(self.end_offset_changes[period])?;
+            self.voters = self.voters.checked_sub(self.voter_changes[period].into())?;
+
+            self.ve_balances[period] =
+                self.ve_balances[period].checked_add(self.calculate_slope_ve_balance(period)?)?;
+            self.ve_counts[period] = self.ve_counts[period].checked_add(self.voters)?;
+            self.materialized_periods = period.checked_add(1)?.to_u16()?;
+        }
+        Some(())
+    }
+
+    /// Adds a [Lockup] to all periods that have not been materialized.
+    pub(crate) fn add_lockup(&mut self, lockup: &Lockup) -> Option<()> {
+        let since_period = self.materialized_periods.to_usize()?;
+        match self.tracking(lockup, since_period, self.max_stake_duration)? {
+            Tracking::None => {}
+            Tracking::Slope {
+                end_period,
+                end_offset,
+            } => {
+                self.slope = self.slope.checked_add(lockup.power)?;
+                self.bias = self
+                    .bias
+                    .checked_add(end_period.to_u128()?.checked_mul(lockup.power.into())?)?;
+                self.end_offset = self.end_offset.checked_add(end_offset)?;
+                self.voters = self.voters.checked_add(1)?;
+                if let Some(end_period) = end_period.to_usize().filter(|p| *p < ERA_NUM_PERIODS) {
+                    self.slope_changes[end_period] =
+                        self.slope_changes[end_period].checked_add(lockup.power)?;
+                    self.end_offset_changes[end_period] =
+                        self.end_offset_changes[end_period].checked_add(end_offset)?;
+                    self.voter_changes[end_period] =
+                        self.voter_changes[end_period].checked_add(1)?;
+                }
+            }
+            Tracking::Direct => {
+                for period in since_period..ERA_NUM_PERIODS {
+                    let ve_balance = lockup.calculate_power(
+                        &self.geometry(),
+                        self.era,
+                        period,
+                        self.max_stake_duration,
+                    )?;
+                    if ve_balance == 0 {
+                        continue;
+                    }
+                    self.ve_balances[period] = self.ve_balances[period].checked_add(ve_balance)?;
+                    self.ve_counts[period] = self.ve_counts[period].checked_add(1)?;
+                }
+            }
+        }
+        Some(())
+    }
+
+    /// Removes a [Lockup] that was added when `added_at_period` periods were materialized
+    /// from all periods that have not been materialized.
+    pub(crate) fn remove_lockup(&mut self, lockup: &Lockup, added_at_period: usize) -> Option<()> {
+        let since_period = self.materialized_periods.to_usize()?;
+        match self.tracking(lockup, added_at_period, self.max_stake_duration)? {
+            Tracking::None => {}
+            Tracking::Slope {
+                end_period,
+                end_offset,
+            } => {
+                // the lockup was already removed when its end period was materialized
+                if end_period < since_period.to_u64()? {
+                    return Some(());
+                }
+                self.slope = self.slope.checked_sub(lockup.power)?;
+                self.bias = self
+                    .bias
+                    .checked_sub(end_period.to_u128()?.checked_mul(lockup.power.into())?)?;
+                self.end_offset = self.end_offset.checked_sub(end_offset)?;
+                self.voters = self.voters.checked_sub(1)?;
+                if let Some(end_period) = end_period.to_usize().filter(|p| *p < ERA_NUM_PERIODS) {
+                    self.slope_changes[end_period] =
+                        self.slope_changes[end_period].checked_sub(lockup.power)?;
+                    self.end_offset_changes[end_period] =
+                        self.end_offset_changes[end_period].checked_sub(end_offset)?;
+                    self.voter_changes[end_period] =
+                        self.voter_changes[end_period].checked_sub(1)?;
+                }
+            }
+            Tracking::Direct => {
+                for period in since_period..ERA_NUM_PERIODS {
+                    let ve_balance = lockup.calculate_power(
+                        &self.geometry(),
+                        self.era,
+                        period,
+                        self.max_stake_duration,
+                    )?;
+                    if ve_balance == 0 {
+                        continue;
+                    }
+                    self.ve_balances[period] = self.ve_balances[period].checked_sub(ve_balance)?;
+                    self.ve_counts[period] = self.ve_counts[period].checked_sub(1)?;
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+impl LockerHistory {
+    /// Discards the contributions of all lockups to the periods that have not been materialized,
+    /// and records new [locked_voter::LockerParams].
+    pub(crate) fn reset(
+        &mut self,
+        max_stake_duration: u64,
+        max_stake_vote_multiplier: u8,
+    ) -> Option<()> {
+        let resync_period = self.materialized_periods.to_usize()?;
+        // lockups tracked by slope that have not ended, plus those written directly
+        let pending_resyncs = if resync_period < ERA_NUM_PERIODS {
+            self.voters
+                .checked_sub(self.voter_changes[resync_period].into())?
+                .checked_add(self.ve_counts[resync_period])?
+        } else {
+            0
+        };
+
+        self.slope = 0;
+        self.bias = 0;
+        self.end_offset = 0;
+        self.voters = 0;
+        for period in resync_period..ERA_NUM_PERIODS {
+            self.ve_balances[period] = 0;
+            self.ve_counts[period] = 0;
+            self.slope_changes[period] = 0;
+            self.end_offset_changes[period] = 0;
+            self.voter_changes[period] = 0;
+        }
+
+        self.max_stake_duration = max_stake_duration;
+        self.max_stake_vote_multiplier = max_stake_vote_multiplier;
+        self.params_version = self.params_version.checked_add(1)?;
+        self.resync_period = resync_period.to_u16()?;
+        self.pending_resyncs = pending_resyncs.to_u32()?;
+        self.resyncing = u8::from(pending_resyncs != 0);
+        Some(())
+    }
+
+    /// Returns true if the lockup of an [EscrowHistory] synced before the last reset
+    /// was counted in [LockerHistory::pending_resyncs].
+    fn is_pending_resync<H: EscrowLockups>(&self, escrow_history: &H) -> Option<bool> {
+        if self.resyncing == 0 {
+            return Some(false);
+        }
+        let lockup = escrow_history.lockup();
+        let resync_period = self.resync_period.to_usize()?;
+        Some(
+            match self.tracking(
+                &lockup,
+                escrow_history.materialized_periods().to_usize()?,
+                escrow_history.max_stake_duration(),
+            )? {
+                Tracking::None => false,
+                Tracking::Slope { end_period, .. } => end_period > resync_period.to_u64()?,
+                Tracking::Direct => {
+                    lockup.calculate_power(
+                        &self.geometry(),
+                        self.era,
+                        resync_period,
+                        escrow_history.max_stake_duration(),
+                    )? != 0
+                }
+            },
+        )
+    }
+
+    /// Moves the lockup of an [EscrowHistory] synced before the last reset to the current
+    /// parameters, and materializes both histories' balances of all periods before `num_periods`.
+    ///
+    /// Balances before the [LockerHistory::resync_period] keep the parameters the lockup was synced
+    /// with. Later periods that were materialized since the reset are recomputed, and the lockup is
+    /// added to all periods that have not been materialized.
+    pub(crate) fn resync_escrow_history<H: EscrowLockups>(
+        &mut self,
+        escrow_history: &mut H,
+        num_periods: usize,
+    ) -> Option<()> {
+        if self.is_pending_resync(escrow_history)? {
+            self.pending_resyncs = self.pending_resyncs.checked_sub(1)?;
+            if self.pending_resyncs == 0 {
+                self.resyncing = 0;
+            }
+        }
+
+        let resync_period = self.resync_period.to_usize()?;
+        self.materialize(num_periods)?;
+        escrow_history.materialize(num_periods.min(resync_period))?;
+
+        let prev_lockup = escrow_history.lockup();
+        let lockup = Lockup {
+            power: if prev_lockup.power == 0 {
+                0
+            } else {
+                prev_lockup
+                    .power
+                    .checked_div(escrow_history.max_stake_vote_multiplier().into())?
+                    .checked_mul(self.max_stake_vote_multiplier.into())?
+            },
+            ..prev_lockup
+        };
+        escrow_history.record_lockup(&lockup, self)?;
+        escrow_history.materialize(num_periods)?;
+
+        for period in resync_period..self.materialized_periods.to_usize()? {
+            let ve_balance = lockup.calculate_power(
+                &self.geometry(),
+                self.era,
+                period,
+                self.max_stake_duration,
+            )?;
+            if ve_balance == 0 {
+                continue;
+            }
+            self.ve_balances[period] = self.ve_balances[period].checked_add(ve_balance)?;
+            self.ve_counts[period] = self.ve_counts[period].checked_add(1)?;
+        }
+        self.add_lockup(&lockup)
+    }
+}
+
+impl LockerHistory {
+    /// Counts a new [LockerHistoryShard] towards the shards of every period, materializing all
+    /// periods before `num_periods` first.
+    ///
+    /// The new shard has no balances in the periods that have been materialized, so it counts as
+    /// merged into them. Otherwise, creating a shard would undo the finality of those periods.
+    /// Returns the number of periods the shard counts as merged into.
+    pub(crate) fn add_shard(&mut self, num_periods: usize) -> Option<u16> {
+        self.num_shards = self.num_shards.checked_add(1)?;
+        self.materialize(num_periods)?;
+        for period in 0..self.materialized_periods.to_usize()? {
+            self.merged_shards[period] = self.merged_shards[period].checked_add(1)?;
+        }
+        Some(self.materialized_periods)
+    }
+
+    /// Adds the balances of a [LockerHistoryShard] to the periods before `num_periods`
+    /// that it has not been merged into yet.
+    pub(crate) fn merge_shard(
+        &mut self,
+        shard: &mut LockerHistoryShard,
+        num_periods: usize,
+    ) -> Option<()> {
+        self.materialize(num_periods)?;
+        shard.history.materialize(num_periods)?;
+
+        // Periods of a shard that is being resynced may still change.
+        let start = shard.merged_periods.to_usize()?;
+        let end = if shard.history.resyncing != 0 {
+            shard
+                .history
+                .materialized_periods
+                .min(shard.history.resync_period)
+                .max(shard.merged_periods)
+        } else {
+            shard.history.materialized_periods
+        };
+        for period in start..end.to_usize()? {
+            self.ve_balances[period] =
+                self.ve_balances[period].checked_add(shard.history.ve_balances[period])?;
+            self.ve_counts[period] =
+                self.ve_counts[period].checked_add(shard.history.ve_counts[period])?;
+            self.merged_shards[period] = self.merged_shards[period].checked_add(1)?;
+        }
+        shard.merged_periods = end;
+        Some(())
+    }
+}
+
+impl EscrowLockups for EscrowHistory {
+    fn escrow(&self) -> Pubkey {
+        self.escrow
+    }
+
+    fn era(&self) -> u16 {
+        self.era
+    }
+
+    fn shard(&self) -> u16 {
+        self.shard
+    }
+
+    fn set_shard(&mut self, shard: u16) {
+        self.shard = shard;
+    }
+
+    fn params_version(&self) -> u16 {
+        self.params_version
+    }
+
+    fn materialized_periods(&self) -> u16 {
+        self.materialized_periods
+    }
+
+    fn max_stake_vote_multiplier(&self) -> u8 {
+        self.max_stake_vote_multiplier
+    }
+
+    fn max_stake_duration(&self) -> u64 {
+        self.max_stake_duration
+    }
+
+    fn lockup(&self) -> Lockup {
+        Lockup {
+            power: self.power,
+            started_at: self.started_at,
+            ends_at: self.ends_at,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        false
+    }
+
+    fn record_lockup(&mut self, lockup: &Lockup, locker_history: &LockerHistory) -> Option<()> {
+        self.power = lockup.power;
+        self.started_at = lockup.started_at;
+        self.ends_at = lockup.ends_at;
+        self.params_version = locker_history.params_version;
+        self.max_stake_vote_multiplier = locker_history.max_stake_vote_multiplier;
+        self.max_stake_duration = locker_history.max_stake_duration;
+        self.period_seconds = locker_history.period_seconds;
+        self.origin_ts = locker_history.origin_ts;
+        Some(())
+    }
+
+    /// Materializes the balances of all periods before `num_periods` from the recorded lockup.
+    fn materialize(&mut self, num_periods: usize) -> Option<()> {
+        let start = self.materialized_periods.to_usize()?;
+        let end = num_periods.min(ERA_NUM_PERIODS);
+        if start >= end {
+            return Some(());
+        }
+        let lockup = self.lockup();
+        for period in start..end {
+            self.ve_balances[period] = lockup.calculate_power(
+                &self.geometry(),
+                self.era,
+                period,
+                self.max_stake_duration,
+            )?;
+        }
+        self.materialized_periods = end.to_u16()?;
+        Some(())
+    }
+}
+
+impl EscrowHistory {
+    /// Calculates the veBalance of the [locked_voter::Escrow] at the given period.
+    ///
+    /// Periods that have not been materialized are computed from the recorded lockup.
+    pub fn calculate_ve_balance(&self, period: u8) -> Option<u64> {
+        let period = period.to_usize()?;
+        if period < self.materialized_periods.to_usize()? {
+            return Some(self.ve_balances[period]);
+        }
+        self.lockup()
+            .calculate_power(&self.geometry(), self.era, period, self.max_stake_duration)
+    }
+
+    /// Copies a [CompactEscrowHistory], materializing the balances of its materialized periods.
+    pub(crate) fn expand(&mut self, compact: &CompactEscrowHistory) -> Option<()> {
+        let materialized_periods = compact.materialized_periods.to_usize()?;
+        for period in 0..materialized_periods {
+            self.ve_balances[period] = compact.get_balance(period.to_u8()?)?;
+        }
+        let lockup = compact.lockup();
+        self.power = lockup.power;
+        self.started_at = lockup.started_at;
+        self.ends_at = lockup.ends_at;
+        self.max_stake_duration = compact.max_stake_duration();
+        self.materialized_periods = compact.materialized_periods;
+        self.shard = compact.shard;
+        self.params_version = compact.params_version;
+        self.max_stake_vote_multiplier = compact.max_stake_vote_multiplier;
+        self.period_seconds = compact.period_seconds;
+        self.origin_ts = compact.origin_ts;
+        Some(())
+    }
+}
+
+impl EscrowLockups for CompactEscrowHistory {
+    fn escrow(&self) -> Pubkey {
+        self.escrow
+    }
+
+    fn era(&self) -> u16 {
+        self.era
+    }
+
+    fn shard(&self) -> u16 {
+        self.shard
+    }
+
+    fn set_shard(&mut self, shard: u16) {
+        self.shard = shard;
+    }
+
+    fn params_version(&self) -> u16 {
+        self.params_version
+    }
+
+    fn materialized_periods(&self) -> u16 {
+        self.materialized_periods
+    }
+
+    fn max_stake_vote_multiplier(&self) -> u8 {
+        self.max_stake_vote_multiplier
+    }
+
+    fn max_stake_duration(&self) -> u64 {
+        self.last_checkpoint()
+            .map(|index| self.checkpoint_max_stake_durations[index])
+            .unwrap_or(0)
+    }
+
+    fn lockup(&self) -> Lockup {
+        self.last_checkpoint()
+            .map(|index| self.checkpoint_lockup(index))
+            .unwrap_or_default()
+    }
+
+    fn is_full(&self) -> bool {
+        usize::from(self.num_checkpoints) >= COMPACT_ESCROW_HISTORY_CHECKPOINTS
+    }
+
+    /// Records a checkpoint starting at the first period that has not been materialized,
+    /// unless the lockup has not changed.
+    fn record_lockup(&mut self, lockup: &Lockup, locker_history: &LockerHistory) -> Option<()> {
+        self.params_version = locker_history.params_version;
+        self.max_stake_vote_multiplier = locker_history.max_stake_vote_multiplier;
+        self.period_seconds = locker_history.period_seconds;
+        self.origin_ts = locker_history.origin_ts;
+
+        let max_stake_duration = locker_history.max_stake_duration;
+        let index = match self.last_checkpoint() {
+            Some(last) => {
+                if self.checkpoint_lockup(last) == *lockup
+                    && self.checkpoint_max_stake_durations[last] == max_stake_duration
+                {
+                    return Some(());
+                }
+                if self.checkpoint_periods[last] == self.materialized_periods {
+                    // none of the periods of the last checkpoint have been materialized
+                    last
+                } else {
+                    last.checked_add(1)?
+                }
+            }
+            // periods before the first checkpoint have no balance
+            None if lockup.power == 0 => return Some(()),
+            None => 0,
+        };
+        if index >= COMPACT_ESCROW_HISTORY_CHECKPOINTS {
+            return None;
+        }
+        self.checkpoint_periods[index] = self.materialized_periods;
+        self.checkpoint_powers[index] = lockup.power;
+        self.checkpoint_started_at[index] = lockup.started_at;
+        self.checkpoint_ends_at[index] = lockup.ends_at;
+        self.checkpoint_max_stake_durations[index] = max_stake_duration;
+        self.num_checkpoints = index.checked_add(1)?.to_u8()?;
+        Some(())
+    }
+
+    /// Balances are computed from the checkpoints, so this only records that
+    /// the periods can no longer change.
+    fn materialize(&mut self, num_periods: usize) -> Option<()> {
+        let end = num_periods.min(ERA_NUM_PERIODS).to_u16()?;
+        self.materialized_periods = self.materialized_periods.max(end);
+        Some(())
+    }
+}
+
+impl CompactEscrowHistory {
+    /// Index of the last checkpoint, if any.
+    fn last_checkpoint(&self) -> Option<usize> {
+        usize::from(self.num_checkpoints).checked_sub(1)
+    }
+
+    /// The [Lockup] of a checkpoint.
+    fn checkpoint_lockup(&self, index: usize) -> Lockup {
+        Lockup {
+            power: self.checkpoint_powers[index],
+            started_at: self.checkpoint_started_at[index],
+            ends_at: self.checkpoint_ends_at[index],
+        }
+    }
+
+    /// Calculates the veBalance of the [locked_voter::Escrow] at the given period.
+    pub fn get_balance(&self, period: u8) -> Option<u64> {
+        let period = period.to_usize()?;
+        let checkpoint = (0..usize::from(self.num_checkpoints))
+            .rev()
+            .find(|&index| usize::from(self.checkpoint_periods[index]) <= period);
+        match checkpoint {
+            Some(index) => self.checkpoint_lockup(index).calculate_power(
+                &self.geometry(),
+                self.era,
+                period,
+                self.checkpoint_max_stake_durations[index],
+            ),
+            None => Some(0),
+        }
+    }
+
+    /// Copies an [EscrowHistory] into a single checkpoint.
+    ///
+    /// Returns false if the materialized balances of the history are not all either
+    /// zero before some period, or those of its recorded lockup from that period onwards.
+    pub(crate) fn compact(&mut self, escrow_history: &EscrowHistory) -> Option<bool> {
+        let materialized_periods = escrow_history.materialized_periods.to_usize()?;
+        let materialized = escrow_history.ve_balances.get(..materialized_periods)?;
+        let since_period = materialized
+            .iter()
+            .position(|ve_balance| *ve_balance != 0)
+            .unwrap_or(materialized_periods);
+        let lockup = escrow_history.lockup();
+        for (period, ve_balance) in materialized.iter().enumerate().skip(since_period) {
+            if lockup.calculate_power(
+                &escrow_history.geometry(),
+                escrow_history.era,
+                period,
+                escrow_history.max_stake_duration,
+            )? != *ve_balance
+            {
+                return Some(false);
+            }
+        }
+
+        self.shard = escrow_history.shard;
+        self.params_version = escrow_history.params_version;
+        self.max_stake_vote_multiplier = escrow_history.max_stake_vote_multiplier;
+        self.period_seconds = escrow_history.period_seconds;
+        self.origin_ts = escrow_history.origin_ts;
+        if lockup.power != 0 {
+            self.checkpoint_periods[0] = since_period.to_u16()?;
+            self.checkpoint_powers[0] = lockup.power;
+            self.checkpoint_started_at[0] = lockup.started_at;
+            self.checkpoint_ends_at[0] = lockup.ends_at;
+            self.checkpoint_max_stake_durations[0] = escrow_history.max_stake_duration;
+            self.num_checkpoints = 1;
+        }
+        self.materialized_periods = escrow_history.materialized_periods;
+        Some(true)
+    }
+}
+
+/// The balances of an [locked_voter::Escrow] over an era, for instructions that only read them,
+/// which accept either an [EscrowHistory] or a [CompactEscrowHistory].
+pub(crate) trait EscrowBalances: EscrowLockups {
+    /// The [EraGeometry] of the periods of the history.
+    fn geometry(&self) -> EraGeometry;
+    /// Calculates the veBalance of the [locked_voter::Escrow] at the given period.
+    fn calculate_ve_balance(&self, period: u8) -> Option<u64>;
+}
+
+impl EscrowBalances for EscrowHistory {
+    fn geometry(&self) -> EraGeometry {
+        EscrowHistory::geometry(self)
+    }
+
+    fn calculate_ve_balance(&self, period: u8) -> Option<u64> {
+        EscrowHistory::calculate_ve_balance(self, period)
+    }
+}
+
+impl EscrowBalances for CompactEscrowHistory {
+    fn geometry(&self) -> EraGeometry {
+        CompactEscrowHistory::geometry(self)
+    }
+
+    fn calculate_ve_balance(&self, period: u8) -> Option<u64> {
+        self.get_balance(period)
+    }
+}
+
+/// An [EscrowHistory] or a [CompactEscrowHistory], told apart by its discriminator.
+pub(crate) enum AnyEscrowHistory<'info> {
+    /// An [EscrowHistory].
+    Full(AccountLoader<'info, EscrowHistory>),
+    /// A [CompactEscrowHistory].
+    Compact(AccountLoader<'info, CompactEscrowHistory>),
+}
+
+impl<'info> AnyEscrowHistory<'info> {
+    /// Checks that the account is an [EscrowHistory] or a [CompactEscrowHistory] of this program.
+    pub(crate) fn try_from(info: &AccountInfo<'info>) -> Result<Self> {
+        let is_compact =
+            info.try_borrow_data()?.get(..8) == Some(&CompactEscrowHistory::discriminator()[..]);
+        if is_compact {
+            Ok(Self::Compact(AccountLoader::try_from(info)?))
+        } else {
+            Ok(Self::Full(AccountLoader::try_from(info)?))
+        }
+    }
+
+    /// Loads the balances of the history.
+    pub(crate) fn load(&self) -> Result<Ref<'_, dyn EscrowBalances>> {
+        Ok(match self {
+            Self::Full(loader) => {
+                Ref::map(loader.load()?, |history| history as &dyn EscrowBalances)
+            }
+            Self::Compact(loader) => {
+                Ref::map(loader.load()?, |history| history as &dyn EscrowBalances)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    const MAX_STAKE_DURATION: u64 = 86_400 * 365 * 5;
+
+    /// Builds a [Lockup] started before period `since_period` of era 1, from offsets in seconds.
+    fn make_lockup(power: u64, since_period: usize, ends_after: u64) -> Lockup {
+        let since_ts =
+            calculate_period_start_ts_of_era(&EraGeometry::DEFAULT, 1, since_period).unwrap();
+        Lockup {
+            power,
+            started_at: since_ts - 1,
+            ends_at: since_ts - 1 + ends_after,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_materialized_totals_bound_escrow_balances(
+            lockups in prop::collection::vec(
+                (1..=(u64::MAX >> 12), 0..ERA_NUM_PERIODS, 1..=MAX_STAKE_DURATION),
+                1..8
+            ),
+        ) {
+            let mut locker_history = LockerHistory {
+                era: 1,
+                max_stake_duration: MAX_STAKE_DURATION,
+                ..Default::default()
+            };
+
+            let mut lockups = lockups;
+            lockups.sort_by_key(|(_, since_period, _)| *since_period);
+            let mut escrow_histories = vec![];
+            for (power, since_period, ends_after) in lockups {
+                locker_history.materialize(since_period).unwrap();
+                let lockup = make_lockup(power, since_period, ends_after);
+                locker_history.add_lockup(&lockup).unwrap();
+
+                let mut escrow_history = EscrowHistory {
+                    era: 1,
+                    ..Default::default()
+                };
+                escrow_history.materialize(since_period).unwrap();
+                escrow_history.record_lockup(&lockup, &locker_history).unwrap();
+                escrow_histories.push(escrow_history);
+            }
+
+            locker_history.materialize(ERA_NUM_PERIODS).unwrap();
+            for escrow_history in escrow_histories.iter_mut() {
+                escrow_history.materialize(ERA_NUM_PERIODS).unwrap();
+            }
+
+            let num_lockups = escrow_histories.len() as u64;
+            for period in 0..ERA_NUM_PERIODS {
+                let sum: u64 = escrow_histories
+                    .iter()
+                    .map(|h| h.ve_balances[period])
+                    .sum();
+                let total = locker_history.ve_balances[period];
+                prop_assert!(total >= sum);
+                prop_assert!(total - sum < 2 * num_lockups);
+            }
+        }
+
+        #[test]
+        fn test_compact_escrow_history_matches_escrow_history(
+            lockups in prop::collection::vec(
+                (0..=(u64::MAX >> 12), 0..ERA_NUM_PERIODS, 1..=MAX_STAKE_DURATION),
+                1..=COMPACT_ESCROW_HISTORY_CHECKPOINTS
+            ),
+        ) {
+            let locker_history = LockerHistory {
+                era: 1,
+                max_stake_duration: MAX_STAKE_DURATION,
+                ..Default::default()
+            };
+            let mut escrow_history = EscrowHistory {
+                era: 1,
+                ..Default::default()
+            };
+            let mut compact = CompactEscrowHistory {
+                era: 1,
+                ..Default::default()
+            };
+
+            let mut lockups = lockups;
+            lockups.sort_by_key(|(_, since_period, _)| *since_period);
+            for (power, since_period, ends_after) in lockups {
+                let lockup = make_lockup(power, since_period, ends_after);
+                escrow_history.materialize(since_period).unwrap();
+                escrow_history.record_lockup(&lockup, &locker_history).unwrap();
+                compact.materialize(since_period).unwrap();
+                compact.record_lockup(&lockup, &locker_history).unwrap();
+            }
+            for period in 0..ERA_NUM_PERIODS {
+                prop_assert_eq!(
+                    compact.get_balance(period as u8),
+                    escrow_history.calculate_ve_balance(period as u8)
+                );
+            }
+
+            escrow_history.materialize(ERA_NUM_PERIODS).unwrap();
+            compact.materialize(ERA_NUM_PERIODS).unwrap();
+            let mut expanded = EscrowHistory {
+                era: 1,
+                ..Default::default()
+            };
+            expanded.expand(&compact).unwrap();
+            prop_assert_eq!(expanded, escrow_history);
+        }
+
+        #[test]
+        fn test_remove_lockup_is_inverse_of_add(
+            power in 1..=(u64::MAX >> 12),
+            since_period in 0..ERA_NUM_PERIODS,
+            ends_after in 1..=(MAX_STAKE_DURATION * 2),
+        ) {
+            let mut locker_history = LockerHistory {
+                era: 1,
+                max_stake_duration: MAX_STAKE_DURATION,
+                ..Default::default()
+            };
+            locker_history.materialize(since_period).unwrap();
+            let before = locker_history;
+
+            let lockup = make_lockup(power, since_period, ends_after);
+            locker_history.add_lockup(&lockup).unwrap();
+            locker_history.remove_lockup(&lockup, since_period).unwrap();
+            prop_assert_eq!(locker_history, before);
+        }
+    }
+
+    #[test]
+    fn test_single_lockup_ending_on_period_boundary() {
+        let mut locker_history = LockerHistory {
+            era: 1,
+            max_stake_duration: MAX_STAKE_DURATION,
+            ..Default::default()
+        };
+        let lockup = Lockup {
+            power: 1_000_000,
+            started_at: calculate_period_start_ts_of_era(&EraGeometry::DEFAULT, 1, 0).unwrap(),
+            ends_at: calculate_period_start_ts_of_era(&EraGeometry::DEFAULT, 1, 10).unwrap(),
+        };
+        locker_history.materialize(1).unwrap();
+        locker_history.add_lockup(&lockup).unwrap();
+        locker_history.materialize(ERA_NUM_PERIODS).unwrap();
+
+        for period in 1..ERA_NUM_PERIODS {
+            assert_eq!(
+                locker_history.ve_balances[period],
+                lockup
+                    .calculate_power(&EraGeometry::DEFAULT, 1, period, MAX_STAKE_DURATION)
+                    .unwrap()
+            );
+            assert_eq!(locker_history.ve_counts[period], u64::from(period < 10));
+        }
+    }
+
+    #[test]
+    fn test_retention_expires_after_era_end() {
+        let geometry = EraGeometry::DEFAULT;
+        let era_end_ts = calculate_era_start_ts(2).unwrap();
+        assert!(!has_retention_expired(&geometry, 1, 0, era_end_ts - 1).unwrap());
+        assert!(has_retention_expired(&geometry, 1, 0, era_end_ts).unwrap());
+        assert!(!has_retention_expired(&geometry, 1, 100, era_end_ts + 99).unwrap());
+        assert!(has_retention_expired(&geometry, 1, 100, era_end_ts + 100).unwrap());
+        assert!(has_retention_expired(&geometry, u16::MAX, 0, u64::MAX).is_none());
+    }
+
+    #[test]
+    fn test_decrease_only_changes_future_periods() {
+        let mut locker_history = LockerHistory {
+            era: 1,
+            max_stake_duration: MAX_STAKE_DURATION,
+            ..Default::default()
+        };
+        let lockup = Lockup {
+            power: 1_000_000,
+            started_at: calculate_period_start_ts_of_era(&EraGeometry::DEFAULT, 1, 0).unwrap(),
+            ends_at: calculate_period_start_ts_of_era(&EraGeometry::DEFAULT, 1, 100).unwrap(),
+        };
+        locker_history.materialize(1).unwrap();
+        locker_history.add_lockup(&lockup).unwrap();
+        locker_history.materialize(20).unwrap();
+
+        // the lockup is replaced by a shorter one after period 20 has started
+        let shorter = Lockup {
+            ends_at: calculate_period_start_ts_of_era(&EraGeometry::DEFAULT, 1, 30).unwrap(),
+            ..lockup
+        };
+        locker_history.remove_lockup(&lockup, 1).unwrap();
+        locker_history.add_lockup(&shorter).unwrap();
+        locker_history.materialize(ERA_NUM_PERIODS).unwrap();
+
+        for period in 1..ERA_NUM_PERIODS {
+            let expected = if period < 20 { lockup } else { shorter };
+            assert_eq!(
+                locker_history.ve_balances[period],
+                expected
+                    .calculate_power(&EraGeometry::DEFAULT, 1, period, MAX_STAKE_DURATION)
+                    .unwrap()
+            );
+            assert_eq!(locker_history.ve_counts[period], u64::from(period < 30));
+        }
+    }
+
+    #[test]
+    fn test_resync_after_reset_recomputes_future_periods() {
+        let mut locker_history = LockerHistory {
+            era: 1,
+            max_stake_duration: MAX_STAKE_DURATION,
+            max_stake_vote_multiplier: 10,
+            ..Default::default()
+        };
+        let lockups = [
+            // decays linearly past the reset
+            make_lockup(1_000_000 * 10, 0, MAX_STAKE_DURATION / 2),
+            // written directly once the maximum duration is shortened
+            make_lockup(3_000_000 * 10, 0, MAX_STAKE_DURATION),
+            // ends before the reset
+            make_lockup(5_000_000 * 10, 0, u64::from(PERIOD_SECONDS) * 3),
+        ];
+        let mut escrow_histories = vec![];
+        for lockup in lockups.iter() {
+            locker_history.add_lockup(lockup).unwrap();
+            let mut escrow_history = EscrowHistory {
+                era: 1,
+                ..Default::default()
+            };
+            escrow_history
+                .record_lockup(lockup, &locker_history)
+                .unwrap();
+            escrow_histories.push(escrow_history);
+        }
+        locker_history.materialize(5).unwrap();
+        let before_reset = locker_history;
+
+        locker_history.reset(MAX_STAKE_DURATION / 2, 20).unwrap();
+        assert_eq!(locker_history.resync_period, 5);
+        assert_eq!(locker_history.pending_resyncs, 2);
+        assert!(locker_history.is_period_resyncing(5));
+        assert!(!locker_history.is_period_resyncing(4));
+
+        locker_history.materialize(10).unwrap();
+        for escrow_history in escrow_histories.iter_mut() {
+            locker_history
+                .resync_escrow_history(escrow_history, 10)
+                .unwrap();
+        }
+        assert_eq!(locker_history.resyncing, 0);
+        assert_eq!(locker_history.pending_resyncs, 0);
+
+        locker_history.materialize(ERA_NUM_PERIODS).unwrap();
+        for escrow_history in escrow_histories.iter_mut() {
+            escrow_history.materialize(ERA_NUM_PERIODS).unwrap();
+        }
+        for period in 0..ERA_NUM_PERIODS {
+            if period < 5 {
+                assert_eq!(
+                    locker_history.ve_balances[period],
+                    before_reset.ve_balances[period]
+                );
+            }
+            let sum: u64 = escrow_histories.iter().map(|h| h.ve_balances[period]).sum();
+            let total = locker_history.ve_balances[period];
+            assert!(total >= sum);
+            assert!(total - sum < 2 * lockups.len() as u64);
+        }
+
+        // balances after the reset use the new parameters
+        let resynced = Lockup {
+            power: lockups[0].power * 2,
+            ..lockups[0]
+        };
+        for period in 5..ERA_NUM_PERIODS {
+            assert_eq!(
+                escrow_histories[0].ve_balances[period],
+                resynced
+                    .calculate_power(&EraGeometry::DEFAULT, 1, period, MAX_STAKE_DURATION / 2)
+                    .unwrap()
+            );
+        }
+    }
+
+    /// Adds a [LockerHistoryShard] to a history, like `create_locker_history_shard`
+    /// after `num_periods` periods have started.
+    fn add_shard(locker_history: &mut LockerHistory, num_periods: usize) -> LockerHistoryShard {
+        let merged_periods = locker_history.add_shard(num_periods).unwrap();
+        LockerHistoryShard {
+            locker_history: Pubkey::default(),
+            index: locker_history.num_shards - 1,
+            merged_periods,
+            bump: 0,
+            _padding: [0; 11],
+            history: LockerHistory {
+                era: locker_history.era,
+                max_stake_duration: locker_history.max_stake_duration,
+                max_stake_vote_multiplier: locker_history.max_stake_vote_multiplier,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_merging_shards_finalizes_periods() {
+        let mut locker_history = LockerHistory {
+            era: 1,
+            max_stake_duration: MAX_STAKE_DURATION,
+            max_stake_vote_multiplier: 10,
+            ..Default::default()
+        };
+        // without shards, materialized periods are final
+        locker_history.materialize(2).unwrap();
+        assert!(locker_history.is_period_final(1));
+        assert!(!locker_history.is_period_final(2));
+
+        // a new shard counts as merged into the periods that have started
+        let mut shard_a = add_shard(&mut locker_history, 3);
+        assert_eq!(locker_history.num_shards, 1);
+        assert_eq!(shard_a.merged_periods, 3);
+        assert!(locker_history.merged_shards[..3].iter().all(|&n| n == 1));
+        assert!(locker_history.merged_shards[3..].iter().all(|&n| n == 0));
+        assert_eq!(locker_history.calculate_final_periods(), 3);
+
+        let lockup_a = make_lockup(1_000_000 * 10, 3, MAX_STAKE_DURATION / 2);
+        shard_a.history.materialize(3).unwrap();
+        shard_a.history.add_lockup(&lockup_a).unwrap();
+
+        // periods materialized before the shard is merged are not final
+        let mut shard_b = add_shard(&mut locker_history, 5);
+        assert_eq!(locker_history.num_shards, 2);
+        assert_eq!(shard_b.merged_periods, 5);
+        assert!(locker_history.is_period_final(2));
+        assert!(!locker_history.is_period_final(3));
+        assert_eq!(locker_history.calculate_final_periods(), 3);
+
+        let lockup_b = make_lockup(3_000_000 * 10, 5, MAX_STAKE_DURATION);
+        shard_b.history.materialize(5).unwrap();
+        shard_b.history.add_lockup(&lockup_b).unwrap();
+
+        locker_history.merge_shard(&mut shard_a, 7).unwrap();
+        assert_eq!(shard_a.merged_periods, 7);
+        assert_eq!(&locker_history.merged_shards[3..7], &[2, 2, 1, 1]);
+        assert!(locker_history.is_period_final(4));
+        assert!(!locker_history.is_period_final(5));
+        assert_eq!(locker_history.ve_balances[2], 0);
+        assert_eq!(
+            locker_history.ve_balances[3],
+            shard_a.history.ve_balances[3]
+        );
+        assert_ne!(locker_history.ve_balances[3], 0);
+
+        // merging again within the same periods has no effect
+        let merged = locker_history;
+        let merged_shard = shard_a;
+        locker_history.merge_shard(&mut shard_a, 7).unwrap();
+        assert_eq!(locker_history, merged);
+        assert_eq!(shard_a, merged_shard);
+
+        locker_history.merge_shard(&mut shard_b, 7).unwrap();
+        assert_eq!(shard_b.merged_periods, 7);
+        assert_eq!(locker_history.calculate_final_periods(), 7);
+        for period in 0..7 {
+            assert_eq!(
+                locker_history.ve_balances[period],
+                shard_a.history.ve_balances[period] + shard_b.history.ve_balances[period]
+            );
+            assert_eq!(
+                locker_history.ve_counts[period],
+                shard_a.history.ve_counts[period] + shard_b.history.ve_counts[period]
+            );
+        }
+        assert_eq!(locker_history.ve_counts[5], 2);
+    }
+
+    #[test]
+    fn test_merging_shard_waits_for_resync() {
+        let mut locker_history = LockerHistory {
+            era: 1,
+            max_stake_duration: MAX_STAKE_DURATION,
+            max_stake_vote_multiplier: 10,
+            ..Default::default()
+        };
+        let mut shard = add_shard(&mut locker_history, 0);
+        let lockup = make_lockup(1_000_000 * 10, 0, MAX_STAKE_DURATION / 2);
+        shard.history.add_lockup(&lockup).unwrap();
+        let mut escrow_history = EscrowHistory {
+            era: 1,
+            ..Default::default()
+        };
+        escrow_history
+            .record_lockup(&lockup, &shard.history)
+            .unwrap();
+        locker_history.merge_shard(&mut shard, 3).unwrap();
+        assert_eq!(locker_history.calculate_final_periods(), 3);
+
+        // periods of the shard after the reset are not merged until the escrow is resynced
+        shard.history.reset(MAX_STAKE_DURATION / 2, 20).unwrap();
+        assert_eq!(shard.history.resync_period, 3);
+        assert_eq!(shard.history.pending_resyncs, 1);
+        locker_history.merge_shard(&mut shard, 6).unwrap();
+        assert_eq!(shard.merged_periods, 3);
+        assert_eq!(locker_history.materialized_periods, 6);
+        assert!(locker_history.merged_shards[3..].iter().all(|&n| n == 0));
+        assert_eq!(locker_history.calculate_final_periods(), 3);
+
+        shard
+            .history
+            .resync_escrow_history(&mut escrow_history, 6)
+            .unwrap();
+        assert_eq!(shard.history.resyncing, 0);
+        locker_history.merge_shard(&mut shard, 6).unwrap();
+        assert_eq!(shard.merged_periods, 6);
+        assert_eq!(locker_history.calculate_final_periods(), 6);
+        for period in 0..6 {
+            let total = locker_history.ve_balances[period];
+            let ve_balance = escrow_history.ve_balances[period];
+            assert_eq!(total, shard.history.ve_balances[period]);
+            assert!(total >= ve_balance);
+            assert!(total - ve_balance < 2);
+        }
+        // balances after the reset use the new parameters
+        assert!(escrow_history.ve_balances[3] > escrow_history.ve_balances[2]);
+    }
+
+    #[test]
+    fn test_escrow_history_matches_projection() {
+        let amount = 1_234_567;
+        let max_stake_vote_multiplier = 10;
+        let locker_history = LockerHistory {
+            era: 1,
+            max_stake_duration: MAX_STAKE_DURATION,
+            max_stake_vote_multiplier,
+            ..Default::default()
+        };
+        let lockup = Lockup {
+            power: calculate_power_if_max_lockup(amount, max_stake_vote_multiplier).unwrap(),
+            ..make_lockup(0, 0, MAX_STAKE_DURATION / 3)
+        };
+        let mut escrow_history = EscrowHistory {
+            era: 1,
+            ..Default::default()
+        };
+        escrow_history
+            .record_lockup(&lockup, &locker_history)
+            .unwrap();
+        escrow_history.materialize(ERA_NUM_PERIODS).unwrap();
+
+        let projected = project_escrow_history(
+            amount,
+            max_stake_vote_multiplier,
+            lockup.started_at,
+            lockup.ends_at,
+            MAX_STAKE_DURATION,
+            &EraGeometry::DEFAULT,
+            1,
+        )
+        .unwrap();
+        assert_eq!(escrow_history.ve_balances, projected);
+        assert!(projected[0] > 0);
+    }
+
+    /// Syncs a lockup of `duration` seconds into histories of the given [EraGeometry] and
+    /// checks their balances against the projection, returning the end period of the lockup.
+    fn check_custom_geometry(geometry: &EraGeometry, duration: u64) -> usize {
+        let mut locker_history = LockerHistory {
+            era: 1,
+            max_stake_duration: MAX_STAKE_DURATION,
+            max_stake_vote_multiplier: 10,
+            period_seconds: geometry.period_seconds,
+            origin_ts: geometry.origin_ts,
+            ..Default::default()
+        };
+        let started_at = calculate_period_start_ts_of_era(geometry, 1, 0).unwrap() - 1;
+        let lockup = Lockup {
+            power: calculate_power_if_max_lockup(1_000_000, 10).unwrap(),
+            started_at,
+            ends_at: started_at + duration,
+        };
+        locker_history.add_lockup(&lockup).unwrap();
+        let mut escrow_history = EscrowHistory {
+            era: 1,
+            ..Default::default()
+        };
+        escrow_history
+            .record_lockup(&lockup, &locker_history)
+            .unwrap();
+        assert_eq!(escrow_history.geometry(), *geometry);
+        let mut compact = CompactEscrowHistory {
+            era: 1,
+            ..Default::default()
+        };
+        compact.record_lockup(&lockup, &locker_history).unwrap();
+        assert_eq!(compact.geometry(), *geometry);
+
+        locker_history.materialize(ERA_NUM_PERIODS).unwrap();
+        escrow_history.materialize(ERA_NUM_PERIODS).unwrap();
+        compact.materialize(ERA_NUM_PERIODS).unwrap();
+        let projected = project_escrow_history(
+            1_000_000,
+            10,
+            lockup.started_at,
+            lockup.ends_at,
+            MAX_STAKE_DURATION,
+            geometry,
+            1,
+        )
+        .unwrap();
+        assert_eq!(escrow_history.ve_balances, projected);
+
+        let end_period = lockup.calculate_end_period(geometry, 1).unwrap();
+        for (period, ve_balance) in projected.iter().enumerate() {
+            assert_eq!(
+                compact.calculate_ve_balance(period.try_into().unwrap()),
+                Some(*ve_balance)
+            );
+            let total = locker_history.ve_balances[period];
+            assert!(total >= *ve_balance);
+            assert!(total - ve_balance < 2);
+            assert_eq!(
+                locker_history.ve_counts[period],
+                u64::from(period < end_period)
+            );
+        }
+        end_period
+    }
+
+    #[test]
+    fn test_custom_geometry() {
+        let geometry = EraGeometry::new(86_400 * 7, 1_700_000_000).unwrap();
+        // the lockup ends in period 65 of 7 days
+        assert_eq!(check_custom_geometry(&geometry, MAX_STAKE_DURATION / 4), 66);
+    }
+
+    #[test]
+    fn test_one_day_geometry() {
+        let geometry = EraGeometry::new(86_400, 1_700_000_000).unwrap();
+        // the lockup ends in period 99 of 1 day
+        assert_eq!(check_custom_geometry(&geometry, 86_400 * 100), 100);
+        // lockups longer than the era count in all of its periods
+        assert_eq!(
+            check_custom_geometry(&geometry, MAX_STAKE_DURATION / 4),
+            ERA_NUM_PERIODS
+        );
+    }
+
+    #[test]
+    fn test_geometries_of_new_histories_differ() {
+        // Histories created before a config keep the default geometry,
+        // which never matches that of a config.
+        let geometry = EraGeometry::new(86_400 * 7, EraGeometry::DEFAULT.origin_ts).unwrap();
+        let locker_history = LockerHistory {
+            period_seconds: geometry.period_seconds,
+            origin_ts: geometry.origin_ts,
+            ..Default::default()
+        };
+        assert_eq!(locker_history.geometry(), geometry);
+        assert_ne!(EscrowHistory::default().geometry(), geometry);
+        assert_ne!(CompactEscrowHistory::default().geometry(), geometry);
+        assert_eq!(
+            EscrowHistory::default().geometry(),
+            LockerHistory::default().geometry()
+        );
+    }
+
+    #[test]
+    fn test_compact_escrow_history_full() {
+        let locker_history = LockerHistory {
+            era: 1,
+            max_stake_duration: MAX_STAKE_DURATION,
+            ..Default::default()
+        };
+        let mut compact = CompactEscrowHistory {
+            era: 1,
+            ..Default::default()
+        };
+        // lockups without power are not recorded until the escrow has a balance
+        compact
+            .record_lockup(&make_lockup(0, 0, 1), &locker_history)
+            .unwrap();
+        assert_eq!(compact.num_checkpoints, 0);
+
+        for period in 0..COMPACT_ESCROW_HISTORY_CHECKPOINTS {
+            let lockup = make_lockup(1_000_000, period, MAX_STAKE_DURATION);
+            compact.materialize(period).unwrap();
+            compact.record_lockup(&lockup, &locker_history).unwrap();
+            // a lockup replaced within the same period only uses one checkpoint
+            compact.record_lockup(&lockup, &locker_history).unwrap();
+            let extended = Lockup {
+                ends_at: lockup.ends_at + 1,
+                ..lockup
+            };
+            compact.record_lockup(&extended, &locker_history).unwrap();
+        }
+        assert!(compact.is_full());
+        assert_eq!(
+            usize::from(compact.num_checkpoints),
+            COMPACT_ESCROW_HISTORY_CHECKPOINTS
+        );
+
+        compact
+            .materialize(COMPACT_ESCROW_HISTORY_CHECKPOINTS)
+            .unwrap();
+        let lockup = compact.lockup();
+        assert!(compact.record_lockup(&lockup, &locker_history).is_some());
+        let withdrawn = Lockup { power: 0, ..lockup };
+        assert!(compact.record_lockup(&withdrawn, &locker_history).is_none());
+    }
+
+    #[test]
+    fn test_compact_escrow_history_migration() {
+        let locker_history = LockerHistory {
+            era: 1,
+            max_stake_duration: MAX_STAKE_DURATION,
+            ..Default::default()
+        };
+        let lockup = make_lockup(1_000_000, 10, MAX_STAKE_DURATION / 2);
+        let mut escrow_history = EscrowHistory {
+            era: 1,
+            ..Default::default()
+        };
+        escrow_history.materialize(10).unwrap();
+        escrow_history
+            .record_lockup(&lockup, &locker_history)
+            .unwrap();
+        escrow_history.materialize(50).unwrap();
+
+        let mut compact = CompactEscrowHistory {
+            era: 1,
+            ..Default::default()
+        };
+        assert!(compact.compact(&escrow_history).unwrap());
+        assert_eq!(compact.num_checkpoints, 1);
+        assert_eq!(compact.checkpoint_periods[0], 10);
+        for period in 0..=u8::MAX {
+            assert_eq!(
+                compact.get_balance(period),
+                escrow_history.calculate_ve_balance(period)
+            );
+        }
+
+        // balances of an earlier lockup cannot be recovered from a single checkpoint
+        let extended = Lockup {
+            ends_at: lockup.ends_at + MAX_STAKE_DURATION / 4,
+            ..lockup
+        };
+        escrow_history
+            .record_lockup(&extended, &locker_history)
+            .unwrap();
+        let mut compact = CompactEscrowHistory {
+            era: 1,
+            ..Default::default()
+        };
+        assert!(!compact.compact(&escrow_history).unwrap());
+    }
+}
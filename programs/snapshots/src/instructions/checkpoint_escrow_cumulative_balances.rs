@@ -0,0 +1,93 @@
+//! Processor for [snapshots::checkpoint_escrow_cumulative_balances].
+
+use crate::*;
+use locked_voter::Escrow;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::checkpoint_escrow_cumulative_balances].
+#[derive(Accounts)]
+pub struct CheckpointEscrowCumulativeBalances<'info> {
+    /// The [Escrow] of the escrow history.
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [EscrowHistory] or [CompactEscrowHistory] being summed.
+    /// CHECK: Checked to be an [EscrowHistory] or [CompactEscrowHistory] when loaded.
+    pub escrow_history: UncheckedAccount<'info>,
+
+    /// The [LockerHistory] of the era, whose final periods bound the sums.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [CumulativeBalances] of the escrow history.
+    #[account(mut)]
+    pub cumulative_balances: AccountLoader<'info, CumulativeBalances>,
+}
+
+impl<'info> CheckpointEscrowCumulativeBalances<'info> {
+    fn checkpoint_escrow_cumulative_balances(&self) -> Result<()> {
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let escrow_history = escrow_history_loader.load()?;
+        let cumulative_balances = &mut self.cumulative_balances.load_mut()?;
+        unwrap_int!(extend_escrow_cumulative_balances(
+            cumulative_balances,
+            &*escrow_history,
+            &*self.locker_history.load()?
+        ));
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CheckpointEscrowCumulativeBalances>) -> Result<()> {
+    ctx.accounts.checkpoint_escrow_cumulative_balances()
+}
+
+/// Records the sums of the escrow balances up to the last period that is either
+/// materialized or final in the [LockerHistory].
+///
+/// Balances of final periods that are not materialized are computed from the recorded lockup:
+/// a period only becomes final once every shard has been merged into it, which requires the
+/// escrow to have been resynced after any change to the locker parameters.
+pub(crate) fn extend_escrow_cumulative_balances(
+    cumulative_balances: &mut CumulativeBalances,
+    escrow_history: &dyn EscrowBalances,
+    locker_history: &LockerHistory,
+) -> Option<()> {
+    let num_periods = locker_history
+        .calculate_final_periods()
+        .max(usize::from(escrow_history.materialized_periods()));
+    cumulative_balances.extend(num_periods, |period| {
+        escrow_history.calculate_ve_balance(period.to_u8()?)
+    })
+}
+
+/// Checks that the escrow history is of the [Escrow], and of the same era as the [LockerHistory].
+pub(crate) fn validate_escrow_cumulative_balances_histories<'info>(
+    escrow: &Account<'info, Escrow>,
+    escrow_history: &AccountInfo<'info>,
+    locker_history: &AccountLoader<'info, LockerHistory>,
+) -> Result<()> {
+    let escrow_history_loader = AnyEscrowHistory::try_from(escrow_history)?;
+    let escrow_history = escrow_history_loader.load()?;
+    let locker_history = locker_history.load()?;
+    assert_keys_eq!(escrow_history.escrow(), *escrow);
+    assert_keys_eq!(locker_history.locker, escrow.locker);
+    invariant!(locker_history.era == escrow_history.era(), EraMismatch);
+    invariant!(
+        locker_history.geometry() == escrow_history.geometry(),
+        GeometryMismatch
+    );
+    Ok(())
+}
+
+impl<'info> Validate<'info> for CheckpointEscrowCumulativeBalances<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(
+            self.cumulative_balances.load()?.history,
+            self.escrow_history
+        );
+        validate_escrow_cumulative_balances_histories(
+            &self.escrow,
+            &self.escrow_history,
+            &self.locker_history,
+        )
+    }
+}
@@ -0,0 +1,40 @@
+//! Processor for [snapshots::checkpoint_locker_cumulative_balances].
+
+use crate::*;
+
+/// Accounts for [snapshots::checkpoint_locker_cumulative_balances].
+#[derive(Accounts)]
+pub struct CheckpointLockerCumulativeBalances<'info> {
+    /// The [LockerHistory] being summed.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [CumulativeBalances] of the [LockerHistory].
+    #[account(mut)]
+    pub cumulative_balances: AccountLoader<'info, CumulativeBalances>,
+}
+
+impl<'info> CheckpointLockerCumulativeBalances<'info> {
+    fn checkpoint_locker_cumulative_balances(&self) -> Result<()> {
+        let locker_history = self.locker_history.load()?;
+        let cumulative_balances = &mut self.cumulative_balances.load_mut()?;
+        unwrap_int!(cumulative_balances
+            .extend(locker_history.calculate_final_periods(), |period| {
+                locker_history.ve_balances.get(period).copied()
+            }));
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CheckpointLockerCumulativeBalances>) -> Result<()> {
+    ctx.accounts.checkpoint_locker_cumulative_balances()
+}
+
+impl<'info> Validate<'info> for CheckpointLockerCumulativeBalances<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(
+            self.cumulative_balances.load()?.history,
+            self.locker_history
+        );
+        Ok(())
+    }
+}
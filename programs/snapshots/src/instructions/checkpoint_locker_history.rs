@@ -0,0 +1,36 @@
+//! Processor for [snapshots::checkpoint_locker_history].
+
+use crate::*;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::checkpoint_locker_history].
+#[derive(Accounts)]
+pub struct CheckpointLockerHistory<'info> {
+    /// The [LockerHistory] to checkpoint.
+    #[account(mut)]
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+}
+
+impl<'info> CheckpointLockerHistory<'info> {
+    fn checkpoint_locker_history(&self) -> Result<()> {
+        let locker_history = &mut self.locker_history.load_mut()?;
+        let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+        let started_periods = unwrap_int!(calculate_started_periods(
+            &locker_history.geometry(),
+            locker_history.era,
+            now
+        ));
+        unwrap_int!(locker_history.materialize(started_periods));
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CheckpointLockerHistory>) -> Result<()> {
+    ctx.accounts.checkpoint_locker_history()
+}
+
+impl<'info> Validate<'info> for CheckpointLockerHistory<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,154 @@
+//! Processor for [snapshots::claim].
+
+use crate::*;
+use ::u128::mul_div_u64;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use locked_voter::Escrow;
+
+/// Accounts for [snapshots::claim].
+#[derive(Accounts)]
+pub struct Claim<'info> {
+    /// The [Distributor].
+    pub distributor: Account<'info, Distributor>,
+
+    /// The [DistributorPeriod] to claim from.
+    #[account(mut, has_one = distributor)]
+    pub distributor_period: Account<'info, DistributorPeriod>,
+
+    /// The [LockerHistory] of the period.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [Escrow] receiving the rewards.
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [EscrowHistory] or [CompactEscrowHistory] of the period.
+    /// CHECK: Checked to be an [EscrowHistory] or [CompactEscrowHistory] when loaded.
+    pub escrow_history: UncheckedAccount<'info>,
+
+    /// The [ClaimReceipt] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"ClaimReceipt".as_ref(),
+            distributor_period.key().as_ref(),
+            escrow.key().as_ref()
+        ],
+        bump,
+        space = 8 + ClaimReceipt::LEN,
+        payer = payer
+    )]
+    pub claim_receipt: Account<'info, ClaimReceipt>,
+
+    /// [Distributor::vault].
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Token account of the [Escrow::owner] to send the rewards to.
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+
+    /// Payer of the [ClaimReceipt].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Claim<'info> {
+    fn claim(&mut self, bump: u8) -> Result<()> {
+        let locker_history = self.locker_history.load()?;
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let escrow_history = escrow_history_loader.load()?;
+        let period = self.distributor_period.period;
+
+        // Only finalized totals can be used, otherwise claims would not add up to the rewards.
+        invariant!(locker_history.is_period_final(period), PeriodNotFinal);
+        let locker_ve_balance = locker_history.ve_balances[usize::from(period)];
+        let escrow_ve_balance = unwrap_int!(escrow_history.calculate_ve_balance(period));
+        let amount = if escrow_ve_balance == 0 {
+            0
+        } else {
+            unwrap_int!(mul_div_u64(
+                self.distributor_period.rewards,
+                escrow_ve_balance,
+                locker_ve_balance
+            ))
+        };
+
+        let seeds: &[&[&[u8]]] = &[&[
+            b"Distributor".as_ref(),
+            self.distributor.locker.as_ref(),
+            self.distributor.mint.as_ref(),
+            self.distributor.authority.as_ref(),
+            &[self.distributor.bump],
+        ]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault.to_account_info(),
+                    to: self.destination.to_account_info(),
+                    authority: self.distributor.to_account_info(),
+                },
+                seeds,
+            ),
+            amount,
+        )?;
+
+        let distributor_period = &mut self.distributor_period;
+        distributor_period.claimed_rewards =
+            unwrap_int!(distributor_period.claimed_rewards.checked_add(amount));
+        invariant!(distributor_period.claimed_rewards <= distributor_period.rewards);
+
+        let claim_receipt = &mut self.claim_receipt;
+        claim_receipt.distributor_period = distributor_period.key();
+        claim_receipt.escrow = self.escrow.key();
+        claim_receipt.bump = bump;
+        claim_receipt.amount = amount;
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<Claim>) -> Result<()> {
+    ctx.accounts
+        .claim(*unwrap_int!(ctx.bumps.get("claim_receipt")))
+}
+
+impl<'info> Validate<'info> for Claim<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.vault, self.distributor.vault);
+        assert_keys_eq!(self.escrow.locker, self.distributor.locker);
+        assert_keys_eq!(self.destination.owner, self.escrow.owner);
+        assert_keys_eq!(self.destination.mint, self.distributor.mint);
+
+        let era = self.distributor_period.era;
+        let locker_history = self.locker_history.load()?;
+        assert_keys_eq!(locker_history.locker, self.distributor.locker);
+        invariant!(locker_history.era == era, EraMismatch);
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let escrow_history = escrow_history_loader.load()?;
+        assert_keys_eq!(escrow_history.escrow(), self.escrow);
+        invariant!(escrow_history.era() == era, EraMismatch);
+        let geometry = self.distributor.geometry();
+        invariant!(locker_history.geometry() == geometry, GeometryMismatch);
+        invariant!(escrow_history.geometry() == geometry, GeometryMismatch);
+
+        let now = Clock::get()?.unix_timestamp;
+        invariant!(
+            unwrap_int!(geometry
+                .has_period_elapsed(PeriodId::from((era, self.distributor_period.period)), now)),
+            PeriodNotElapsed
+        );
+        invariant!(
+            now < unwrap_int!(self
+                .distributor
+                .calculate_claim_deadline(&self.distributor_period)),
+            DistributorClaimDeadlinePassed
+        );
+        Ok(())
+    }
+}
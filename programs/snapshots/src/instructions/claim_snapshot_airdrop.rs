@@ -0,0 +1,146 @@
+//! Processor for [snapshots::claim_snapshot_airdrop].
+
+use crate::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use snapshots_airdrop::{compute_leaf, verify_proof};
+
+/// Accounts for [snapshots::claim_snapshot_airdrop].
+#[derive(Accounts)]
+pub struct ClaimSnapshotAirdrop<'info> {
+    /// The [SnapshotAirdrop].
+    #[account(mut)]
+    pub airdrop: Account<'info, SnapshotAirdrop>,
+
+    /// The [SnapshotAirdropBitmap] tracking the claim.
+    #[account(mut)]
+    pub bitmap: AccountLoader<'info, SnapshotAirdropBitmap>,
+
+    /// [SnapshotAirdrop::vault].
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Account the claim was made out to.
+    pub claimant: Signer<'info>,
+
+    /// Token account of the [Self::claimant] to send the tokens to.
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimSnapshotAirdrop<'info> {
+    fn claim_snapshot_airdrop(
+        &mut self,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        // Bitmaps past the last claim cannot be created, but the last one may track more claims.
+        invariant!(index < self.airdrop.max_num_nodes, AirdropIndexOutOfRange);
+        let bitmap = &mut self.bitmap.load_mut()?;
+        invariant!(
+            bitmap.page == unwrap_int!(SnapshotAirdropBitmap::calculate_page(index)),
+            "claim is tracked by another bitmap"
+        );
+        invariant!(
+            !unwrap_int!(bitmap.is_claimed(index)),
+            AirdropAlreadyClaimed
+        );
+
+        let leaf = compute_leaf(index, &self.claimant.key(), amount);
+        invariant!(
+            verify_proof(&proof, &self.airdrop.root, &leaf),
+            AirdropInvalidProof
+        );
+        unwrap_int!(bitmap.set_claimed(index));
+
+        let airdrop = &self.airdrop;
+        let seeds: &[&[&[u8]]] = &[&[
+            b"SnapshotAirdrop".as_ref(),
+            airdrop.locker.as_ref(),
+            &airdrop.era.to_le_bytes(),
+            &airdrop.period.to_le_bytes(),
+            airdrop.mint.as_ref(),
+            airdrop.authority.as_ref(),
+            &[airdrop.bump],
+        ]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault.to_account_info(),
+                    to: self.destination.to_account_info(),
+                    authority: self.airdrop.to_account_info(),
+                },
+                seeds,
+            ),
+            amount,
+        )?;
+
+        let airdrop = &mut self.airdrop;
+        airdrop.total_amount_claimed =
+            unwrap_int!(airdrop.total_amount_claimed.checked_add(amount));
+        invariant!(
+            airdrop.total_amount_claimed <= airdrop.max_total_claim,
+            AirdropExceededMaxClaim
+        );
+        airdrop.num_nodes_claimed = unwrap_int!(airdrop.num_nodes_claimed.checked_add(1));
+        invariant!(
+            airdrop.num_nodes_claimed <= airdrop.max_num_nodes,
+            AirdropExceededMaxClaim
+        );
+
+        emit!(SnapshotAirdropClaimed {
+            airdrop: airdrop.key(),
+            claimant: self.claimant.key(),
+            index,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(
+    ctx: Context<ClaimSnapshotAirdrop>,
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
+    ctx.accounts.claim_snapshot_airdrop(index, amount, proof)
+}
+
+impl<'info> Validate<'info> for ClaimSnapshotAirdrop<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.vault, self.airdrop.vault);
+        assert_keys_eq!(self.bitmap.load()?.airdrop, self.airdrop);
+        assert_keys_eq!(self.destination.owner, self.claimant);
+        assert_keys_eq!(self.destination.mint, self.airdrop.mint);
+
+        let now = Clock::get()?.unix_timestamp;
+        invariant!(
+            now < self.airdrop.claim_deadline,
+            AirdropClaimDeadlinePassed
+        );
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::claim_snapshot_airdrop].
+pub struct SnapshotAirdropClaimed {
+    /// The [SnapshotAirdrop].
+    #[index]
+    pub airdrop: Pubkey,
+    /// Account the claim was made out to.
+    #[index]
+    pub claimant: Pubkey,
+    /// Index of the claim.
+    pub index: u64,
+    /// Amount of tokens claimed.
+    pub amount: u64,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
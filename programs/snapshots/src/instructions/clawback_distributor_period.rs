@@ -0,0 +1,113 @@
+//! Processor for [snapshots::clawback_distributor_period].
+
+use crate::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+/// Accounts for [snapshots::clawback_distributor_period].
+#[derive(Accounts)]
+pub struct ClawbackDistributorPeriod<'info> {
+    /// The [Distributor].
+    #[account(has_one = authority)]
+    pub distributor: Account<'info, Distributor>,
+
+    /// The [DistributorPeriod] whose unclaimed rewards are taken back.
+    #[account(mut, has_one = distributor)]
+    pub distributor_period: Account<'info, DistributorPeriod>,
+
+    /// [Distributor::vault].
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// [Distributor::authority].
+    pub authority: Signer<'info>,
+
+    /// Token account to send the unclaimed rewards to.
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClawbackDistributorPeriod<'info> {
+    fn clawback_distributor_period(&mut self) -> Result<()> {
+        // The vault holds the rewards of every period, so only take back those of this one.
+        let distributor_period = &self.distributor_period;
+        let amount = unwrap_int!(distributor_period
+            .rewards
+            .checked_sub(distributor_period.claimed_rewards)
+            .and_then(|unclaimed| unclaimed.checked_sub(distributor_period.clawed_back_rewards)));
+
+        let distributor = &self.distributor;
+        let seeds: &[&[&[u8]]] = &[&[
+            b"Distributor".as_ref(),
+            distributor.locker.as_ref(),
+            distributor.mint.as_ref(),
+            distributor.authority.as_ref(),
+            &[distributor.bump],
+        ]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault.to_account_info(),
+                    to: self.destination.to_account_info(),
+                    authority: self.distributor.to_account_info(),
+                },
+                seeds,
+            ),
+            amount,
+        )?;
+
+        let distributor_period = &mut self.distributor_period;
+        distributor_period.clawed_back_rewards =
+            unwrap_int!(distributor_period.clawed_back_rewards.checked_add(amount));
+
+        emit!(DistributorPeriodClawedBack {
+            distributor: self.distributor.key(),
+            distributor_period: distributor_period.key(),
+            destination: self.destination.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<ClawbackDistributorPeriod>) -> Result<()> {
+    ctx.accounts.clawback_distributor_period()
+}
+
+impl<'info> Validate<'info> for ClawbackDistributorPeriod<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.vault, self.distributor.vault);
+        assert_keys_eq!(self.destination.mint, self.distributor.mint);
+
+        // Unclaimed rewards can only be taken back once claims have closed.
+        let now = Clock::get()?.unix_timestamp;
+        invariant!(
+            now >= unwrap_int!(self
+                .distributor
+                .calculate_claim_deadline(&self.distributor_period)),
+            DistributorClaimDeadlineNotPassed
+        );
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::clawback_distributor_period].
+pub struct DistributorPeriodClawedBack {
+    /// The [Distributor].
+    #[index]
+    pub distributor: Pubkey,
+    /// The [DistributorPeriod].
+    #[index]
+    pub distributor_period: Pubkey,
+    /// Token account the unclaimed rewards were sent to.
+    pub destination: Pubkey,
+    /// Amount of tokens clawed back.
+    pub amount: u64,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
@@ -0,0 +1,95 @@
+//! Processor for [snapshots::clawback_snapshot_airdrop].
+
+use crate::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+/// Accounts for [snapshots::clawback_snapshot_airdrop].
+#[derive(Accounts)]
+pub struct ClawbackSnapshotAirdrop<'info> {
+    /// The [SnapshotAirdrop].
+    #[account(has_one = authority)]
+    pub airdrop: Account<'info, SnapshotAirdrop>,
+
+    /// [SnapshotAirdrop::vault].
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// [SnapshotAirdrop::authority].
+    pub authority: Signer<'info>,
+
+    /// Token account to send the unclaimed tokens to.
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClawbackSnapshotAirdrop<'info> {
+    fn clawback_snapshot_airdrop(&mut self) -> Result<()> {
+        let airdrop = &self.airdrop;
+        let amount = self.vault.amount;
+        let seeds: &[&[&[u8]]] = &[&[
+            b"SnapshotAirdrop".as_ref(),
+            airdrop.locker.as_ref(),
+            &airdrop.era.to_le_bytes(),
+            &airdrop.period.to_le_bytes(),
+            airdrop.mint.as_ref(),
+            airdrop.authority.as_ref(),
+            &[airdrop.bump],
+        ]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.vault.to_account_info(),
+                    to: self.destination.to_account_info(),
+                    authority: self.airdrop.to_account_info(),
+                },
+                seeds,
+            ),
+            amount,
+        )?;
+
+        emit!(SnapshotAirdropClawedBack {
+            airdrop: airdrop.key(),
+            destination: self.destination.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<ClawbackSnapshotAirdrop>) -> Result<()> {
+    ctx.accounts.clawback_snapshot_airdrop()
+}
+
+impl<'info> Validate<'info> for ClawbackSnapshotAirdrop<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.vault, self.airdrop.vault);
+        assert_keys_eq!(self.destination.mint, self.airdrop.mint);
+
+        // Unclaimed tokens can only be taken back once claims have closed.
+        let now = Clock::get()?.unix_timestamp;
+        invariant!(
+            now >= self.airdrop.claim_deadline,
+            AirdropClaimDeadlineNotPassed
+        );
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::clawback_snapshot_airdrop].
+pub struct SnapshotAirdropClawedBack {
+    /// The [SnapshotAirdrop].
+    #[index]
+    pub airdrop: Pubkey,
+    /// Token account the unclaimed tokens were sent to.
+    pub destination: Pubkey,
+    /// Amount of tokens clawed back.
+    pub amount: u64,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
@@ -0,0 +1,38 @@
+//! Processor for [snapshots::close_compact_escrow_history].
+
+use crate::*;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::close_compact_escrow_history].
+#[derive(Accounts)]
+pub struct CloseCompactEscrowHistory<'info> {
+    /// The [CompactEscrowHistory] to close.
+    #[account(mut, close = payer)]
+    pub compact_escrow_history: AccountLoader<'info, CompactEscrowHistory>,
+
+    /// The account that paid for the [CompactEscrowHistory].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub fn handler(_ctx: Context<CloseCompactEscrowHistory>) -> Result<()> {
+    Ok(())
+}
+
+impl<'info> Validate<'info> for CloseCompactEscrowHistory<'info> {
+    fn validate(&self) -> Result<()> {
+        let compact_escrow_history = self.compact_escrow_history.load()?;
+        assert_keys_eq!(compact_escrow_history.payer, self.payer);
+        let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+        invariant!(
+            unwrap_int!(has_retention_expired(
+                &compact_escrow_history.geometry(),
+                compact_escrow_history.era,
+                compact_escrow_history.retention_seconds,
+                now
+            )),
+            HistoryRetained
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+//! Processor for [snapshots::close_escrow_history].
+
+use crate::*;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::close_escrow_history].
+#[derive(Accounts)]
+pub struct CloseEscrowHistory<'info> {
+    /// The [EscrowHistory] to close.
+    #[account(mut, close = payer)]
+    pub escrow_history: AccountLoader<'info, EscrowHistory>,
+
+    /// The account that paid for the [EscrowHistory].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub fn handler(_ctx: Context<CloseEscrowHistory>) -> Result<()> {
+    Ok(())
+}
+
+impl<'info> Validate<'info> for CloseEscrowHistory<'info> {
+    fn validate(&self) -> Result<()> {
+        let escrow_history = self.escrow_history.load()?;
+        assert_keys_eq!(escrow_history.payer, self.payer);
+        let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+        invariant!(
+            unwrap_int!(has_retention_expired(
+                &escrow_history.geometry(),
+                escrow_history.era,
+                escrow_history.retention_seconds,
+                now
+            )),
+            HistoryRetained
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+//! Processor for [snapshots::close_locker_history].
+
+use crate::*;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::close_locker_history].
+#[derive(Accounts)]
+pub struct CloseLockerHistory<'info> {
+    /// The [LockerHistory] to close.
+    #[account(mut, close = payer)]
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The account that paid for the [LockerHistory].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub fn handler(_ctx: Context<CloseLockerHistory>) -> Result<()> {
+    Ok(())
+}
+
+impl<'info> Validate<'info> for CloseLockerHistory<'info> {
+    fn validate(&self) -> Result<()> {
+        let locker_history = self.locker_history.load()?;
+        assert_keys_eq!(locker_history.payer, self.payer);
+        let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+        invariant!(
+            unwrap_int!(has_retention_expired(
+                &locker_history.geometry(),
+                locker_history.era,
+                locker_history.retention_seconds,
+                now
+            )),
+            HistoryRetained
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+//! Processor for [snapshots::close_locker_history_shard].
+
+use crate::*;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::close_locker_history_shard].
+#[derive(Accounts)]
+pub struct CloseLockerHistoryShard<'info> {
+    /// The [LockerHistory] of the shard, which may have been closed.
+    /// CHECK: Only checked to be the history of the shard, and whether it is empty.
+    pub locker_history: UncheckedAccount<'info>,
+
+    /// The [LockerHistoryShard] to close.
+    #[account(mut, close = payer)]
+    pub shard: AccountLoader<'info, LockerHistoryShard>,
+
+    /// The account that paid for the [LockerHistoryShard].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+}
+
+pub fn handler(_ctx: Context<CloseLockerHistoryShard>) -> Result<()> {
+    Ok(())
+}
+
+impl<'info> Validate<'info> for CloseLockerHistoryShard<'info> {
+    fn validate(&self) -> Result<()> {
+        let shard = self.shard.load()?;
+        assert_keys_eq!(shard.locker_history, self.locker_history);
+        assert_keys_eq!(shard.history.payer, self.payer);
+        let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+        invariant!(
+            unwrap_int!(has_retention_expired(
+                &shard.history.geometry(),
+                shard.history.era,
+                shard.history.retention_seconds,
+                now
+            )),
+            HistoryRetained
+        );
+        // Closing an unmerged shard would leave the remaining periods of the history unfinished.
+        invariant!(
+            usize::from(shard.merged_periods) >= ERA_NUM_PERIODS
+                || self.locker_history.data_is_empty(),
+            ShardNotMerged
+        );
+        Ok(())
+    }
+}
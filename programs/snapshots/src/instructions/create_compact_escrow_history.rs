@@ -0,0 +1,125 @@
+//! Processor for [snapshots::create_compact_escrow_history].
+
+use crate::*;
+use locked_voter::Escrow;
+
+/// Accounts for [snapshots::create_compact_escrow_history].
+#[derive(Accounts)]
+#[instruction(era: u16, retention_seconds: u64)]
+pub struct CreateCompactEscrowHistory<'info> {
+    /// The [Escrow].
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [SnapshotConfig] of the [Escrow]'s [locked_voter::Locker], which may not exist.
+    /// CHECK: Only read if it has been created. See [load_era_geometry].
+    #[account(
+        seeds = [
+            b"SnapshotConfig".as_ref(),
+            escrow.locker.as_ref()
+        ],
+        bump
+    )]
+    pub snapshot_config: UncheckedAccount<'info>,
+
+    /// The [EscrowHistory] of the era, which must not exist.
+    /// CHECK: Only checked to be empty.
+    #[account(
+        seeds = [
+            b"EscrowHistory".as_ref(),
+            escrow.key().as_ref(),
+            era.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub escrow_history: UncheckedAccount<'info>,
+
+    /// The [CompactEscrowHistory] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"CompactEscrowHistory".as_ref(),
+            escrow.key().as_ref(),
+            era.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + CompactEscrowHistory::LEN,
+        payer = payer
+    )]
+    pub compact_escrow_history: AccountLoader<'info, CompactEscrowHistory>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateCompactEscrowHistory<'info> {
+    fn create_compact_escrow_history(
+        &mut self,
+        bump: u8,
+        era: u16,
+        retention_seconds: u64,
+    ) -> Result<()> {
+        let geometry = load_era_geometry(&self.snapshot_config)?;
+        assert_retention_not_expired(&geometry, era, retention_seconds)?;
+        let history = &mut *self.compact_escrow_history.load_init()?;
+        history.escrow = self.escrow.key();
+        history.era = era;
+        history.bump = bump;
+        history.retention_seconds = retention_seconds;
+        history.payer = self.payer.key();
+        history.period_seconds = geometry.period_seconds;
+        history.origin_ts = geometry.origin_ts;
+        emit!(CompactEscrowHistoryCreated {
+            escrow: self.escrow.key(),
+            compact_escrow_history: self.compact_escrow_history.key(),
+            era,
+            payer: self.payer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+/// Ensures that an escrow does not have another history for the era, which would be counted twice.
+pub(crate) fn assert_history_absent(info: &AccountInfo) -> Result<()> {
+    invariant!(info.data_is_empty(), EscrowHistoryExists);
+    Ok(())
+}
+
+pub fn handler(
+    ctx: Context<CreateCompactEscrowHistory>,
+    era: u16,
+    retention_seconds: u64,
+) -> Result<()> {
+    ctx.accounts.create_compact_escrow_history(
+        *unwrap_int!(ctx.bumps.get("compact_escrow_history")),
+        era,
+        retention_seconds,
+    )
+}
+
+impl<'info> Validate<'info> for CreateCompactEscrowHistory<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_history_absent(&self.escrow_history)?;
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::create_compact_escrow_history] and [snapshots::migrate_escrow_history].
+pub struct CompactEscrowHistoryCreated {
+    /// The [Escrow].
+    #[index]
+    pub escrow: Pubkey,
+    /// The [CompactEscrowHistory] that was created.
+    pub compact_escrow_history: Pubkey,
+    /// The era of the history.
+    pub era: u16,
+    /// Payer of the history.
+    pub payer: Pubkey,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
@@ -0,0 +1,98 @@
+//! Processor for [snapshots::create_distributor].
+
+use crate::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use locked_voter::Locker;
+
+/// Accounts for [snapshots::create_distributor].
+#[derive(Accounts)]
+pub struct CreateDistributor<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [SnapshotConfig] of the [Locker], which may not exist.
+    /// CHECK: Only read if it has been created. See [load_era_geometry].
+    #[account(
+        seeds = [
+            b"SnapshotConfig".as_ref(),
+            locker.key().as_ref()
+        ],
+        bump
+    )]
+    pub snapshot_config: UncheckedAccount<'info>,
+
+    /// Mint of the token to distribute.
+    pub mint: Account<'info, Mint>,
+
+    /// [Distributor::authority].
+    pub authority: Signer<'info>,
+
+    /// The [Distributor] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"Distributor".as_ref(),
+            locker.key().as_ref(),
+            mint.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump,
+        space = 8 + Distributor::LEN,
+        payer = payer
+    )]
+    pub distributor: Account<'info, Distributor>,
+
+    /// Token account holding the rewards of the [Distributor].
+    #[account(
+        init,
+        seeds = [
+            b"DistributorVault".as_ref(),
+            distributor.key().as_ref()
+        ],
+        bump,
+        token::mint = mint,
+        token::authority = distributor,
+        payer = payer
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+
+    /// [Rent] sysvar.
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> CreateDistributor<'info> {
+    fn create_distributor(&mut self, bump: u8, claim_seconds: u64) -> Result<()> {
+        let distributor = &mut self.distributor;
+        distributor.locker = self.locker.key();
+        distributor.mint = self.mint.key();
+        distributor.bump = bump;
+        distributor.vault = self.vault.key();
+        let geometry = load_era_geometry(&self.snapshot_config)?;
+        distributor.period_seconds = geometry.period_seconds;
+        distributor.origin_ts = geometry.origin_ts;
+        distributor.authority = self.authority.key();
+        distributor.claim_seconds = claim_seconds;
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateDistributor>, claim_seconds: u64) -> Result<()> {
+    ctx.accounts
+        .create_distributor(*unwrap_int!(ctx.bumps.get("distributor")), claim_seconds)
+}
+
+impl<'info> Validate<'info> for CreateDistributor<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
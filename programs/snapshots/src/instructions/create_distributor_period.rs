@@ -0,0 +1,58 @@
+//! Processor for [snapshots::create_distributor_period].
+
+use crate::*;
+
+/// Accounts for [snapshots::create_distributor_period].
+#[derive(Accounts)]
+#[instruction(era: u16, period: u8)]
+pub struct CreateDistributorPeriod<'info> {
+    /// The [Distributor].
+    pub distributor: Account<'info, Distributor>,
+
+    /// The [DistributorPeriod] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"DistributorPeriod".as_ref(),
+            distributor.key().as_ref(),
+            era.to_le_bytes().as_ref(),
+            period.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + DistributorPeriod::LEN,
+        payer = payer
+    )]
+    pub distributor_period: Account<'info, DistributorPeriod>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateDistributorPeriod<'info> {
+    fn create_distributor_period(&mut self, bump: u8, era: u16, period: u8) -> Result<()> {
+        let distributor_period = &mut self.distributor_period;
+        distributor_period.distributor = self.distributor.key();
+        distributor_period.era = era;
+        distributor_period.period = period;
+        distributor_period.bump = bump;
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateDistributorPeriod>, era: u16, period: u8) -> Result<()> {
+    ctx.accounts.create_distributor_period(
+        *unwrap_int!(ctx.bumps.get("distributor_period")),
+        era,
+        period,
+    )
+}
+
+impl<'info> Validate<'info> for CreateDistributorPeriod<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+//! Processor for [snapshots::create_escrow_cumulative_balances].
+
+use crate::*;
+use locked_voter::Escrow;
+
+/// Accounts for [snapshots::create_escrow_cumulative_balances].
+#[derive(Accounts)]
+pub struct CreateEscrowCumulativeBalances<'info> {
+    /// The [Escrow] of the escrow history.
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [EscrowHistory] or [CompactEscrowHistory] to sum.
+    /// CHECK: Checked to be an [EscrowHistory] or [CompactEscrowHistory] when loaded.
+    pub escrow_history: UncheckedAccount<'info>,
+
+    /// The [LockerHistory] of the era, whose final periods bound the sums.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [CumulativeBalances] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"CumulativeBalances".as_ref(),
+            escrow_history.key().as_ref()
+        ],
+        bump,
+        space = 8 + CumulativeBalances::LEN,
+        payer = payer
+    )]
+    pub cumulative_balances: AccountLoader<'info, CumulativeBalances>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateEscrowCumulativeBalances<'info> {
+    fn create_escrow_cumulative_balances(&mut self, bump: u8) -> Result<()> {
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let escrow_history = escrow_history_loader.load()?;
+        let cumulative_balances = &mut self.cumulative_balances.load_init()?;
+        cumulative_balances.history = self.escrow_history.key();
+        cumulative_balances.era = escrow_history.era();
+        cumulative_balances.bump = bump;
+        unwrap_int!(extend_escrow_cumulative_balances(
+            cumulative_balances,
+            &*escrow_history,
+            &*self.locker_history.load()?
+        ));
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateEscrowCumulativeBalances>) -> Result<()> {
+    ctx.accounts
+        .create_escrow_cumulative_balances(*unwrap_int!(ctx.bumps.get("cumulative_balances")))
+}
+
+impl<'info> Validate<'info> for CreateEscrowCumulativeBalances<'info> {
+    fn validate(&self) -> Result<()> {
+        validate_escrow_cumulative_balances_histories(
+            &self.escrow,
+            &self.escrow_history,
+            &self.locker_history,
+        )
+    }
+}
@@ -0,0 +1,235 @@
+//! Processor for [snapshots::create_escrow_histories].
+
+use crate::*;
+use anchor_lang::system_program;
+use locked_voter::{Escrow, Locker};
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::create_escrow_histories].
+///
+/// The remaining accounts are, for each escrow of the [Locker], the [Escrow] followed by an
+/// `(EscrowHistory, CompactEscrowHistory)` pair of addresses for each era to create,
+/// starting from `first_era`. The [EscrowHistory] accounts must be writable.
+///
+/// Histories that already exist, and eras in which the escrow has a [CompactEscrowHistory],
+/// are skipped.
+#[derive(Accounts)]
+pub struct CreateEscrowHistories<'info> {
+    /// The [Locker] of the escrows.
+    pub locker: Account<'info, Locker>,
+
+    /// The [SnapshotConfig] of the [Locker], which may not exist.
+    /// CHECK: Only read if it has been created. See [load_era_geometry].
+    #[account(
+        seeds = [
+            b"SnapshotConfig".as_ref(),
+            locker.key().as_ref()
+        ],
+        bump
+    )]
+    pub snapshot_config: UncheckedAccount<'info>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateEscrowHistories<'info> {
+    fn create_escrow_histories(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>],
+        first_era: u16,
+        num_eras: u8,
+        retention_seconds: u64,
+    ) -> Result<()> {
+        invariant!(num_eras > 0, "no eras to create");
+        let group_len = unwrap_int!(usize::from(num_eras)
+            .checked_mul(2)
+            .and_then(|len| len.checked_add(1)));
+        let groups = remaining_accounts.chunks_exact(group_len);
+        invariant!(
+            !remaining_accounts.is_empty() && groups.remainder().is_empty(),
+            "expected an Escrow followed by (EscrowHistory, CompactEscrowHistory) pairs"
+        );
+        let geometry = load_era_geometry(&self.snapshot_config)?;
+        for group in groups {
+            let escrow: Account<Escrow> = Account::try_from(&group[0])?;
+            assert_keys_eq!(escrow.locker, self.locker, LockerEscrowMismatch);
+            for (era_offset, pair) in group[1..].chunks_exact(2).enumerate() {
+                let era = unwrap_int!(first_era.checked_add(unwrap_int!(era_offset.to_u16())));
+                self.create_escrow_history(
+                    &escrow,
+                    &geometry,
+                    era,
+                    &pair[0],
+                    &pair[1],
+                    retention_seconds,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the [EscrowHistory] of an era at its address, unless the escrow already has a history.
+    fn create_escrow_history(
+        &self,
+        escrow: &Account<'info, Escrow>,
+        geometry: &EraGeometry,
+        era: u16,
+        escrow_history_info: &AccountInfo<'info>,
+        compact_escrow_history_info: &AccountInfo<'info>,
+        retention_seconds: u64,
+    ) -> Result<()> {
+        let escrow_key = escrow.key();
+        let era_bytes = era.to_le_bytes();
+        let (escrow_history_key, bump) = Pubkey::find_program_address(
+            &[
+                b"EscrowHistory".as_ref(),
+                escrow_key.as_ref(),
+                era_bytes.as_ref(),
+            ],
+            &crate::ID,
+        );
+        assert_keys_eq!(
+            escrow_history_key,
+            *escrow_history_info,
+            InvalidHistoryAddress
+        );
+        let (compact_escrow_history_key, _) = Pubkey::find_program_address(
+            &[
+                b"CompactEscrowHistory".as_ref(),
+                escrow_key.as_ref(),
+                era_bytes.as_ref(),
+            ],
+            &crate::ID,
+        );
+        assert_keys_eq!(
+            compact_escrow_history_key,
+            *compact_escrow_history_info,
+            InvalidHistoryAddress
+        );
+        if !escrow_history_info.data_is_empty() || !compact_escrow_history_info.data_is_empty() {
+            return Ok(());
+        }
+        assert_retention_not_expired(geometry, era, retention_seconds)?;
+
+        self.create_account(
+            escrow_history_info,
+            8 + EscrowHistory::LEN,
+            &[
+                b"EscrowHistory".as_ref(),
+                escrow_key.as_ref(),
+                era_bytes.as_ref(),
+                &[bump],
+            ],
+        )?;
+        let escrow_history: AccountLoader<EscrowHistory> =
+            AccountLoader::try_from_unchecked(&crate::ID, escrow_history_info)?;
+        init_escrow_history(
+            &mut *escrow_history.load_init()?,
+            escrow_key,
+            geometry,
+            era,
+            bump,
+            retention_seconds,
+            self.payer.key(),
+        );
+        escrow_history.exit(&crate::ID)?;
+
+        emit!(EscrowHistoryCreated {
+            escrow: escrow_key,
+            escrow_history: escrow_history_key,
+            era,
+            payer: self.payer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Creates a rent-exempt account owned by this program at a program-derived address.
+    ///
+    /// Like `init`, this also works if the address has already received lamports.
+    fn create_account(
+        &self,
+        info: &AccountInfo<'info>,
+        space: usize,
+        seeds: &[&[u8]],
+    ) -> Result<()> {
+        let rent = Rent::get()?.minimum_balance(space);
+        let space = unwrap_int!(space.to_u64());
+        let system_program = self.system_program.to_account_info();
+        let current_lamports = info.lamports();
+        if current_lamports == 0 {
+            return system_program::create_account(
+                CpiContext::new_with_signer(
+                    system_program,
+                    system_program::CreateAccount {
+                        from: self.payer.to_account_info(),
+                        to: info.clone(),
+                    },
+                    &[seeds],
+                ),
+                rent,
+                space,
+                &crate::ID,
+            );
+        }
+
+        let required_lamports = rent.saturating_sub(current_lamports);
+        if required_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.clone(),
+                    system_program::Transfer {
+                        from: self.payer.to_account_info(),
+                        to: info.clone(),
+                    },
+                ),
+                required_lamports,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::Allocate {
+                    account_to_allocate: info.clone(),
+                },
+                &[seeds],
+            ),
+            space,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program,
+                system_program::Assign {
+                    account_to_assign: info.clone(),
+                },
+                &[seeds],
+            ),
+            &crate::ID,
+        )
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateEscrowHistories<'info>>,
+    first_era: u16,
+    num_eras: u8,
+    retention_seconds: u64,
+) -> Result<()> {
+    ctx.accounts.create_escrow_histories(
+        ctx.remaining_accounts,
+        first_era,
+        num_eras,
+        retention_seconds,
+    )
+}
+
+impl<'info> Validate<'info> for CreateEscrowHistories<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -5,11 +5,34 @@ use locked_voter::Escrow;
 
 /// Accounts for [snapshots::create_escrow_history].
 #[derive(Accounts)]
-#[instruction(era: u16)]
+#[instruction(era: u16, retention_seconds: u64)]
 pub struct CreateEscrowHistory<'info> {
     /// The [Escrow].
     pub escrow: Account<'info, Escrow>,
 
+    /// The [SnapshotConfig] of the [Escrow]'s [locked_voter::Locker], which may not exist.
+    /// CHECK: Only read if it has been created. See [load_era_geometry].
+    #[account(
+        seeds = [
+            b"SnapshotConfig".as_ref(),
+            escrow.locker.as_ref()
+        ],
+        bump
+    )]
+    pub snapshot_config: UncheckedAccount<'info>,
+
+    /// The [CompactEscrowHistory] of the era, which must not exist.
+    /// CHECK: Only checked to be empty.
+    #[account(
+        seeds = [
+            b"CompactEscrowHistory".as_ref(),
+            escrow.key().as_ref(),
+            era.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub compact_escrow_history: UncheckedAccount<'info>,
+
     /// The [EscrowHistory] to be created.
     #[account(
         init,
@@ -33,23 +56,79 @@ pub struct CreateEscrowHistory<'info> {
 }
 
 impl<'info> CreateEscrowHistory<'info> {
-    fn create_escrow_history(&mut self, bump: u8, era: u16) -> Result<()> {
-        let history = &mut self.escrow_history.load_init()?;
-        history.escrow = self.escrow.key();
-        history.era = era;
-        history.bump = bump;
+    fn create_escrow_history(&mut self, bump: u8, era: u16, retention_seconds: u64) -> Result<()> {
+        let geometry = load_era_geometry(&self.snapshot_config)?;
+        assert_retention_not_expired(&geometry, era, retention_seconds)?;
+        init_escrow_history(
+            &mut *self.escrow_history.load_init()?,
+            self.escrow.key(),
+            &geometry,
+            era,
+            bump,
+            retention_seconds,
+            self.payer.key(),
+        );
+        emit!(EscrowHistoryCreated {
+            escrow: self.escrow.key(),
+            escrow_history: self.escrow_history.key(),
+            era,
+            payer: self.payer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 }
 
-pub fn handler(ctx: Context<CreateEscrowHistory>, era: u16) -> Result<()> {
-    ctx.accounts
-        .create_escrow_history(*unwrap_int!(ctx.bumps.get("escrow_history")), era)?;
+/// Initializes the fields of a newly created [EscrowHistory].
+///
+/// The geometry is recorded so the history can be closed on time even if it is never synced.
+pub(crate) fn init_escrow_history(
+    history: &mut EscrowHistory,
+    escrow: Pubkey,
+    geometry: &EraGeometry,
+    era: u16,
+    bump: u8,
+    retention_seconds: u64,
+    payer: Pubkey,
+) {
+    history.escrow = escrow;
+    history.era = era;
+    history.bump = bump;
+    history.retention_seconds = retention_seconds;
+    history.payer = payer;
+    history.period_seconds = geometry.period_seconds;
+    history.origin_ts = geometry.origin_ts;
+}
+
+pub fn handler(ctx: Context<CreateEscrowHistory>, era: u16, retention_seconds: u64) -> Result<()> {
+    ctx.accounts.create_escrow_history(
+        *unwrap_int!(ctx.bumps.get("escrow_history")),
+        era,
+        retention_seconds,
+    )?;
     Ok(())
 }
 
 impl<'info> Validate<'info> for CreateEscrowHistory<'info> {
     fn validate(&self) -> Result<()> {
+        assert_history_absent(&self.compact_escrow_history)?;
         Ok(())
     }
 }
+
+#[event]
+/// Event called in [snapshots::create_escrow_history], [snapshots::create_escrow_histories],
+/// [snapshots::sync_init_if_needed] and [snapshots::expand_compact_escrow_history].
+pub struct EscrowHistoryCreated {
+    /// The [Escrow].
+    #[index]
+    pub escrow: Pubkey,
+    /// The [EscrowHistory] that was created.
+    pub escrow_history: Pubkey,
+    /// The era of the history.
+    pub era: u16,
+    /// Payer of the history.
+    pub payer: Pubkey,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
@@ -0,0 +1,127 @@
+//! Processor for [snapshots::create_govern_proposal_snapshot].
+
+use crate::*;
+use govern::Proposal;
+use locked_voter::Locker;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::create_govern_proposal_snapshot].
+#[derive(Accounts)]
+pub struct CreateGovernProposalSnapshot<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [Proposal].
+    pub proposal: Account<'info, Proposal>,
+
+    /// The [SnapshotConfig] of the [Locker], which may not exist.
+    /// CHECK: Only read if it has been created. See [load_era_geometry].
+    #[account(
+        seeds = [
+            b"SnapshotConfig".as_ref(),
+            locker.key().as_ref()
+        ],
+        bump
+    )]
+    pub snapshot_config: UncheckedAccount<'info>,
+
+    /// The [LockerHistory] of the era containing [Proposal::created_at].
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [GovernProposalSnapshot] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"GovernProposalSnapshot".as_ref(),
+            proposal.key().as_ref()
+        ],
+        bump,
+        space = 8 + GovernProposalSnapshot::LEN,
+        payer = payer
+    )]
+    pub snapshot: Account<'info, GovernProposalSnapshot>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateGovernProposalSnapshot<'info> {
+    fn create_govern_proposal_snapshot(&mut self, bump: u8) -> Result<()> {
+        let locker_history = self.locker_history.load()?;
+        let geometry = locker_history.geometry();
+        // Histories created before the config keep the default geometry, so
+        // their periods would not line up with those of newer escrow histories.
+        invariant!(
+            geometry == load_era_geometry(&self.snapshot_config)?,
+            GeometryMismatch
+        );
+        let created_at = unwrap_int!(self.proposal.created_at.to_u64());
+        let PeriodId { era, period } = unwrap_int!(geometry.period_of_ts(created_at));
+        let (era, period): (u16, u8) = (era.into(), period.into());
+        invariant!(locker_history.era == era, EraMismatch);
+        // Only finalized totals can be used, otherwise the quorum could still change.
+        invariant!(locker_history.is_period_final(period), PeriodNotFinal);
+
+        let snapshot = &mut self.snapshot;
+        snapshot.proposal = self.proposal.key();
+        snapshot.locker = self.locker.key();
+        snapshot.era = era;
+        snapshot.period = period;
+        snapshot.bump = bump;
+        snapshot.total_voting_power = locker_history.ve_balances[usize::from(period)];
+        snapshot.num_voters = locker_history.ve_counts[usize::from(period)];
+        // The governor's quorum is an absolute number of votes rather than a fraction of the
+        // voting power, so it applies to the snapshot total as is.
+        snapshot.quorum_votes = self.proposal.quorum_votes;
+        snapshot.period_seconds = locker_history.period_seconds;
+        snapshot.origin_ts = locker_history.origin_ts;
+
+        emit!(GovernProposalSnapshotCreated {
+            proposal: snapshot.proposal,
+            snapshot: snapshot.key(),
+            era,
+            period,
+            total_voting_power: snapshot.total_voting_power,
+            quorum_votes: snapshot.quorum_votes,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateGovernProposalSnapshot>) -> Result<()> {
+    ctx.accounts
+        .create_govern_proposal_snapshot(*unwrap_int!(ctx.bumps.get("snapshot")))
+}
+
+impl<'info> Validate<'info> for CreateGovernProposalSnapshot<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.proposal.governor, self.locker.governor);
+        assert_keys_eq!(self.locker_history.load()?.locker, self.locker);
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::create_govern_proposal_snapshot].
+pub struct GovernProposalSnapshotCreated {
+    /// The [Proposal].
+    #[index]
+    pub proposal: Pubkey,
+    /// The [GovernProposalSnapshot] that was created.
+    pub snapshot: Pubkey,
+    /// The era of the snapshot.
+    pub era: u16,
+    /// The period of the snapshot within the era.
+    pub period: u8,
+    /// Total voting power at the period.
+    pub total_voting_power: u64,
+    /// Votes in support required to reach quorum.
+    pub quorum_votes: u64,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
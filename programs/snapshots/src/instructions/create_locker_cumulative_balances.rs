@@ -0,0 +1,56 @@
+//! Processor for [snapshots::create_locker_cumulative_balances].
+
+use crate::*;
+
+/// Accounts for [snapshots::create_locker_cumulative_balances].
+#[derive(Accounts)]
+pub struct CreateLockerCumulativeBalances<'info> {
+    /// The [LockerHistory] to sum.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [CumulativeBalances] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"CumulativeBalances".as_ref(),
+            locker_history.key().as_ref()
+        ],
+        bump,
+        space = 8 + CumulativeBalances::LEN,
+        payer = payer
+    )]
+    pub cumulative_balances: AccountLoader<'info, CumulativeBalances>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateLockerCumulativeBalances<'info> {
+    fn create_locker_cumulative_balances(&mut self, bump: u8) -> Result<()> {
+        let locker_history = self.locker_history.load()?;
+        let cumulative_balances = &mut self.cumulative_balances.load_init()?;
+        cumulative_balances.history = self.locker_history.key();
+        cumulative_balances.era = locker_history.era;
+        cumulative_balances.bump = bump;
+        unwrap_int!(cumulative_balances
+            .extend(locker_history.calculate_final_periods(), |period| {
+                locker_history.ve_balances.get(period).copied()
+            }));
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateLockerCumulativeBalances>) -> Result<()> {
+    ctx.accounts
+        .create_locker_cumulative_balances(*unwrap_int!(ctx.bumps.get("cumulative_balances")))
+}
+
+impl<'info> Validate<'info> for CreateLockerCumulativeBalances<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -2,14 +2,26 @@
 
 use crate::*;
 use locked_voter::Locker;
+use num_traits::ToPrimitive;
 
 /// Accounts for [snapshots::create_locker_history].
 #[derive(Accounts)]
-#[instruction(era: u16)]
+#[instruction(era: u16, retention_seconds: u64)]
 pub struct CreateLockerHistory<'info> {
     /// The [Locker].
     pub locker: Account<'info, Locker>,
 
+    /// The [SnapshotConfig] of the [Locker], which may not exist.
+    /// CHECK: Only read if it has been created. See [load_era_geometry].
+    #[account(
+        seeds = [
+            b"SnapshotConfig".as_ref(),
+            locker.key().as_ref()
+        ],
+        bump
+    )]
+    pub snapshot_config: UncheckedAccount<'info>,
+
     /// The [LockerHistory] to be created.
     #[account(
         init,
@@ -33,18 +45,70 @@ pub struct CreateLockerHistory<'info> {
 }
 
 impl<'info> CreateLockerHistory<'info> {
-    fn create_locker_history(&mut self, bump: u8, era: u16) -> Result<()> {
-        let history = &mut self.locker_history.load_init()?;
-        history.locker = self.locker.key();
-        history.era = era;
-        history.bump = bump;
+    fn create_locker_history(&mut self, bump: u8, era: u16, retention_seconds: u64) -> Result<()> {
+        let geometry = load_era_geometry(&self.snapshot_config)?;
+        assert_retention_not_expired(&geometry, era, retention_seconds)?;
+        init_locker_history(
+            &mut *self.locker_history.load_init()?,
+            &self.locker,
+            &geometry,
+            era,
+            bump,
+            retention_seconds,
+            self.payer.key(),
+        );
+        emit!(LockerHistoryCreated {
+            locker: self.locker.key(),
+            locker_history: self.locker_history.key(),
+            era,
+            payer: self.payer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 }
 
-pub fn handler(ctx: Context<CreateLockerHistory>, era: u16) -> Result<()> {
-    ctx.accounts
-        .create_locker_history(*unwrap_int!(ctx.bumps.get("locker_history")), era)
+/// Ensures that a history of the era would not be closable as soon as it is created.
+pub(crate) fn assert_retention_not_expired(
+    geometry: &EraGeometry,
+    era: u16,
+    retention_seconds: u64,
+) -> Result<()> {
+    let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+    invariant!(
+        !unwrap_int!(has_retention_expired(geometry, era, retention_seconds, now)),
+        HistoryRetentionExpired
+    );
+    Ok(())
+}
+
+/// Initializes the fields of a newly created [LockerHistory].
+pub(crate) fn init_locker_history(
+    history: &mut LockerHistory,
+    locker: &Account<Locker>,
+    geometry: &EraGeometry,
+    era: u16,
+    bump: u8,
+    retention_seconds: u64,
+    payer: Pubkey,
+) {
+    history.locker = locker.key();
+    history.era = era;
+    history.bump = bump;
+    history.retention_seconds = retention_seconds;
+    history.payer = payer;
+    history.max_stake_duration = locker.params.max_stake_duration;
+    history.max_stake_vote_multiplier = locker.params.max_stake_vote_multiplier;
+    history.period_seconds = geometry.period_seconds;
+    history.origin_ts = geometry.origin_ts;
+}
+
+pub fn handler(ctx: Context<CreateLockerHistory>, era: u16, retention_seconds: u64) -> Result<()> {
+    ctx.accounts.create_locker_history(
+        *unwrap_int!(ctx.bumps.get("locker_history")),
+        era,
+        retention_seconds,
+    )
 }
 
 impl<'info> Validate<'info> for CreateLockerHistory<'info> {
@@ -52,3 +116,19 @@ impl<'info> Validate<'info> for CreateLockerHistory<'info> {
         Ok(())
     }
 }
+
+#[event]
+/// Event called in [snapshots::create_locker_history] and [snapshots::sync_init_if_needed].
+pub struct LockerHistoryCreated {
+    /// The [Locker].
+    #[index]
+    pub locker: Pubkey,
+    /// The [LockerHistory] that was created.
+    pub locker_history: Pubkey,
+    /// The era of the history.
+    pub era: u16,
+    /// Payer of the history.
+    pub payer: Pubkey,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
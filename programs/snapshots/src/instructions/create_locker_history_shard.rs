@@ -0,0 +1,77 @@
+//! Processor for [snapshots::create_locker_history_shard].
+
+use crate::*;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::create_locker_history_shard].
+#[derive(Accounts)]
+#[instruction(index: u16)]
+pub struct CreateLockerHistoryShard<'info> {
+    /// The [LockerHistory].
+    #[account(mut)]
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [LockerHistoryShard] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"LockerHistoryShard".as_ref(),
+            locker_history.key().as_ref(),
+            index.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + LockerHistoryShard::LEN,
+        payer = payer
+    )]
+    pub shard: AccountLoader<'info, LockerHistoryShard>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateLockerHistoryShard<'info> {
+    fn create_locker_history_shard(&mut self, bump: u8, index: u16) -> Result<()> {
+        let locker_history = &mut self.locker_history.load_mut()?;
+        invariant!(
+            index == locker_history.num_shards,
+            "shards must be created in order"
+        );
+        let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+        let started_periods = unwrap_int!(calculate_started_periods(
+            &locker_history.geometry(),
+            locker_history.era,
+            now
+        ));
+        let merged_periods = unwrap_int!(locker_history.add_shard(started_periods));
+
+        let shard = &mut self.shard.load_init()?;
+        shard.locker_history = self.locker_history.key();
+        shard.index = index;
+        shard.merged_periods = merged_periods;
+        shard.bump = bump;
+        shard.history.locker = locker_history.locker;
+        shard.history.era = locker_history.era;
+        shard.history.max_stake_duration = locker_history.max_stake_duration;
+        shard.history.max_stake_vote_multiplier = locker_history.max_stake_vote_multiplier;
+        shard.history.retention_seconds = locker_history.retention_seconds;
+        shard.history.period_seconds = locker_history.period_seconds;
+        shard.history.origin_ts = locker_history.origin_ts;
+        shard.history.payer = self.payer.key();
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateLockerHistoryShard>, index: u16) -> Result<()> {
+    ctx.accounts
+        .create_locker_history_shard(*unwrap_int!(ctx.bumps.get("shard")), index)
+}
+
+impl<'info> Validate<'info> for CreateLockerHistoryShard<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+//! Processor for [snapshots::create_proposal_snapshot].
+
+use crate::*;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::create_proposal_snapshot].
+#[derive(Accounts)]
+pub struct CreateProposalSnapshot<'info> {
+    /// The [VoterWeightRegistrar].
+    pub registrar: Account<'info, VoterWeightRegistrar>,
+
+    /// The SPL Governance governance of the proposal.
+    /// CHECK: Decoded in [CreateProposalSnapshot::validate].
+    pub governance: UncheckedAccount<'info>,
+
+    /// The SPL Governance proposal.
+    /// CHECK: Decoded in [CreateProposalSnapshot::validate].
+    pub proposal: UncheckedAccount<'info>,
+
+    /// The [ProposalSnapshot] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"ProposalSnapshot".as_ref(),
+            registrar.key().as_ref(),
+            proposal.key().as_ref()
+        ],
+        bump,
+        space = 8 + ProposalSnapshot::LEN,
+        payer = payer
+    )]
+    pub proposal_snapshot: Account<'info, ProposalSnapshot>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateProposalSnapshot<'info> {
+    fn create_proposal_snapshot(&mut self, bump: u8) -> Result<()> {
+        let proposal = load_proposal(&self.proposal, &self.registrar.governance_program)?;
+        let draft_at = unwrap_int!(proposal.draft_at.to_u64());
+        let PeriodId { era, period } =
+            unwrap_int!(self.registrar.geometry().period_of_ts(draft_at));
+
+        let proposal_snapshot = &mut self.proposal_snapshot;
+        proposal_snapshot.registrar = self.registrar.key();
+        proposal_snapshot.proposal = self.proposal.key();
+        proposal_snapshot.era = era.into();
+        proposal_snapshot.period = period.into();
+        proposal_snapshot.bump = bump;
+
+        emit!(ProposalSnapshotCreated {
+            proposal: self.proposal.key(),
+            proposal_snapshot: self.proposal_snapshot.key(),
+            era: era.into(),
+            period: period.into(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateProposalSnapshot>) -> Result<()> {
+    ctx.accounts
+        .create_proposal_snapshot(*unwrap_int!(ctx.bumps.get("proposal_snapshot")))
+}
+
+impl<'info> Validate<'info> for CreateProposalSnapshot<'info> {
+    fn validate(&self) -> Result<()> {
+        let governance_program = &self.registrar.governance_program;
+        let governance = load_governance(&self.governance, governance_program)?;
+        assert_keys_eq!(governance.realm, self.registrar.realm);
+        let proposal = load_proposal(&self.proposal, governance_program)?;
+        assert_keys_eq!(proposal.governance, self.governance);
+        assert_keys_eq!(
+            proposal.governing_token_mint,
+            self.registrar.governing_token_mint
+        );
+        invariant!(proposal.is_draft(), ProposalNotDraft);
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::create_proposal_snapshot].
+pub struct ProposalSnapshotCreated {
+    /// The SPL Governance proposal.
+    #[index]
+    pub proposal: Pubkey,
+    /// The [ProposalSnapshot] that was created.
+    pub proposal_snapshot: Pubkey,
+    /// The era of the snapshot.
+    pub era: u16,
+    /// The period of the snapshot within the era.
+    pub period: u8,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
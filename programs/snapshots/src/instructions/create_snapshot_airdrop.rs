@@ -0,0 +1,209 @@
+//! Processor for [snapshots::create_snapshot_airdrop].
+
+use crate::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use locked_voter::Locker;
+
+/// Accounts for [snapshots::create_snapshot_airdrop].
+#[derive(Accounts)]
+#[instruction(era: u16, period: u8)]
+pub struct CreateSnapshotAirdrop<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [SnapshotConfig] of the [Locker], which may not exist.
+    /// CHECK: Only read if it has been created. See [load_era_geometry].
+    #[account(
+        seeds = [
+            b"SnapshotConfig".as_ref(),
+            locker.key().as_ref()
+        ],
+        bump
+    )]
+    pub snapshot_config: UncheckedAccount<'info>,
+
+    /// The [LockerHistory] of the snapshot.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// Mint of the token to airdrop.
+    pub mint: Account<'info, Mint>,
+
+    /// The [SnapshotAirdrop] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"SnapshotAirdrop".as_ref(),
+            locker.key().as_ref(),
+            era.to_le_bytes().as_ref(),
+            period.to_le_bytes().as_ref(),
+            mint.key().as_ref(),
+            authority.key().as_ref()
+        ],
+        bump,
+        space = 8 + SnapshotAirdrop::LEN,
+        payer = payer
+    )]
+    pub airdrop: Account<'info, SnapshotAirdrop>,
+
+    /// Token account holding the tokens of the [SnapshotAirdrop].
+    #[account(
+        init,
+        seeds = [
+            b"SnapshotAirdropVault".as_ref(),
+            airdrop.key().as_ref()
+        ],
+        bump,
+        token::mint = mint,
+        token::authority = airdrop,
+        payer = payer
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Authority of the airdrop, which funds it.
+    pub authority: Signer<'info>,
+
+    /// Tokens to fund the airdrop with.
+    #[account(mut)]
+    pub source: Account<'info, TokenAccount>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+
+    /// [Rent] sysvar.
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> CreateSnapshotAirdrop<'info> {
+    #[allow(clippy::too_many_arguments)]
+    fn create_snapshot_airdrop(
+        &mut self,
+        bump: u8,
+        era: u16,
+        period: u8,
+        root: [u8; 32],
+        max_total_claim: u64,
+        max_num_nodes: u64,
+        claim_deadline: i64,
+    ) -> Result<()> {
+        let locker_history = self.locker_history.load()?;
+        invariant!(locker_history.era == era, EraMismatch);
+        // Proofs are built from escrow histories, which use the geometry of the config.
+        invariant!(
+            locker_history.geometry() == load_era_geometry(&self.snapshot_config)?,
+            GeometryMismatch
+        );
+        // Only finalized totals can be used, otherwise claims would not match the snapshot.
+        invariant!(locker_history.is_period_final(period), PeriodNotFinal);
+        invariant!(
+            claim_deadline > Clock::get()?.unix_timestamp,
+            AirdropClaimDeadlinePassed
+        );
+
+        token::transfer(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.source.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    authority: self.authority.to_account_info(),
+                },
+            ),
+            max_total_claim,
+        )?;
+
+        let airdrop = &mut self.airdrop;
+        airdrop.authority = self.authority.key();
+        airdrop.locker = self.locker.key();
+        airdrop.era = era;
+        airdrop.period = period;
+        airdrop.bump = bump;
+        airdrop.mint = self.mint.key();
+        airdrop.vault = self.vault.key();
+        airdrop.root = root;
+        airdrop.locker_ve_balance = locker_history.ve_balances[usize::from(period)];
+        airdrop.max_total_claim = max_total_claim;
+        airdrop.max_num_nodes = max_num_nodes;
+        airdrop.claim_deadline = claim_deadline;
+
+        emit!(SnapshotAirdropCreated {
+            locker: airdrop.locker,
+            airdrop: airdrop.key(),
+            era: airdrop.era,
+            period,
+            mint: airdrop.mint,
+            authority: airdrop.authority,
+            root,
+            locker_ve_balance: airdrop.locker_ve_balance,
+            max_total_claim,
+            max_num_nodes,
+            claim_deadline,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(
+    ctx: Context<CreateSnapshotAirdrop>,
+    era: u16,
+    period: u8,
+    root: [u8; 32],
+    max_total_claim: u64,
+    max_num_nodes: u64,
+    claim_deadline: i64,
+) -> Result<()> {
+    ctx.accounts.create_snapshot_airdrop(
+        *unwrap_int!(ctx.bumps.get("airdrop")),
+        era,
+        period,
+        root,
+        max_total_claim,
+        max_num_nodes,
+        claim_deadline,
+    )
+}
+
+impl<'info> Validate<'info> for CreateSnapshotAirdrop<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.locker_history.load()?.locker, self.locker);
+        assert_keys_eq!(self.source.mint, self.mint);
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::create_snapshot_airdrop].
+pub struct SnapshotAirdropCreated {
+    /// The [locked_voter::Locker].
+    #[index]
+    pub locker: Pubkey,
+    /// The [SnapshotAirdrop] that was created.
+    pub airdrop: Pubkey,
+    /// The era of the snapshot.
+    pub era: u16,
+    /// The period of the snapshot.
+    pub period: u8,
+    /// Mint of the token being airdropped.
+    pub mint: Pubkey,
+    /// Authority of the airdrop.
+    pub authority: Pubkey,
+    /// Root of the Merkle tree of claims.
+    pub root: [u8; 32],
+    /// [LockerHistory] total of the period.
+    pub locker_ve_balance: u64,
+    /// Amount of tokens deposited.
+    pub max_total_claim: u64,
+    /// Number of claims.
+    pub max_num_nodes: u64,
+    /// When claims close.
+    pub claim_deadline: i64,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
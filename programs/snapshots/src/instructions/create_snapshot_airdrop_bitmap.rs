@@ -0,0 +1,60 @@
+//! Processor for [snapshots::create_snapshot_airdrop_bitmap].
+
+use crate::*;
+
+/// Accounts for [snapshots::create_snapshot_airdrop_bitmap].
+#[derive(Accounts)]
+#[instruction(page: u32)]
+pub struct CreateSnapshotAirdropBitmap<'info> {
+    /// The [SnapshotAirdrop].
+    pub airdrop: Account<'info, SnapshotAirdrop>,
+
+    /// The [SnapshotAirdropBitmap] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"SnapshotAirdropBitmap".as_ref(),
+            airdrop.key().as_ref(),
+            page.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + SnapshotAirdropBitmap::LEN,
+        payer = payer
+    )]
+    pub bitmap: AccountLoader<'info, SnapshotAirdropBitmap>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateSnapshotAirdropBitmap<'info> {
+    fn create_snapshot_airdrop_bitmap(&mut self, bump: u8, page: u32) -> Result<()> {
+        // pages past the last claim would never be used
+        let first_claim = unwrap_int!(u64::from(page).checked_mul(SNAPSHOT_AIRDROP_BITMAP_CLAIMS));
+        invariant!(
+            first_claim < self.airdrop.max_num_nodes,
+            "page is past the last claim"
+        );
+
+        let bitmap = &mut self.bitmap.load_init()?;
+        bitmap.airdrop = self.airdrop.key();
+        bitmap.page = page;
+        bitmap.bump = bump;
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateSnapshotAirdropBitmap>, page: u32) -> Result<()> {
+    ctx.accounts
+        .create_snapshot_airdrop_bitmap(*unwrap_int!(ctx.bumps.get("bitmap")), page)
+}
+
+impl<'info> Validate<'info> for CreateSnapshotAirdropBitmap<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
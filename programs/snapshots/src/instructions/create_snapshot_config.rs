@@ -0,0 +1,183 @@
+//! Processor for [snapshots::create_snapshot_config].
+
+use crate::*;
+use govern::Governor;
+use locked_voter::Locker;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::create_snapshot_config].
+#[derive(Accounts)]
+pub struct CreateSnapshotConfig<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [Governor] of the [Locker].
+    pub governor: Account<'info, Governor>,
+
+    /// The [Governor::smart_wallet].
+    pub smart_wallet: Signer<'info>,
+
+    /// The [LockerHistory] of the current era of the [EraGeometry::DEFAULT], which must not exist.
+    /// CHECK: Only checked to be empty.
+    pub locker_history: UncheckedAccount<'info>,
+
+    /// The [SnapshotConfig] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"SnapshotConfig".as_ref(),
+            locker.key().as_ref()
+        ],
+        bump,
+        space = 8 + SnapshotConfig::LEN,
+        payer = payer
+    )]
+    pub snapshot_config: Account<'info, SnapshotConfig>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateSnapshotConfig<'info> {
+    fn create_snapshot_config(
+        &mut self,
+        bump: u8,
+        period_seconds: u64,
+        origin_ts: u64,
+    ) -> Result<()> {
+        invariant!(
+            EraGeometry::new(period_seconds, origin_ts).is_some(),
+            InvalidEraGeometry
+        );
+
+        let snapshot_config = &mut self.snapshot_config;
+        snapshot_config.locker = self.locker.key();
+        snapshot_config.bump = bump;
+        snapshot_config.period_seconds = period_seconds;
+        snapshot_config.origin_ts = origin_ts;
+
+        emit!(SnapshotConfigCreated {
+            locker: self.locker.key(),
+            snapshot_config: self.snapshot_config.key(),
+            period_seconds,
+            origin_ts,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+/// Reads the [EraGeometry] of a [Locker] from the address of its [SnapshotConfig],
+/// which is the [EraGeometry::DEFAULT] if the config has not been created.
+pub(crate) fn load_era_geometry(snapshot_config: &AccountInfo) -> Result<EraGeometry> {
+    if snapshot_config.data_is_empty() {
+        return Ok(EraGeometry::DEFAULT);
+    }
+    Ok(Account::<SnapshotConfig>::try_from(snapshot_config)?.geometry())
+}
+
+pub fn handler(
+    ctx: Context<CreateSnapshotConfig>,
+    period_seconds: u64,
+    origin_ts: u64,
+) -> Result<()> {
+    ctx.accounts.create_snapshot_config(
+        *unwrap_int!(ctx.bumps.get("snapshot_config")),
+        period_seconds,
+        origin_ts,
+    )
+}
+
+impl<'info> Validate<'info> for CreateSnapshotConfig<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.locker.governor, self.governor);
+        assert_keys_eq!(self.governor.smart_wallet, self.smart_wallet);
+
+        // Histories record the geometry when they are created, so changing it afterwards
+        // would leave the locker with histories of different geometries.
+        let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+        let (era, _) = unwrap_int!(calculate_era_and_period_of_ts(now));
+        let (locker_history, _) = Pubkey::find_program_address(
+            &[
+                b"LockerHistory".as_ref(),
+                self.locker.key().as_ref(),
+                era.to_le_bytes().as_ref(),
+            ],
+            &crate::ID,
+        );
+        assert_keys_eq!(locker_history, self.locker_history, InvalidHistoryAddress);
+        invariant!(self.locker_history.data_is_empty(), LockerHistoryExists);
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::create_snapshot_config].
+pub struct SnapshotConfigCreated {
+    /// The [Locker].
+    #[index]
+    pub locker: Pubkey,
+    /// The [SnapshotConfig] that was created.
+    pub snapshot_config: Pubkey,
+    /// Number of seconds in a period.
+    pub period_seconds: u64,
+    /// The Unix timestamp of the start of era 0.
+    pub origin_ts: u64,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn load(data: &mut [u8], owner: &Pubkey) -> Result<EraGeometry> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let info = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        load_era_geometry(&info)
+    }
+
+    fn serialize(snapshot_config: &SnapshotConfig) -> Vec<u8> {
+        let mut data = vec![];
+        snapshot_config.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_load_era_geometry_without_config() {
+        assert_eq!(load(&mut [], &System::id()).unwrap(), EraGeometry::DEFAULT);
+    }
+
+    #[test]
+    fn test_load_era_geometry() {
+        for period_seconds in [86_400, 86_400 * 7] {
+            let mut data = serialize(&SnapshotConfig {
+                period_seconds,
+                origin_ts: 1_700_000_000,
+                ..Default::default()
+            });
+            assert_eq!(
+                load(&mut data, &crate::ID).unwrap(),
+                EraGeometry::new(period_seconds, 1_700_000_000).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_load_era_geometry_rejects_other_accounts() {
+        let mut data = serialize(&SnapshotConfig {
+            period_seconds: 86_400,
+            ..Default::default()
+        });
+        assert!(load(&mut data, &Pubkey::new_unique()).is_err());
+
+        let mut data = vec![0; 8 + SnapshotConfig::LEN];
+        assert!(load(&mut data, &crate::ID).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+//! Processor for [snapshots::create_vote_weight_receipt].
+
+use crate::*;
+use locked_voter::Escrow;
+
+/// Accounts for [snapshots::create_vote_weight_receipt].
+#[derive(Accounts)]
+pub struct CreateVoteWeightReceipt<'info> {
+    /// The [GovernProposalSnapshot].
+    pub snapshot: Account<'info, GovernProposalSnapshot>,
+
+    /// The [Escrow].
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [EscrowHistory] or [CompactEscrowHistory] of the era of the [GovernProposalSnapshot].
+    /// CHECK: Checked to be an [EscrowHistory] or [CompactEscrowHistory] when loaded.
+    pub escrow_history: UncheckedAccount<'info>,
+
+    /// The [VoteWeightReceipt] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"VoteWeightReceipt".as_ref(),
+            snapshot.key().as_ref(),
+            escrow.key().as_ref()
+        ],
+        bump,
+        space = 8 + VoteWeightReceipt::LEN,
+        payer = payer
+    )]
+    pub receipt: Account<'info, VoteWeightReceipt>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateVoteWeightReceipt<'info> {
+    fn create_vote_weight_receipt(&mut self, bump: u8) -> Result<()> {
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let escrow_history = escrow_history_loader.load()?;
+        invariant!(escrow_history.era() == self.snapshot.era, EraMismatch);
+        invariant!(
+            escrow_history.geometry() == self.snapshot.geometry(),
+            GeometryMismatch
+        );
+        let voting_power = unwrap_int!(escrow_history.calculate_ve_balance(self.snapshot.period));
+
+        let receipt = &mut self.receipt;
+        receipt.snapshot = self.snapshot.key();
+        receipt.escrow = self.escrow.key();
+        receipt.owner = self.escrow.owner;
+        receipt.bump = bump;
+        receipt.voting_power = voting_power;
+
+        emit!(VoteWeightReceiptCreated {
+            snapshot: receipt.snapshot,
+            escrow: receipt.escrow,
+            owner: receipt.owner,
+            voting_power,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateVoteWeightReceipt>) -> Result<()> {
+    ctx.accounts
+        .create_vote_weight_receipt(*unwrap_int!(ctx.bumps.get("receipt")))
+}
+
+impl<'info> Validate<'info> for CreateVoteWeightReceipt<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.escrow.locker, self.snapshot.locker);
+        assert_keys_eq!(
+            AnyEscrowHistory::try_from(&self.escrow_history)?
+                .load()?
+                .escrow(),
+            self.escrow
+        );
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::create_vote_weight_receipt].
+pub struct VoteWeightReceiptCreated {
+    /// The [GovernProposalSnapshot].
+    #[index]
+    pub snapshot: Pubkey,
+    /// The [Escrow].
+    #[index]
+    pub escrow: Pubkey,
+    /// The [Escrow::owner].
+    pub owner: Pubkey,
+    /// Voting power of the escrow at the period of the snapshot.
+    pub voting_power: u64,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
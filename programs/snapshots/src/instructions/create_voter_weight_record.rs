@@ -0,0 +1,56 @@
+//! Processor for [snapshots::create_voter_weight_record].
+
+use crate::*;
+
+/// Accounts for [snapshots::create_voter_weight_record].
+#[derive(Accounts)]
+pub struct CreateVoterWeightRecord<'info> {
+    /// The [VoterWeightRegistrar].
+    pub registrar: Account<'info, VoterWeightRegistrar>,
+
+    /// The owner of the governing tokens.
+    /// CHECK: Any account may own a [locked_voter::Escrow].
+    pub governing_token_owner: UncheckedAccount<'info>,
+
+    /// The [VoterWeightRecord] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"VoterWeightRecord".as_ref(),
+            registrar.key().as_ref(),
+            governing_token_owner.key().as_ref()
+        ],
+        bump,
+        space = 8 + VoterWeightRecord::LEN,
+        payer = payer
+    )]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateVoterWeightRecord<'info> {
+    fn create_voter_weight_record(&mut self) -> Result<()> {
+        let voter_weight_record = &mut self.voter_weight_record;
+        voter_weight_record.realm = self.registrar.realm;
+        voter_weight_record.governing_token_mint = self.registrar.governing_token_mint;
+        voter_weight_record.governing_token_owner = self.governing_token_owner.key();
+        voter_weight_record.voter_weight_expiry = Some(0);
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateVoterWeightRecord>) -> Result<()> {
+    ctx.accounts.create_voter_weight_record()
+}
+
+impl<'info> Validate<'info> for CreateVoterWeightRecord<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+//! Processor for [snapshots::create_voter_weight_registrar].
+
+use crate::*;
+use locked_voter::Locker;
+
+/// Accounts for [snapshots::create_voter_weight_registrar].
+#[derive(Accounts)]
+pub struct CreateVoterWeightRegistrar<'info> {
+    /// The SPL Governance realm.
+    /// CHECK: Decoded in [CreateVoterWeightRegistrar::validate].
+    pub realm: UncheckedAccount<'info>,
+
+    /// The authority of the realm.
+    pub realm_authority: Signer<'info>,
+
+    /// The [Locker] whose escrows vote in the realm.
+    pub locker: Account<'info, Locker>,
+
+    /// The [SnapshotConfig] of the [Locker], which may not exist.
+    /// CHECK: Only read if it has been created. See [load_era_geometry].
+    #[account(
+        seeds = [
+            b"SnapshotConfig".as_ref(),
+            locker.key().as_ref()
+        ],
+        bump
+    )]
+    pub snapshot_config: UncheckedAccount<'info>,
+
+    /// The [VoterWeightRegistrar] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"VoterWeightRegistrar".as_ref(),
+            realm.key().as_ref()
+        ],
+        bump,
+        space = 8 + VoterWeightRegistrar::LEN,
+        payer = payer
+    )]
+    pub registrar: Account<'info, VoterWeightRegistrar>,
+
+    /// Payer.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateVoterWeightRegistrar<'info> {
+    fn create_voter_weight_registrar(&mut self, bump: u8) -> Result<()> {
+        let geometry = load_era_geometry(&self.snapshot_config)?;
+        let registrar = &mut self.registrar;
+        registrar.realm = self.realm.key();
+        registrar.governance_program = *self.realm.owner;
+        registrar.governing_token_mint = self.locker.token_mint;
+        registrar.locker = self.locker.key();
+        registrar.bump = bump;
+        registrar.period_seconds = geometry.period_seconds;
+        registrar.origin_ts = geometry.origin_ts;
+
+        emit!(VoterWeightRegistrarCreated {
+            realm: self.realm.key(),
+            registrar: self.registrar.key(),
+            locker: self.locker.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CreateVoterWeightRegistrar>) -> Result<()> {
+    ctx.accounts
+        .create_voter_weight_registrar(*unwrap_int!(ctx.bumps.get("registrar")))
+}
+
+impl<'info> Validate<'info> for CreateVoterWeightRegistrar<'info> {
+    fn validate(&self) -> Result<()> {
+        let realm = load_realm(&self.realm, self.realm.owner)?;
+        invariant!(
+            realm.authority == Some(self.realm_authority.key()),
+            "realm_authority must be the authority of the realm"
+        );
+        assert_keys_eq!(realm.community_mint, self.locker.token_mint);
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::create_voter_weight_registrar].
+pub struct VoterWeightRegistrarCreated {
+    /// The SPL Governance realm.
+    #[index]
+    pub realm: Pubkey,
+    /// The [VoterWeightRegistrar] that was created.
+    pub registrar: Pubkey,
+    /// The [Locker] whose escrows vote in the realm.
+    pub locker: Pubkey,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
@@ -0,0 +1,109 @@
+//! Processor for [snapshots::expand_compact_escrow_history].
+
+use crate::*;
+use locked_voter::Escrow;
+
+/// Accounts for [snapshots::expand_compact_escrow_history].
+#[derive(Accounts)]
+#[instruction(era: u16)]
+pub struct ExpandCompactEscrowHistory<'info> {
+    /// The [Escrow].
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [CompactEscrowHistory] to expand, which is closed.
+    #[account(mut, close = compact_escrow_history_payer)]
+    pub compact_escrow_history: AccountLoader<'info, CompactEscrowHistory>,
+
+    /// The account that paid for the [CompactEscrowHistory], which receives its rent.
+    /// CHECK: Checked against [CompactEscrowHistory::payer].
+    #[account(mut)]
+    pub compact_escrow_history_payer: UncheckedAccount<'info>,
+
+    /// The [EscrowHistory] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"EscrowHistory".as_ref(),
+            escrow.key().as_ref(),
+            era.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + EscrowHistory::LEN,
+        payer = payer
+    )]
+    pub escrow_history: AccountLoader<'info, EscrowHistory>,
+
+    /// Payer of the [EscrowHistory].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExpandCompactEscrowHistory<'info> {
+    fn expand_compact_escrow_history(&mut self, bump: u8, era: u16) -> Result<()> {
+        let compact_escrow_history = self.compact_escrow_history.load()?;
+        invariant!(compact_escrow_history.era == era, EraMismatch);
+        let escrow_history = &mut *self.escrow_history.load_init()?;
+        init_escrow_history(
+            escrow_history,
+            compact_escrow_history.escrow,
+            &compact_escrow_history.geometry(),
+            era,
+            bump,
+            compact_escrow_history.retention_seconds,
+            self.payer.key(),
+        );
+        unwrap_int!(escrow_history.expand(&compact_escrow_history));
+
+        emit!(EscrowHistoryCreated {
+            escrow: self.escrow.key(),
+            escrow_history: self.escrow_history.key(),
+            era,
+            payer: self.payer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        emit!(CompactEscrowHistoryExpanded {
+            escrow: self.escrow.key(),
+            compact_escrow_history: self.compact_escrow_history.key(),
+            escrow_history: self.escrow_history.key(),
+            era,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<ExpandCompactEscrowHistory>, era: u16) -> Result<()> {
+    ctx.accounts
+        .expand_compact_escrow_history(*unwrap_int!(ctx.bumps.get("escrow_history")), era)
+}
+
+impl<'info> Validate<'info> for ExpandCompactEscrowHistory<'info> {
+    fn validate(&self) -> Result<()> {
+        let compact_escrow_history = self.compact_escrow_history.load()?;
+        assert_keys_eq!(compact_escrow_history.escrow, self.escrow);
+        assert_keys_eq!(
+            compact_escrow_history.payer,
+            self.compact_escrow_history_payer
+        );
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::expand_compact_escrow_history].
+pub struct CompactEscrowHistoryExpanded {
+    /// The [Escrow].
+    #[index]
+    pub escrow: Pubkey,
+    /// The [CompactEscrowHistory] that was closed.
+    pub compact_escrow_history: Pubkey,
+    /// The [EscrowHistory] that replaced it.
+    pub escrow_history: Pubkey,
+    /// The era of the histories.
+    pub era: u16,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
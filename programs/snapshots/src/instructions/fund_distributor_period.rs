@@ -0,0 +1,70 @@
+//! Processor for [snapshots::fund_distributor_period].
+
+use crate::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+/// Accounts for [snapshots::fund_distributor_period].
+#[derive(Accounts)]
+pub struct FundDistributorPeriod<'info> {
+    /// The [Distributor].
+    pub distributor: Account<'info, Distributor>,
+
+    /// The [DistributorPeriod] to fund.
+    #[account(mut, has_one = distributor)]
+    pub distributor_period: Account<'info, DistributorPeriod>,
+
+    /// [Distributor::vault].
+    #[account(mut)]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Tokens to fund the period with.
+    #[account(mut)]
+    pub source: Account<'info, TokenAccount>,
+
+    /// Owner of the [Self::source].
+    pub source_authority: Signer<'info>,
+
+    /// [Token] program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FundDistributorPeriod<'info> {
+    fn fund_distributor_period(&mut self, amount: u64) -> Result<()> {
+        token::transfer(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.source.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    authority: self.source_authority.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let distributor_period = &mut self.distributor_period;
+        distributor_period.rewards = unwrap_int!(distributor_period.rewards.checked_add(amount));
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<FundDistributorPeriod>, amount: u64) -> Result<()> {
+    ctx.accounts.fund_distributor_period(amount)
+}
+
+impl<'info> Validate<'info> for FundDistributorPeriod<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.vault, self.distributor.vault);
+
+        // Rewards are split once the period elapses, so they can no longer be added to.
+        let now = Clock::get()?.unix_timestamp;
+        invariant!(
+            !unwrap_int!(self.distributor.geometry().has_period_elapsed(
+                PeriodId::from((self.distributor_period.era, self.distributor_period.period)),
+                now
+            )),
+            PeriodElapsed
+        );
+        Ok(())
+    }
+}
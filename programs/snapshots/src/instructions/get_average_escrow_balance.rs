@@ -0,0 +1,76 @@
+//! Processor for [snapshots::get_average_escrow_balance].
+
+use crate::*;
+use num_traits::ToPrimitive;
+use std::iter;
+
+/// Accounts for [snapshots::get_average_escrow_balance].
+///
+/// The remaining accounts are the [EscrowHistory]s or [CompactEscrowHistory]s of each following era
+/// of the range, in order. They may be followed by the [CumulativeBalances] of any of the histories,
+/// which are then read instead of the balance of each period they have recorded.
+#[derive(Accounts)]
+pub struct GetAverageEscrowBalance<'info> {
+    /// The [EscrowHistory] or [CompactEscrowHistory] of the first era of the range.
+    /// CHECK: Checked to be an [EscrowHistory] or [CompactEscrowHistory] when loaded.
+    pub escrow_history: UncheckedAccount<'info>,
+}
+
+impl<'info> GetAverageEscrowBalance<'info> {
+    fn get_average_escrow_balance(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>],
+        query: PeriodRangeQuery,
+    ) -> Result<u64> {
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let first_history = escrow_history_loader.load()?;
+        let (start, end) = unwrap_int!(query.calculate_period_range(&first_history.geometry()));
+        invariant!(start <= end, InvalidPeriodRange);
+        invariant!(start.era.0 == first_history.era(), EraMismatch);
+        let (history_infos, cumulative_balances) =
+            ViewCumulativeBalances::split(remaining_accounts)?;
+        let eras = start.era.0..=end.era.0;
+        invariant!(
+            eras.len() == unwrap_int!(history_infos.len().checked_add(1)),
+            "expected an EscrowHistory for each era of the range"
+        );
+
+        let mut ve_balance = TimeWeightedAverage::default();
+        let history_infos = iter::once(self.escrow_history.as_ref()).chain(history_infos);
+        for (era, history_info) in eras.zip(history_infos) {
+            let history_loader = AnyEscrowHistory::try_from(history_info)?;
+            let history = history_loader.load()?;
+            assert_keys_eq!(history.escrow(), first_history.escrow());
+            invariant!(history.era() == era, EraMismatch);
+            invariant!(
+                history.geometry() == first_history.geometry(),
+                GeometryMismatch
+            );
+            let periods = calculate_periods_of_era(start, end, era);
+            if let Some(sum) = cumulative_balances.calculate_sum(history_info.key, &periods)? {
+                unwrap_int!(ve_balance.add_sum(sum, unwrap_int!(periods.len().to_u64())));
+                continue;
+            }
+            for period in periods {
+                unwrap_int!(ve_balance.add(unwrap_int!(history.calculate_ve_balance(period))));
+            }
+        }
+        Ok(unwrap_int!(ve_balance.average()))
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, GetAverageEscrowBalance<'info>>,
+    query: PeriodRangeQuery,
+) -> Result<()> {
+    set_view_return_data(
+        &ctx.accounts
+            .get_average_escrow_balance(ctx.remaining_accounts, query)?,
+    )
+}
+
+impl<'info> Validate<'info> for GetAverageEscrowBalance<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
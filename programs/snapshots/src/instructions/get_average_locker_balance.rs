@@ -0,0 +1,91 @@
+//! Processor for [snapshots::get_average_locker_balance].
+
+use crate::*;
+use num_traits::ToPrimitive;
+use std::iter;
+
+/// Accounts for [snapshots::get_average_locker_balance].
+///
+/// The remaining accounts are the [LockerHistory]s of each following era of the range, in order.
+/// They may be followed by the [CumulativeBalances] of any of the histories, which are then read
+/// instead of the total balance of each period they have recorded.
+#[derive(Accounts)]
+pub struct GetAverageLockerBalance<'info> {
+    /// The [LockerHistory] of the first era of the range.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+}
+
+impl<'info> GetAverageLockerBalance<'info> {
+    fn get_average_locker_balance(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>],
+        query: PeriodRangeQuery,
+    ) -> Result<LockerBalance> {
+        let first_history = self.locker_history.load()?;
+        let (start, end) = unwrap_int!(query.calculate_period_range(&first_history.geometry()));
+        invariant!(start <= end, InvalidPeriodRange);
+        invariant!(start.era.0 == first_history.era, EraMismatch);
+        let (history_infos, cumulative_balances) =
+            ViewCumulativeBalances::split(remaining_accounts)?;
+        let eras = start.era.0..=end.era.0;
+        invariant!(
+            eras.len() == unwrap_int!(history_infos.len().checked_add(1)),
+            "expected a LockerHistory for each era of the range"
+        );
+
+        let mut ve_balance = TimeWeightedAverage::default();
+        let mut ve_count = TimeWeightedAverage::default();
+        let mut is_final = true;
+        let history_infos = iter::once(self.locker_history.as_ref()).chain(history_infos);
+        for (era, history_info) in eras.zip(history_infos) {
+            let history_loader: AccountLoader<LockerHistory> =
+                AccountLoader::try_from(history_info)?;
+            let history = history_loader.load()?;
+            assert_keys_eq!(history.locker, first_history.locker);
+            invariant!(history.era == era, EraMismatch);
+            invariant!(
+                history.geometry() == first_history.geometry(),
+                GeometryMismatch
+            );
+            let periods = calculate_periods_of_era(start, end, era);
+            // Only final periods are recorded in the sums.
+            let sum = cumulative_balances.calculate_sum(history_info.key, &periods)?;
+            if let Some(sum) = sum {
+                unwrap_int!(ve_balance.add_sum(sum, unwrap_int!(periods.len().to_u64())));
+            }
+            for period in periods {
+                // Totals are only written once the period has been materialized.
+                invariant!(
+                    u16::from(period) < history.materialized_periods,
+                    PeriodNotElapsed
+                );
+                if sum.is_none() {
+                    unwrap_int!(ve_balance.add(history.ve_balances[usize::from(period)]));
+                    is_final = is_final && history.is_period_final(period);
+                }
+                unwrap_int!(ve_count.add(history.ve_counts[usize::from(period)]));
+            }
+        }
+        Ok(LockerBalance {
+            ve_balance: unwrap_int!(ve_balance.average()),
+            ve_count: unwrap_int!(ve_count.average()),
+            is_final,
+        })
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, GetAverageLockerBalance<'info>>,
+    query: PeriodRangeQuery,
+) -> Result<()> {
+    set_view_return_data(
+        &ctx.accounts
+            .get_average_locker_balance(ctx.remaining_accounts, query)?,
+    )
+}
+
+impl<'info> Validate<'info> for GetAverageLockerBalance<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+//! Processor for [snapshots::get_average_share].
+
+use crate::*;
+use locked_voter::Escrow;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::get_average_share].
+///
+/// The remaining accounts are `(LockerHistory, EscrowHistory)` pairs,
+/// one for each following era of the range, in order. They may be followed by the
+/// [CumulativeBalances] of any of the histories, which are then read instead of the
+/// balances of each period they have recorded.
+#[derive(Accounts)]
+pub struct GetAverageShare<'info> {
+    /// The [Escrow].
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [LockerHistory] of the first era of the range.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [EscrowHistory] or [CompactEscrowHistory] of the first era of the range.
+    /// CHECK: Checked to be an [EscrowHistory] or [CompactEscrowHistory] when loaded.
+    pub escrow_history: UncheckedAccount<'info>,
+}
+
+impl<'info> GetAverageShare<'info> {
+    fn get_average_share(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>],
+        query: PeriodRangeQuery,
+    ) -> Result<EscrowShare> {
+        let geometry = self.locker_history.load()?.geometry();
+        let (start, end) = unwrap_int!(query.calculate_period_range(&geometry));
+        invariant!(start <= end, InvalidPeriodRange);
+        let (history_infos, cumulative_balances) =
+            ViewCumulativeBalances::split(remaining_accounts)?;
+        let pairs = history_infos.chunks_exact(2);
+        invariant!(
+            pairs.remainder().is_empty(),
+            "expected (LockerHistory, EscrowHistory) pairs"
+        );
+        let eras = start.era.0..=end.era.0;
+        invariant!(
+            eras.len() == unwrap_int!(pairs.len().checked_add(1)),
+            "expected a pair of histories for each era of the range"
+        );
+
+        let mut escrow_ve_balance = TimeWeightedAverage::default();
+        let mut locker_ve_balance = TimeWeightedAverage::default();
+        let mut is_final = true;
+        let history_infos = [[self.locker_history.as_ref(), self.escrow_history.as_ref()]]
+            .into_iter()
+            .chain(pairs.map(|pair| [pair[0], pair[1]]));
+        for (era, [locker_history_info, escrow_history_info]) in eras.zip(history_infos) {
+            let locker_history_loader: AccountLoader<LockerHistory> =
+                AccountLoader::try_from(locker_history_info)?;
+            let escrow_history_loader = AnyEscrowHistory::try_from(escrow_history_info)?;
+            let locker_history = locker_history_loader.load()?;
+            let escrow_history = escrow_history_loader.load()?;
+            assert_keys_eq!(
+                locker_history.locker,
+                self.escrow.locker,
+                LockerEscrowMismatch
+            );
+            assert_keys_eq!(escrow_history.escrow(), self.escrow);
+            invariant!(locker_history.era == era, EraMismatch);
+            invariant!(escrow_history.era() == era, EraMismatch);
+            invariant!(locker_history.geometry() == geometry, GeometryMismatch);
+            invariant!(escrow_history.geometry() == geometry, GeometryMismatch);
+            let periods = calculate_periods_of_era(start, end, era);
+            let num_periods = unwrap_int!(periods.len().to_u64());
+            match cumulative_balances.calculate_sum(escrow_history_info.key, &periods)? {
+                Some(sum) => unwrap_int!(escrow_ve_balance.add_sum(sum, num_periods)),
+                None => {
+                    for period in periods.clone() {
+                        unwrap_int!(escrow_ve_balance
+                            .add(unwrap_int!(escrow_history.calculate_ve_balance(period))));
+                    }
+                }
+            }
+            // Only final periods are recorded in the sums.
+            match cumulative_balances.calculate_sum(locker_history_info.key, &periods)? {
+                Some(sum) => unwrap_int!(locker_ve_balance.add_sum(sum, num_periods)),
+                None => {
+                    for period in periods {
+                        invariant!(
+                            u16::from(period) < locker_history.materialized_periods,
+                            PeriodNotElapsed
+                        );
+                        unwrap_int!(
+                            locker_ve_balance.add(locker_history.ve_balances[usize::from(period)])
+                        );
+                        is_final = is_final && locker_history.is_period_final(period);
+                    }
+                }
+            }
+        }
+        Ok(EscrowShare {
+            escrow_ve_balance: unwrap_int!(escrow_ve_balance.average()),
+            locker_ve_balance: unwrap_int!(locker_ve_balance.average()),
+            is_final,
+        })
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, GetAverageShare<'info>>,
+    query: PeriodRangeQuery,
+) -> Result<()> {
+    set_view_return_data(
+        &ctx.accounts
+            .get_average_share(ctx.remaining_accounts, query)?,
+    )
+}
+
+impl<'info> Validate<'info> for GetAverageShare<'info> {
+    fn validate(&self) -> Result<()> {
+        let locker_history = self.locker_history.load()?;
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let escrow_history = escrow_history_loader.load()?;
+        assert_keys_eq!(
+            locker_history.locker,
+            self.escrow.locker,
+            LockerEscrowMismatch
+        );
+        assert_keys_eq!(escrow_history.escrow(), self.escrow);
+        invariant!(locker_history.era == escrow_history.era(), EraMismatch);
+        invariant!(
+            locker_history.geometry() == escrow_history.geometry(),
+            GeometryMismatch
+        );
+        Ok(())
+    }
+}
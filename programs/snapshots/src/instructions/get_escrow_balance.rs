@@ -0,0 +1,31 @@
+//! Processor for [snapshots::get_escrow_balance].
+
+use crate::*;
+
+/// Accounts for [snapshots::get_escrow_balance].
+#[derive(Accounts)]
+pub struct GetEscrowBalance<'info> {
+    /// The [EscrowHistory] or [CompactEscrowHistory] of the era.
+    /// CHECK: Checked to be an [EscrowHistory] or [CompactEscrowHistory] when loaded.
+    pub escrow_history: UncheckedAccount<'info>,
+}
+
+impl<'info> GetEscrowBalance<'info> {
+    fn get_escrow_balance(&self, query: PeriodQuery) -> Result<u64> {
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let escrow_history = escrow_history_loader.load()?;
+        let (era, period) = unwrap_int!(query.calculate_era_and_period(&escrow_history.geometry()));
+        invariant!(era == escrow_history.era(), EraMismatch);
+        Ok(unwrap_int!(escrow_history.calculate_ve_balance(period)))
+    }
+}
+
+pub fn handler(ctx: Context<GetEscrowBalance>, query: PeriodQuery) -> Result<()> {
+    set_view_return_data(&ctx.accounts.get_escrow_balance(query)?)
+}
+
+impl<'info> Validate<'info> for GetEscrowBalance<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+//! Processor for [snapshots::get_locker_balance].
+
+use crate::*;
+
+/// Accounts for [snapshots::get_locker_balance].
+#[derive(Accounts)]
+pub struct GetLockerBalance<'info> {
+    /// The [LockerHistory] of the era.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+}
+
+impl<'info> GetLockerBalance<'info> {
+    fn get_locker_balance(&self, query: PeriodQuery) -> Result<LockerBalance> {
+        let locker_history = self.locker_history.load()?;
+        let (era, period) = unwrap_int!(query.calculate_era_and_period(&locker_history.geometry()));
+        invariant!(era == locker_history.era, EraMismatch);
+        // Totals are only written once the period has been materialized.
+        invariant!(
+            u16::from(period) < locker_history.materialized_periods,
+            PeriodNotElapsed
+        );
+        Ok(LockerBalance {
+            ve_balance: locker_history.ve_balances[usize::from(period)],
+            ve_count: locker_history.ve_counts[usize::from(period)],
+            is_final: locker_history.is_period_final(period),
+        })
+    }
+}
+
+pub fn handler(ctx: Context<GetLockerBalance>, query: PeriodQuery) -> Result<()> {
+    set_view_return_data(&ctx.accounts.get_locker_balance(query)?)
+}
+
+impl<'info> Validate<'info> for GetLockerBalance<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+//! Processor for [snapshots::get_share].
+
+use crate::*;
+use locked_voter::Escrow;
+
+/// Accounts for [snapshots::get_share].
+#[derive(Accounts)]
+pub struct GetShare<'info> {
+    /// The [Escrow].
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [LockerHistory] of the era.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [EscrowHistory] or [CompactEscrowHistory] of the era.
+    /// CHECK: Checked to be an [EscrowHistory] or [CompactEscrowHistory] when loaded.
+    pub escrow_history: UncheckedAccount<'info>,
+}
+
+impl<'info> GetShare<'info> {
+    fn get_share(&self, query: PeriodQuery) -> Result<EscrowShare> {
+        let locker_history = self.locker_history.load()?;
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let escrow_history = escrow_history_loader.load()?;
+        let (era, period) = unwrap_int!(query.calculate_era_and_period(&locker_history.geometry()));
+        invariant!(era == locker_history.era, EraMismatch);
+        invariant!(
+            u16::from(period) < locker_history.materialized_periods,
+            PeriodNotElapsed
+        );
+        Ok(EscrowShare {
+            escrow_ve_balance: unwrap_int!(escrow_history.calculate_ve_balance(period)),
+            locker_ve_balance: locker_history.ve_balances[usize::from(period)],
+            is_final: locker_history.is_period_final(period),
+        })
+    }
+}
+
+pub fn handler(ctx: Context<GetShare>, query: PeriodQuery) -> Result<()> {
+    set_view_return_data(&ctx.accounts.get_share(query)?)
+}
+
+impl<'info> Validate<'info> for GetShare<'info> {
+    fn validate(&self) -> Result<()> {
+        let locker_history = self.locker_history.load()?;
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let escrow_history = escrow_history_loader.load()?;
+        assert_keys_eq!(
+            locker_history.locker,
+            self.escrow.locker,
+            LockerEscrowMismatch
+        );
+        assert_keys_eq!(escrow_history.escrow(), self.escrow);
+        invariant!(locker_history.era == escrow_history.era(), EraMismatch);
+        invariant!(
+            locker_history.geometry() == escrow_history.geometry(),
+            GeometryMismatch
+        );
+        Ok(())
+    }
+}
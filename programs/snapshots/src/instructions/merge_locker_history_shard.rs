@@ -0,0 +1,43 @@
+//! Processor for [snapshots::merge_locker_history_shard].
+
+use crate::*;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::merge_locker_history_shard].
+#[derive(Accounts)]
+pub struct MergeLockerHistoryShard<'info> {
+    /// The [LockerHistory] to merge into.
+    #[account(mut)]
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [LockerHistoryShard] to merge.
+    #[account(mut)]
+    pub shard: AccountLoader<'info, LockerHistoryShard>,
+}
+
+impl<'info> MergeLockerHistoryShard<'info> {
+    fn merge_locker_history_shard(&self) -> Result<()> {
+        let locker_history = &mut self.locker_history.load_mut()?;
+        let shard = &mut self.shard.load_mut()?;
+
+        let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+        let started_periods = unwrap_int!(calculate_started_periods(
+            &locker_history.geometry(),
+            locker_history.era,
+            now
+        ));
+        unwrap_int!(locker_history.merge_shard(shard, started_periods));
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<MergeLockerHistoryShard>) -> Result<()> {
+    ctx.accounts.merge_locker_history_shard()
+}
+
+impl<'info> Validate<'info> for MergeLockerHistoryShard<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.shard.load()?.locker_history, self.locker_history);
+        Ok(())
+    }
+}
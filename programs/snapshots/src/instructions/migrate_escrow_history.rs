@@ -0,0 +1,104 @@
+//! Processor for [snapshots::migrate_escrow_history].
+
+use crate::*;
+use locked_voter::Escrow;
+
+/// Accounts for [snapshots::migrate_escrow_history].
+#[derive(Accounts)]
+#[instruction(era: u16)]
+pub struct MigrateEscrowHistory<'info> {
+    /// The [Escrow].
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [EscrowHistory] to migrate, which is closed.
+    #[account(mut, close = payer)]
+    pub escrow_history: AccountLoader<'info, EscrowHistory>,
+
+    /// The [CompactEscrowHistory] to be created.
+    #[account(
+        init,
+        seeds = [
+            b"CompactEscrowHistory".as_ref(),
+            escrow.key().as_ref(),
+            era.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + CompactEscrowHistory::LEN,
+        payer = payer
+    )]
+    pub compact_escrow_history: AccountLoader<'info, CompactEscrowHistory>,
+
+    /// The account that paid for the [EscrowHistory], which pays for the [CompactEscrowHistory].
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateEscrowHistory<'info> {
+    fn migrate_escrow_history(&mut self, bump: u8, era: u16) -> Result<()> {
+        let escrow_history = self.escrow_history.load()?;
+        invariant!(escrow_history.era == era, EraMismatch);
+        let history = &mut *self.compact_escrow_history.load_init()?;
+        history.escrow = escrow_history.escrow;
+        history.era = escrow_history.era;
+        history.bump = bump;
+        history.retention_seconds = escrow_history.retention_seconds;
+        history.payer = escrow_history.payer;
+        invariant!(
+            unwrap_int!(history.compact(&escrow_history)),
+            EscrowHistoryNotCompactable
+        );
+
+        emit!(CompactEscrowHistoryCreated {
+            escrow: self.escrow.key(),
+            compact_escrow_history: self.compact_escrow_history.key(),
+            era: escrow_history.era,
+            payer: self.payer.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        emit!(EscrowHistoryMigrated {
+            escrow: self.escrow.key(),
+            escrow_history: self.escrow_history.key(),
+            compact_escrow_history: self.compact_escrow_history.key(),
+            era: escrow_history.era,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<MigrateEscrowHistory>, era: u16) -> Result<()> {
+    ctx.accounts
+        .migrate_escrow_history(*unwrap_int!(ctx.bumps.get("compact_escrow_history")), era)
+}
+
+impl<'info> Validate<'info> for MigrateEscrowHistory<'info> {
+    fn validate(&self) -> Result<()> {
+        let escrow_history = self.escrow_history.load()?;
+        assert_keys_eq!(escrow_history.escrow, self.escrow);
+        assert_keys_eq!(escrow_history.payer, self.payer);
+        // Compact histories are only synced into the [LockerHistory].
+        if escrow_history.power != 0 {
+            invariant!(escrow_history.shard == 0, ShardMismatch);
+        }
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::migrate_escrow_history].
+pub struct EscrowHistoryMigrated {
+    /// The [Escrow].
+    #[index]
+    pub escrow: Pubkey,
+    /// The [EscrowHistory] that was closed.
+    pub escrow_history: Pubkey,
+    /// The [CompactEscrowHistory] that replaced it.
+    pub compact_escrow_history: Pubkey,
+    /// The era of the histories.
+    pub era: u16,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
@@ -1,9 +1,97 @@
 //! Instructions for the [crate::snapshots] program.
 
+pub mod checkpoint_escrow_cumulative_balances;
+pub mod checkpoint_locker_cumulative_balances;
+pub mod checkpoint_locker_history;
+pub mod claim;
+pub mod claim_snapshot_airdrop;
+pub mod clawback_distributor_period;
+pub mod clawback_snapshot_airdrop;
+pub mod close_compact_escrow_history;
+pub mod close_escrow_history;
+pub mod close_locker_history;
+pub mod close_locker_history_shard;
+pub mod create_compact_escrow_history;
+pub mod create_distributor;
+pub mod create_distributor_period;
+pub mod create_escrow_cumulative_balances;
+pub mod create_escrow_histories;
 pub mod create_escrow_history;
+pub mod create_govern_proposal_snapshot;
+pub mod create_locker_cumulative_balances;
 pub mod create_locker_history;
+pub mod create_locker_history_shard;
+pub mod create_proposal_snapshot;
+pub mod create_snapshot_airdrop;
+pub mod create_snapshot_airdrop_bitmap;
+pub mod create_snapshot_config;
+pub mod create_vote_weight_receipt;
+pub mod create_voter_weight_record;
+pub mod create_voter_weight_registrar;
+pub mod expand_compact_escrow_history;
+pub mod fund_distributor_period;
+pub mod get_average_escrow_balance;
+pub mod get_average_locker_balance;
+pub mod get_average_share;
+pub mod get_escrow_balance;
+pub mod get_locker_balance;
+pub mod get_share;
+pub mod merge_locker_history_shard;
+pub mod migrate_escrow_history;
+pub mod reset_locker_history;
+pub mod reset_locker_history_shard;
 pub mod sync;
+pub mod sync_all_eras;
+pub mod sync_compact;
+pub mod sync_init_if_needed;
+pub mod sync_many;
+pub mod sync_sharded;
+pub mod update_voter_weight_record;
 
+pub use checkpoint_escrow_cumulative_balances::*;
+pub use checkpoint_locker_cumulative_balances::*;
+pub use checkpoint_locker_history::*;
+pub use claim::*;
+pub use claim_snapshot_airdrop::*;
+pub use clawback_distributor_period::*;
+pub use clawback_snapshot_airdrop::*;
+pub use close_compact_escrow_history::*;
+pub use close_escrow_history::*;
+pub use close_locker_history::*;
+pub use close_locker_history_shard::*;
+pub use create_compact_escrow_history::*;
+pub use create_distributor::*;
+pub use create_distributor_period::*;
+pub use create_escrow_cumulative_balances::*;
+pub use create_escrow_histories::*;
 pub use create_escrow_history::*;
+pub use create_govern_proposal_snapshot::*;
+pub use create_locker_cumulative_balances::*;
 pub use create_locker_history::*;
+pub use create_locker_history_shard::*;
+pub use create_proposal_snapshot::*;
+pub use create_snapshot_airdrop::*;
+pub use create_snapshot_airdrop_bitmap::*;
+pub use create_snapshot_config::*;
+pub use create_vote_weight_receipt::*;
+pub use create_voter_weight_record::*;
+pub use create_voter_weight_registrar::*;
+pub use expand_compact_escrow_history::*;
+pub use fund_distributor_period::*;
+pub use get_average_escrow_balance::*;
+pub use get_average_locker_balance::*;
+pub use get_average_share::*;
+pub use get_escrow_balance::*;
+pub use get_locker_balance::*;
+pub use get_share::*;
+pub use merge_locker_history_shard::*;
+pub use migrate_escrow_history::*;
+pub use reset_locker_history::*;
+pub use reset_locker_history_shard::*;
 pub use sync::*;
+pub use sync_all_eras::*;
+pub use sync_compact::*;
+pub use sync_init_if_needed::*;
+pub use sync_many::*;
+pub use sync_sharded::*;
+pub use update_voter_weight_record::*;
@@ -0,0 +1,60 @@
+//! Processor for [snapshots::reset_locker_history].
+
+use crate::*;
+use locked_voter::Locker;
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::reset_locker_history].
+#[derive(Accounts)]
+pub struct ResetLockerHistory<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [LockerHistory] to reset.
+    #[account(mut)]
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+}
+
+impl<'info> ResetLockerHistory<'info> {
+    fn reset_locker_history(&self) -> Result<()> {
+        let locker_history = &mut self.locker_history.load_mut()?;
+        reset_history(&self.locker, locker_history)
+    }
+}
+
+/// Resets a [LockerHistory] to the current parameters of its [Locker].
+pub(crate) fn reset_history(
+    locker: &Account<Locker>,
+    locker_history: &mut LockerHistory,
+) -> Result<()> {
+    assert_keys_eq!(locker_history.locker, *locker);
+    invariant!(
+        !locker_history.has_params(&locker.params),
+        LockerParamsUnchanged
+    );
+    invariant!(locker_history.resyncing == 0, ResyncInProgress);
+
+    // Periods that have already started keep the previous parameters.
+    let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+    let started_periods = unwrap_int!(calculate_started_periods(
+        &locker_history.geometry(),
+        locker_history.era,
+        now
+    ));
+    unwrap_int!(locker_history.materialize(started_periods));
+    unwrap_int!(locker_history.reset(
+        locker.params.max_stake_duration,
+        locker.params.max_stake_vote_multiplier
+    ));
+    Ok(())
+}
+
+pub fn handler(ctx: Context<ResetLockerHistory>) -> Result<()> {
+    ctx.accounts.reset_locker_history()
+}
+
+impl<'info> Validate<'info> for ResetLockerHistory<'info> {
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+//! Processor for [snapshots::reset_locker_history_shard].
+
+use crate::*;
+use locked_voter::Locker;
+
+/// Accounts for [snapshots::reset_locker_history_shard].
+#[derive(Accounts)]
+pub struct ResetLockerHistoryShard<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [LockerHistory] of the shard.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [LockerHistoryShard] to reset.
+    #[account(mut)]
+    pub shard: AccountLoader<'info, LockerHistoryShard>,
+}
+
+impl<'info> ResetLockerHistoryShard<'info> {
+    fn reset_locker_history_shard(&self) -> Result<()> {
+        let shard = &mut self.shard.load_mut()?;
+        reset_history(&self.locker, &mut shard.history)
+    }
+}
+
+pub fn handler(ctx: Context<ResetLockerHistoryShard>) -> Result<()> {
+    ctx.accounts.reset_locker_history_shard()
+}
+
+impl<'info> Validate<'info> for ResetLockerHistoryShard<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.shard.load()?.locker_history, self.locker_history);
+        Ok(())
+    }
+}
@@ -1,7 +1,6 @@
 //! Processor for [snapshots::sync].
 
 use crate::*;
-use ::u128::mul_div_u64;
 use locked_voter::{Escrow, Locker};
 use num_traits::ToPrimitive;
 
@@ -26,124 +25,152 @@ pub struct Sync<'info> {
 impl<'info> Sync<'info> {
     fn sync(&self) -> Result<()> {
         let locker_history = &mut self.locker_history.load_mut()?;
-        let escrow_history = &mut self.escrow_history.load_mut()?;
-
-        assert_keys_eq!(locker_history.locker, self.locker);
-        assert_keys_eq!(escrow_history.escrow, self.escrow);
-        invariant!(locker_history.era == escrow_history.era, EraMismatch);
-
-        let start_ts = unwrap_int!(calculate_era_start_ts(locker_history.era));
-        let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
-
-        // The voting power at max lockup.
-        // This is used as a multiplicand to determine the total voting power
-        // at a given time.
-        let power_if_max_lockup = unwrap_int!(self
-            .escrow
-            .amount
-            .checked_mul(self.locker.params.max_stake_vote_multiplier.into()));
-        let escrow_started_at = unwrap_int!(self.escrow.escrow_started_at.to_u64());
-        let escrow_ends_at = unwrap_int!(self.escrow.escrow_ends_at.to_u64());
-
-        // If the escrow never started, we should not be updating anything.
-        if escrow_started_at == 0 {
-            return Ok(());
-        }
-
-        // calculate every period
-        let mut period_start_ts = start_ts;
-        for period in 0..ERA_NUM_PERIODS {
-            if period > 0 {
-                // add the period each iteration
-                period_start_ts = unwrap_int!(period_start_ts.checked_add(PERIOD_SECONDS.into()));
-            }
-
-            // skip over periods that have already passed.
-            if now >= period_start_ts {
-                continue;
-            }
-
-            // The previous value of this period's ve balance.
-            // !WARNING!: not to be confused with the veBalance of the previous period.
-            let prev_period_ve_balance = escrow_history.ve_balances[period];
-
-            // The current value of this period's ve balance.
-            let ve_balance: u64 = unwrap_int!(calculate_voter_power_for_period(
-                power_if_max_lockup,
-                period_start_ts,
-                escrow_started_at,
-                escrow_ends_at,
-                self.locker.params.max_stake_duration,
-            ));
-
-            // skip zero ve balance
-            if ve_balance == 0 {
-                // prev ve balance should have been zero
-                invariant!(prev_period_ve_balance == 0);
-                continue;
-            }
-
-            locker_history.ve_balances[period] = unwrap_checked!({
-                locker_history.ve_balances[period]
-                    .checked_sub(prev_period_ve_balance)?
-                    .checked_add(ve_balance)
-            });
-            escrow_history.ve_balances[period] = ve_balance;
-
-            invariant!(ve_balance >= prev_period_ve_balance, EscrowBalanceDecreased);
-
-            // If the previous balance was zero, this is a newly tracked escrow.
-            // This voter should be recorded in the counts.
-            if prev_period_ve_balance == 0 && ve_balance != 0 {
-                locker_history.ve_counts[period] =
-                    unwrap_int!(locker_history.ve_counts[period].checked_add(1));
-            }
-        }
-
-        Ok(())
+        let escrow_history = &mut *self.escrow_history.load_mut()?;
+        sync_escrow_history(
+            &self.locker,
+            &self.escrow,
+            locker_history,
+            escrow_history,
+            0,
+        )
     }
 }
 
-/// Calculates voter power at the start of a period.
+/// Syncs an [Escrow] into its [EscrowHistory] or [CompactEscrowHistory] and a [LockerHistory].
 ///
-/// - `power_if_max_lockup`: Voting power if the user's lockup was at the maximum amount
-/// - `period_start_ts`: When the period started.
-/// - `escrow_started_at`: When the user's escrow started.
-/// - `escrow_ends_at`: When the user's escrow ends.
-/// - `max_stake_duration`: The maximum duration of a lockup. This is also the duration used to compute the `power_if_max_lockup`.
-fn calculate_voter_power_for_period(
-    power_if_max_lockup: u64,
-    period_start_ts: u64,
-    escrow_started_at: u64,
-    escrow_ends_at: u64,
-    max_stake_duration: u64,
-) -> Option<u64> {
-    // invalid `now` argument, should never happen.
-    if period_start_ts == 0 {
-        return None;
-    }
+/// `shard` is the location of the [LockerHistory], as stored in [EscrowHistory::shard].
+pub(crate) fn sync_escrow_history<H: EscrowBalances>(
+    locker: &Account<Locker>,
+    escrow: &Account<Escrow>,
+    locker_history: &mut LockerHistory,
+    escrow_history: &mut H,
+    shard: u16,
+) -> Result<()> {
+    assert_keys_eq!(locker_history.locker, *locker);
+    assert_keys_eq!(escrow_history.escrow(), *escrow);
+    invariant!(locker_history.era == escrow_history.era(), EraMismatch);
+    // Materialized balances must not mix periods of different lengths.
+    invariant!(
+        escrow_history.geometry() == locker_history.geometry(),
+        GeometryMismatch
+    );
+
+    let escrow_started_at = unwrap_int!(escrow.escrow_started_at.to_u64());
+    let escrow_ends_at = unwrap_int!(escrow.escrow_ends_at.to_u64());
+
+    // If the escrow never started, we should not be updating anything.
     if escrow_started_at == 0 {
-        return Some(0);
+        return Ok(());
     }
-    // Lockup had zero power before the start time.
-    // at the end time, lockup also has zero power.
-    if period_start_ts < escrow_started_at || period_start_ts >= escrow_ends_at {
-        return Some(0);
+
+    // An escrow may only move to another shard if it has no lockup tracked in its current one.
+    if escrow_history.lockup().power != 0 {
+        invariant!(escrow_history.shard() == shard, ShardMismatch);
     }
 
-    // multiply the max lockup power by the fraction of the max stake duration
-    let seconds_until_lockup_expiry = escrow_ends_at.checked_sub(period_start_ts)?.to_u64()?;
-    // elapsed seconds, clamped to the maximum duration
-    let relevant_seconds_until_lockup_expiry = seconds_until_lockup_expiry.min(max_stake_duration);
+    let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+
+    // Periods that have already started are immutable, so write
+    // them out before changing the lockup of the escrow.
+    let started_periods = unwrap_int!(calculate_started_periods(
+        &locker_history.geometry(),
+        locker_history.era,
+        now
+    ));
+    unwrap_int!(locker_history.materialize(started_periods));
+    let resynced = escrow_history.params_version() != locker_history.params_version;
+    let (prev_synced_period, first_period) = if resynced {
+        // The history was reset since the escrow was last synced.
+        if locker_history
+            .resync_escrow_history(escrow_history, started_periods)
+            .is_none()
+        {
+            return Err(history_full_or_overflow(escrow_history));
+        }
+        (
+            unwrap_int!(locker_history.materialized_periods.to_usize()),
+            unwrap_int!(locker_history.resync_period.to_usize()),
+        )
+    } else {
+        let prev_synced_period = unwrap_int!(escrow_history.materialized_periods().to_usize());
+        unwrap_int!(escrow_history.materialize(started_periods));
+        (prev_synced_period, started_periods)
+    };
+
+    // The era is over: there is nothing left to update.
+    if started_periods >= ERA_NUM_PERIODS {
+        return Ok(());
+    }
 
-    // multiply the max lockup power by the fraction of the max stake duration
-    let power = mul_div_u64(
-        power_if_max_lockup,
-        relevant_seconds_until_lockup_expiry,
-        max_stake_duration,
-    )?;
+    // Future balances must be computed with the same parameters as the rest of the history.
+    invariant!(
+        locker_history.has_params(&locker.params),
+        LockerParamsChanged
+    );
+
+    // The voting power at max lockup.
+    // This is used as a multiplicand to determine the total voting power
+    // at a given time.
+    let power_if_max_lockup = unwrap_int!(calculate_power_if_max_lockup(
+        escrow.amount,
+        locker.params.max_stake_vote_multiplier
+    ));
+    let lockup = Lockup {
+        power: power_if_max_lockup,
+        started_at: escrow_started_at,
+        ends_at: escrow_ends_at,
+    };
+
+    // Replace the previous lockup in all periods that have not started.
+    // This may decrease their balances.
+    let prev_lockup = escrow_history.lockup();
+    unwrap_int!(locker_history.remove_lockup(&prev_lockup, prev_synced_period));
+    unwrap_int!(locker_history.add_lockup(&lockup));
+    if escrow_history
+        .record_lockup(&lockup, locker_history)
+        .is_none()
+    {
+        return Err(history_full_or_overflow(escrow_history));
+    }
+    escrow_history.set_shard(shard);
+
+    let era = locker_history.era;
+    let geometry = locker_history.geometry();
+    let prev_counted_until_period = unwrap_int!(prev_lockup.calculate_end_period(&geometry, era));
+    let counted_until_period = unwrap_int!(lockup.calculate_end_period(&geometry, era));
+    let last_period = prev_counted_until_period
+        .max(counted_until_period)
+        .saturating_sub(1)
+        .max(first_period);
+    emit!(EscrowSynced {
+        locker: locker.key(),
+        escrow: escrow.key(),
+        era,
+        shard,
+        resynced,
+        first_period: unwrap_int!(first_period.to_u16()),
+        last_period: unwrap_int!(last_period.to_u16()),
+        prev_power: prev_lockup.power,
+        prev_ends_at: prev_lockup.ends_at,
+        power: lockup.power,
+        started_at: lockup.started_at,
+        ends_at: lockup.ends_at,
+        max_stake_duration: locker_history.max_stake_duration,
+        prev_counted_until_period: unwrap_int!(prev_counted_until_period.to_u16()),
+        counted_until_period: unwrap_int!(counted_until_period.to_u16()),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
 
-    Some(power)
+/// The error of an [EscrowLockups] update that failed.
+fn history_full_or_overflow<H: EscrowLockups>(escrow_history: &H) -> anchor_lang::error::Error {
+    if escrow_history.is_full() {
+        error!(ErrorCode::CompactEscrowHistoryFull)
+    } else {
+        error!(VipersError::IntegerOverflow)
+    }
 }
 
 pub fn handler(ctx: Context<Sync>) -> Result<()> {
@@ -157,63 +184,45 @@ impl<'info> Validate<'info> for Sync<'info> {
     }
 }
 
-#[cfg(test)]
-#[allow(clippy::unwrap_used)]
-mod test {
-    use super::*;
-    use locked_voter::LockerParams;
-    use proptest::prelude::*;
-
-    fn run_test_identical_to_locked_voter(
-        escrow_amount: u64,
-        max_stake_vote_multiplier: u8,
-        period_start_ts: i64,
-        escrow_started_at: i64,
-        escrow_ends_at: i64,
-        max_stake_duration: u64,
-    ) {
-        let reference_locker = LockerParams {
-            max_stake_duration,
-            max_stake_vote_multiplier,
-            ..Default::default()
-        };
-        let reference_escrow = Escrow {
-            amount: escrow_amount,
-            escrow_started_at,
-            escrow_ends_at,
-            ..Default::default()
-        };
-        let power_if_max_lockup = escrow_amount * (max_stake_vote_multiplier as u64);
-        let reference_power =
-            reference_locker.calculate_voter_power(&reference_escrow, period_start_ts);
-        let new_power = calculate_voter_power_for_period(
-            power_if_max_lockup,
-            period_start_ts as u64,
-            escrow_started_at as u64,
-            escrow_ends_at as u64,
-            max_stake_duration,
-        );
-        assert_eq!(reference_power, new_power);
-    }
-
-    proptest! {
-        #[test]
-        fn test_identical_to_locked_voter(
-            escrow_amount in 0..=(u64::MAX >> 8),
-            max_stake_vote_multiplier: u8,
-            period_start_ts in 0..=i64::MAX,
-            escrow_started_at in 0..=i64::MAX,
-            escrow_ends_at in 0..=i64::MAX,
-            max_stake_duration: u64,
-        ) {
-            run_test_identical_to_locked_voter(
-                escrow_amount,
-                max_stake_vote_multiplier,
-                period_start_ts,
-                escrow_started_at,
-                escrow_ends_at,
-                max_stake_duration
-            );
-        }
-    }
+#[event]
+/// Event called when an [Escrow] is synced into its [EscrowHistory].
+///
+/// Only balances of periods between `first_period` and `last_period` (inclusive) may have changed.
+/// Within that range, the change in each period can be computed from the previous and new lockups.
+pub struct EscrowSynced {
+    /// The [Locker].
+    #[index]
+    pub locker: Pubkey,
+    /// The [Escrow].
+    #[index]
+    pub escrow: Pubkey,
+    /// The era of the histories.
+    pub era: u16,
+    /// Where the lockup is tracked, as stored in [EscrowHistory::shard].
+    pub shard: u16,
+    /// True if the history was reset since the escrow was last synced. The previous lockup
+    /// is then the recorded lockup converted to the current parameters.
+    pub resynced: bool,
+    /// First period whose balance may have changed.
+    pub first_period: u16,
+    /// Last period whose balance may have changed.
+    pub last_period: u16,
+    /// Voting power at max lockup of the previous lockup.
+    pub prev_power: u64,
+    /// When the previous lockup ended.
+    pub prev_ends_at: u64,
+    /// Voting power at max lockup of the new lockup.
+    pub power: u64,
+    /// When the new lockup started.
+    pub started_at: u64,
+    /// When the new lockup ends.
+    pub ends_at: u64,
+    /// The [LockerHistory::max_stake_duration] that balances are computed with.
+    pub max_stake_duration: u64,
+    /// The previous lockup was counted in [LockerHistory::ve_counts] before this period.
+    pub prev_counted_until_period: u16,
+    /// The new lockup is counted in [LockerHistory::ve_counts] before this period.
+    pub counted_until_period: u16,
+    /// Timestamp for the event.
+    pub timestamp: i64,
 }
@@ -0,0 +1,79 @@
+//! Processor for [snapshots::sync_all_eras].
+
+use crate::*;
+use locked_voter::{Escrow, Locker};
+
+/// Accounts for [snapshots::sync_all_eras].
+///
+/// The remaining accounts are `(LockerHistory, EscrowHistory)` pairs,
+/// one for each era to sync. Both accounts of each pair must be writable.
+#[derive(Accounts)]
+pub struct SyncAllEras<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [Escrow].
+    pub escrow: Account<'info, Escrow>,
+}
+
+impl<'info> SyncAllEras<'info> {
+    fn sync_all_eras(&self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let pairs = remaining_accounts.chunks_exact(2);
+        invariant!(
+            !remaining_accounts.is_empty() && pairs.remainder().is_empty(),
+            "expected (LockerHistory, EscrowHistory) pairs"
+        );
+        for pair in pairs {
+            let locker_history_loader: AccountLoader<LockerHistory> =
+                AccountLoader::try_from(&pair[0])?;
+            let escrow_history_loader: AccountLoader<EscrowHistory> =
+                AccountLoader::try_from(&pair[1])?;
+            let locker_history = &mut locker_history_loader.load_mut()?;
+            let escrow_history = &mut *escrow_history_loader.load_mut()?;
+
+            let locker_history_key = Pubkey::create_program_address(
+                &[
+                    b"LockerHistory".as_ref(),
+                    self.locker.key().as_ref(),
+                    locker_history.era.to_le_bytes().as_ref(),
+                    &[locker_history.bump],
+                ],
+                &crate::ID,
+            )
+            .map_err(|_| error!(ErrorCode::InvalidHistoryAddress))?;
+            assert_keys_eq!(locker_history_key, pair[0], InvalidHistoryAddress);
+
+            let escrow_history_key = Pubkey::create_program_address(
+                &[
+                    b"EscrowHistory".as_ref(),
+                    self.escrow.key().as_ref(),
+                    escrow_history.era.to_le_bytes().as_ref(),
+                    &[escrow_history.bump],
+                ],
+                &crate::ID,
+            )
+            .map_err(|_| error!(ErrorCode::InvalidHistoryAddress))?;
+            assert_keys_eq!(escrow_history_key, pair[1], InvalidHistoryAddress);
+
+            sync_escrow_history(
+                &self.locker,
+                &self.escrow,
+                locker_history,
+                escrow_history,
+                0,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, SyncAllEras<'info>>) -> Result<()> {
+    ctx.accounts.sync_all_eras(ctx.remaining_accounts)
+}
+
+impl<'info> Validate<'info> for SyncAllEras<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.locker, self.escrow.locker);
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+//! Processor for [snapshots::sync_compact].
+
+use crate::*;
+use locked_voter::{Escrow, Locker};
+
+/// Accounts for [snapshots::sync_compact].
+#[derive(Accounts)]
+pub struct SyncCompact<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [Escrow].
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [LockerHistory] to sync.
+    #[account(mut)]
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [CompactEscrowHistory] to sync.
+    #[account(mut)]
+    pub compact_escrow_history: AccountLoader<'info, CompactEscrowHistory>,
+}
+
+impl<'info> SyncCompact<'info> {
+    fn sync_compact(&self) -> Result<()> {
+        let locker_history = &mut self.locker_history.load_mut()?;
+        let compact_escrow_history = &mut *self.compact_escrow_history.load_mut()?;
+        sync_escrow_history(
+            &self.locker,
+            &self.escrow,
+            locker_history,
+            compact_escrow_history,
+            0,
+        )
+    }
+}
+
+pub fn handler(ctx: Context<SyncCompact>) -> Result<()> {
+    ctx.accounts.sync_compact()
+}
+
+impl<'info> Validate<'info> for SyncCompact<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.locker, self.escrow.locker);
+        Ok(())
+    }
+}
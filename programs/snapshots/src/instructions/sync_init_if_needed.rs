@@ -0,0 +1,156 @@
+//! Processor for [snapshots::sync_init_if_needed].
+
+use crate::*;
+use locked_voter::{Escrow, Locker};
+
+/// Accounts for [snapshots::sync_init_if_needed].
+#[derive(Accounts)]
+#[instruction(era: u16)]
+pub struct SyncInitIfNeeded<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [SnapshotConfig] of the [Locker], which may not exist.
+    /// CHECK: Only read if it has been created. See [load_era_geometry].
+    #[account(
+        seeds = [
+            b"SnapshotConfig".as_ref(),
+            locker.key().as_ref()
+        ],
+        bump
+    )]
+    pub snapshot_config: UncheckedAccount<'info>,
+
+    /// The [Escrow].
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [LockerHistory] to sync, created if it does not exist.
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"LockerHistory".as_ref(),
+            locker.key().as_ref(),
+            era.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + LockerHistory::LEN,
+        payer = payer
+    )]
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [CompactEscrowHistory] of the era, which must not exist if the [EscrowHistory] is created.
+    /// CHECK: Only checked to be empty.
+    #[account(
+        seeds = [
+            b"CompactEscrowHistory".as_ref(),
+            escrow.key().as_ref(),
+            era.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub compact_escrow_history: UncheckedAccount<'info>,
+
+    /// The [EscrowHistory] to sync, created if it does not exist.
+    #[account(
+        init_if_needed,
+        seeds = [
+            b"EscrowHistory".as_ref(),
+            escrow.key().as_ref(),
+            era.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + EscrowHistory::LEN,
+        payer = payer
+    )]
+    pub escrow_history: AccountLoader<'info, EscrowHistory>,
+
+    /// Payer of any history that is created.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// [System] program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SyncInitIfNeeded<'info> {
+    fn sync_init_if_needed(
+        &mut self,
+        locker_history_bump: u8,
+        escrow_history_bump: u8,
+        era: u16,
+        retention_seconds: u64,
+    ) -> Result<()> {
+        let geometry = load_era_geometry(&self.snapshot_config)?;
+        if is_uninitialized(&self.locker_history)? {
+            assert_retention_not_expired(&geometry, era, retention_seconds)?;
+            init_locker_history(
+                &mut *self.locker_history.load_init()?,
+                &self.locker,
+                &geometry,
+                era,
+                locker_history_bump,
+                retention_seconds,
+                self.payer.key(),
+            );
+            emit!(LockerHistoryCreated {
+                locker: self.locker.key(),
+                locker_history: self.locker_history.key(),
+                era,
+                payer: self.payer.key(),
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+        if is_uninitialized(&self.escrow_history)? {
+            assert_history_absent(&self.compact_escrow_history)?;
+            assert_retention_not_expired(&geometry, era, retention_seconds)?;
+            init_escrow_history(
+                &mut *self.escrow_history.load_init()?,
+                self.escrow.key(),
+                &geometry,
+                era,
+                escrow_history_bump,
+                retention_seconds,
+                self.payer.key(),
+            );
+            emit!(EscrowHistoryCreated {
+                escrow: self.escrow.key(),
+                escrow_history: self.escrow_history.key(),
+                era,
+                payer: self.payer.key(),
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        let locker_history = &mut self.locker_history.load_mut()?;
+        let escrow_history = &mut *self.escrow_history.load_mut()?;
+        sync_escrow_history(
+            &self.locker,
+            &self.escrow,
+            locker_history,
+            escrow_history,
+            0,
+        )
+    }
+}
+
+/// Returns true if the account was created by this instruction and has no discriminator yet.
+fn is_uninitialized<T: anchor_lang::ZeroCopy + Owner>(loader: &AccountLoader<T>) -> Result<bool> {
+    let data = loader.as_ref().try_borrow_data()?;
+    Ok(data[..8] == [0u8; 8])
+}
+
+pub fn handler(ctx: Context<SyncInitIfNeeded>, era: u16, retention_seconds: u64) -> Result<()> {
+    ctx.accounts.sync_init_if_needed(
+        *unwrap_int!(ctx.bumps.get("locker_history")),
+        *unwrap_int!(ctx.bumps.get("escrow_history")),
+        era,
+        retention_seconds,
+    )
+}
+
+impl<'info> Validate<'info> for SyncInitIfNeeded<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.locker, self.escrow.locker);
+        Ok(())
+    }
+}
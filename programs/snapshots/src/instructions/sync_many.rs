@@ -0,0 +1,125 @@
+//! Processor for [snapshots::sync_many].
+
+use crate::*;
+use anchor_lang::solana_program::program_error::ProgramError;
+use locked_voter::{Escrow, Locker};
+use num_traits::ToPrimitive;
+
+/// Accounts for [snapshots::sync_many].
+///
+/// The remaining accounts are `(Escrow, EscrowHistory)` pairs, one for each escrow to sync.
+/// Each [EscrowHistory] must be writable.
+#[derive(Accounts)]
+pub struct SyncMany<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [LockerHistory] to sync.
+    #[account(mut)]
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+}
+
+impl<'info> SyncMany<'info> {
+    fn sync_many(&self, remaining_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let pairs = remaining_accounts.chunks_exact(2);
+        invariant!(
+            !remaining_accounts.is_empty() && pairs.remainder().is_empty(),
+            "expected (Escrow, EscrowHistory) pairs"
+        );
+        let locker_history = &mut self.locker_history.load_mut()?;
+
+        // Every sync fails once the locker parameters have changed, so the batch fails instead.
+        let now = unwrap_int!(Clock::get()?.unix_timestamp.to_u64());
+        let started_periods = unwrap_int!(calculate_started_periods(
+            &locker_history.geometry(),
+            locker_history.era,
+            now
+        ));
+        invariant!(
+            started_periods >= ERA_NUM_PERIODS || locker_history.has_params(&self.locker.params),
+            LockerParamsChanged
+        );
+
+        for pair in pairs {
+            let (escrow, escrow_history_loader) =
+                match self.load_escrow(locker_history, &pair[0], &pair[1]) {
+                    Ok(accounts) => accounts,
+                    Err(err) => {
+                        emit!(EscrowSyncSkipped {
+                            locker: self.locker.key(),
+                            escrow: pair[0].key(),
+                            escrow_history: pair[1].key(),
+                            error: u64::from(ProgramError::from(err)),
+                            timestamp: Clock::get()?.unix_timestamp,
+                        });
+                        continue;
+                    }
+                };
+            // The checks above leave only arithmetic errors, which abort the whole batch
+            // since they may occur after the histories have been modified.
+            sync_escrow_history(
+                &self.locker,
+                &escrow,
+                locker_history,
+                &mut *escrow_history_loader.load_mut()?,
+                0,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Loads an [Escrow] and its [EscrowHistory], checking everything
+    /// that would make [sync_escrow_history] fail.
+    fn load_escrow(
+        &self,
+        locker_history: &LockerHistory,
+        escrow_info: &AccountInfo<'info>,
+        escrow_history_info: &AccountInfo<'info>,
+    ) -> Result<(Account<'info, Escrow>, AccountLoader<'info, EscrowHistory>)> {
+        let escrow: Account<Escrow> = Account::try_from(escrow_info)?;
+        assert_keys_eq!(escrow.locker, self.locker, LockerEscrowMismatch);
+
+        let escrow_history_loader: AccountLoader<EscrowHistory> =
+            AccountLoader::try_from(escrow_history_info)?;
+        let escrow_history = escrow_history_loader.load_mut()?;
+        assert_keys_eq!(escrow_history.escrow, escrow);
+        invariant!(locker_history.era == escrow_history.era, EraMismatch);
+        invariant!(
+            locker_history.geometry() == escrow_history.geometry(),
+            GeometryMismatch
+        );
+        if escrow_history.power != 0 {
+            invariant!(escrow_history.shard == 0, ShardMismatch);
+        }
+        drop(escrow_history);
+
+        Ok((escrow, escrow_history_loader))
+    }
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, SyncMany<'info>>) -> Result<()> {
+    ctx.accounts.sync_many(ctx.remaining_accounts)
+}
+
+impl<'info> Validate<'info> for SyncMany<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.locker_history.load()?.locker, self.locker);
+        Ok(())
+    }
+}
+
+#[event]
+/// Event called in [snapshots::sync_many] for each escrow that could not be synced.
+pub struct EscrowSyncSkipped {
+    /// The [Locker].
+    #[index]
+    pub locker: Pubkey,
+    /// The [Escrow].
+    pub escrow: Pubkey,
+    /// The [EscrowHistory].
+    pub escrow_history: Pubkey,
+    /// The error that the sync would have failed with, as a [ProgramError] code.
+    pub error: u64,
+    /// Timestamp for the event.
+    pub timestamp: i64,
+}
@@ -0,0 +1,60 @@
+//! Processor for [snapshots::sync_sharded].
+
+use crate::*;
+use locked_voter::{Escrow, Locker};
+
+/// Accounts for [snapshots::sync_sharded].
+#[derive(Accounts)]
+pub struct SyncSharded<'info> {
+    /// The [Locker].
+    pub locker: Account<'info, Locker>,
+
+    /// The [Escrow].
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [LockerHistory] of the shard.
+    pub locker_history: AccountLoader<'info, LockerHistory>,
+
+    /// The [LockerHistoryShard] to sync.
+    #[account(mut)]
+    pub shard: AccountLoader<'info, LockerHistoryShard>,
+
+    /// The [EscrowHistory] to sync.
+    #[account(mut)]
+    pub escrow_history: AccountLoader<'info, EscrowHistory>,
+}
+
+impl<'info> SyncSharded<'info> {
+    fn sync_sharded(&self) -> Result<()> {
+        let shard = &mut self.shard.load_mut()?;
+        let escrow_history = &mut *self.escrow_history.load_mut()?;
+
+        // Escrows without a lockup are placed into the shard keyed by their address.
+        if escrow_history.power == 0 {
+            let locker_history = self.locker_history.load()?;
+            let index = unwrap_int!(locker_history.calculate_shard_index(self.escrow.key()));
+            invariant!(shard.index == index, ShardMismatch);
+        }
+
+        let location = unwrap_int!(shard.index.checked_add(1));
+        sync_escrow_history(
+            &self.locker,
+            &self.escrow,
+            &mut shard.history,
+            escrow_history,
+            location,
+        )
+    }
+}
+
+pub fn handler(ctx: Context<SyncSharded>) -> Result<()> {
+    ctx.accounts.sync_sharded()
+}
+
+impl<'info> Validate<'info> for SyncSharded<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.locker, self.escrow.locker);
+        assert_keys_eq!(self.shard.load()?.locker_history, self.locker_history);
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+//! Processor for [snapshots::update_voter_weight_record].
+
+use crate::*;
+use locked_voter::Escrow;
+
+/// Accounts for [snapshots::update_voter_weight_record].
+#[derive(Accounts)]
+pub struct UpdateVoterWeightRecord<'info> {
+    /// The [VoterWeightRegistrar].
+    pub registrar: Account<'info, VoterWeightRegistrar>,
+
+    /// The [ProposalSnapshot] of the proposal being voted on.
+    pub proposal_snapshot: Account<'info, ProposalSnapshot>,
+
+    /// The [Escrow] of the [VoterWeightRecord::governing_token_owner].
+    pub escrow: Account<'info, Escrow>,
+
+    /// The [EscrowHistory] or [CompactEscrowHistory] of the era of the [ProposalSnapshot].
+    /// CHECK: Checked to be an [EscrowHistory] or [CompactEscrowHistory] when loaded.
+    pub escrow_history: UncheckedAccount<'info>,
+
+    /// The [VoterWeightRecord] to update.
+    #[account(mut)]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+}
+
+impl<'info> UpdateVoterWeightRecord<'info> {
+    fn update_voter_weight_record(&mut self) -> Result<()> {
+        let escrow_history_loader = AnyEscrowHistory::try_from(&self.escrow_history)?;
+        let escrow_history = escrow_history_loader.load()?;
+        invariant!(
+            escrow_history.era() == self.proposal_snapshot.era,
+            EraMismatch
+        );
+        invariant!(
+            escrow_history.geometry() == self.registrar.geometry(),
+            GeometryMismatch
+        );
+        let voter_weight =
+            unwrap_int!(escrow_history.calculate_ve_balance(self.proposal_snapshot.period));
+
+        let voter_weight_record = &mut self.voter_weight_record;
+        voter_weight_record.voter_weight = voter_weight;
+        voter_weight_record.voter_weight_expiry = Some(Clock::get()?.slot);
+        voter_weight_record.weight_action = Some(VoterWeightAction::CastVote);
+        voter_weight_record.weight_action_target = Some(self.proposal_snapshot.proposal);
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<UpdateVoterWeightRecord>) -> Result<()> {
+    ctx.accounts.update_voter_weight_record()
+}
+
+impl<'info> Validate<'info> for UpdateVoterWeightRecord<'info> {
+    fn validate(&self) -> Result<()> {
+        assert_keys_eq!(self.proposal_snapshot.registrar, self.registrar);
+        assert_keys_eq!(self.voter_weight_record.realm, self.registrar.realm);
+        assert_keys_eq!(
+            self.voter_weight_record.governing_token_mint,
+            self.registrar.governing_token_mint
+        );
+        assert_keys_eq!(self.escrow.locker, self.registrar.locker);
+        assert_keys_eq!(
+            self.escrow.owner,
+            self.voter_weight_record.governing_token_owner
+        );
+        assert_keys_eq!(
+            AnyEscrowHistory::try_from(&self.escrow_history)?
+                .load()?
+                .escrow(),
+            self.escrow
+        );
+        Ok(())
+    }
+}
@@ -11,6 +11,14 @@
 //!
 //! veToken balances are recorded for every `period`. A period is recorded for every 3 days.
 //!
+//! By default, era 0 starts at the beginning of 2022 and a period lasts 3 days. A locker's governance
+//! may call `create_snapshot_config` to choose a different period length and start for era 0,
+//! such as weekly periods. The [SnapshotConfig] cannot be changed once created, and it must be created
+//! before any history of the locker, since each [LockerHistory] and [Distributor] records it at creation.
+//! It cannot be created once the locker has a [LockerHistory] of the current era. Histories created
+//! earlier keep the default geometry, so syncs, views, claims, airdrops and governance snapshots
+//! fail with `GeometryMismatch` unless every account they combine has the same geometry.
+//!
 //! There are two accounts that are used to compute historical balances:
 //!
 //! - [LockerHistory], which stores the total number of veTokens for each period, and
@@ -18,10 +26,119 @@
 //!
 //! Any time someone refreshes and/or modifies their vote escrow, they should refresh their [EscrowHistory] accounts.
 //!
+//! Syncing an escrow records its lockup and updates the slope and bias of the [LockerHistory],
+//! so its cost does not depend on the number of periods the lockup spans. The balances of a period
+//! are written once the period has started, either by a sync or by `checkpoint_locker_history`.
+//! Periods that have started are never modified; a sync that lowers an escrow's balance, such as
+//! after a change to the locker parameters, only decreases the totals of future periods.
+//!
+//! Since only periods that have started are written, `ve_balances` is incomplete for later periods. Clients
+//! read them with `EscrowHistory::calculate_ve_balance`, or with `calculateEscrowVeBalance` and
+//! `calculateLockerVeBalance` in the TypeScript SDK, which compute them from the recorded lockups.
+//!
+//! Each history records the locker parameters its balances are computed with. If they change,
+//! syncs fail until anyone calls `reset_locker_history`, which discards the balances of future
+//! periods. Escrows are then moved to the new parameters as they are synced; until every active
+//! lockup has been resynced, the history is marked as `resyncing` and its later periods are not final.
+//!
+//! A lockup may span several eras; `sync_all_eras` syncs the histories of each of them at once.
+//!
+//! Cranks may sync many escrows into a [LockerHistory] at once with `sync_many`. Escrows that cannot
+//! be synced, such as those tracked in a shard, are skipped and reported with an `EscrowSyncSkipped` event.
+//!
+//! `sync_init_if_needed` creates any missing history of the era before syncing it.
+//! To onboard the existing escrows of a locker, `create_escrow_histories` creates the
+//! [EscrowHistory]s of many escrows and consecutive eras at once.
+//!
+//! To sync many escrows in parallel, a [LockerHistory] may be split into [LockerHistoryShard]s.
+//! Escrows are synced into the shard keyed by their address, and the shard totals are added
+//! to the [LockerHistory] with `merge_locker_history_shard`. A shard is only merged into the periods
+//! that had not started when it was created, so creating one does not change which periods are final.
+//!
+//! Each history records the account that paid for it and a retention window chosen at creation.
+//! A history may not be created once the retention window of its era has already passed.
+//! Once the era has ended and the retention window has passed, the payer may close the history
+//! to reclaim its rent. A [LockerHistoryShard] may be closed once it has also been fully merged.
+//!
+//! # Compact escrow histories
+//!
+//! An [EscrowHistory] stores the balance of every period of the era, even though they are
+//! determined by the lockups of the escrow. A [CompactEscrowHistory] instead stores a checkpoint each
+//! time the lockup changes, and computes the balance of a period from them with `get_balance`,
+//! for about 22% of the rent. An escrow has at most one of the two histories per era.
+//! It is created with `create_compact_escrow_history` and synced with `sync_compact`, which only
+//! syncs into the [LockerHistory], not a shard. The payer of an [EscrowHistory] may replace it with
+//! a compact history with `migrate_escrow_history` if its balances follow a single lockup.
+//!
+//! A compact history holds 8 checkpoints. Once they are used, a sync that changes the lockup fails
+//! until anyone calls `expand_compact_escrow_history`, which replaces it with an [EscrowHistory].
+//! Fee distribution, governance, cumulative balances and the views read either history of an escrow,
+//! and `snapshots-cli` exports and airdrops the balances of both.
+//!
+//! # Fee distribution
+//!
+//! A [Distributor] pays out tokens to the escrows of a locker. Anyone may fund a [DistributorPeriod]
+//! before it elapses. Once it has elapsed, each escrow may `claim` its share of the rewards,
+//! which is its [EscrowHistory] balance divided by the [LockerHistory] total for that period.
+//! Claims close `claim_seconds` after the period ends, as chosen by the authority that created the
+//! distributor. The authority may then `clawback_distributor_period` the rewards that were not claimed,
+//! such as the shares of escrows that never synced or all rewards of a period without any lockups.
+//!
+//! # Airdrops
+//!
+//! A [SnapshotAirdrop] distributes tokens with a Merkle tree whose claims are weighted by the
+//! balances of a final period, such as one generated by `snapshots-cli airdrop`. The airdrop is
+//! tied to a locker, era and period, and records the [LockerHistory] total of that period so
+//! claimants can check it against the tree. Escrow owners `claim_snapshot_airdrop` with a proof
+//! until the claim deadline, after which the authority may `clawback_snapshot_airdrop` the rest.
+//! Claims are recorded in [SnapshotAirdropBitmap]s, which anyone may create.
+//!
+//! # Tribeca Govern
+//!
+//! `locked_voter` computes voting power when a vote is cast, so tokens locked while a proposal is
+//! being voted on count towards it. Once the period containing a [govern::Proposal]'s creation is final,
+//! anyone may `create_govern_proposal_snapshot` to record the [LockerHistory] total of that period and
+//! the proposal's quorum, which the governor sets as an absolute number of votes. `create_vote_weight_receipt`
+//! then records each escrow's [EscrowHistory] balance at that period in a [VoteWeightReceipt], which a
+//! governor can use to tally votes instead of live balances.
+//!
+//! # SPL Governance
+//!
+//! The program implements the SPL Governance voter-weight addin, so a Realms DAO can weigh votes by the
+//! [EscrowHistory] balances of a locker. The realm authority links the realm to a locker with
+//! `create_voter_weight_registrar`. While a proposal is still a draft, anyone may `create_proposal_snapshot`
+//! to record the period in which the proposal was drafted. `update_voter_weight_record` then sets a voter's
+//! [VoterWeightRecord] to their balance at that period, for casting a vote on that proposal. Since started
+//! periods are never modified, locking tokens after the proposal was drafted does not change the outcome of the vote.
+//!
+//! # Reading balances from other programs
+//!
+//! `get_escrow_balance`, `get_locker_balance` and `get_share` look up the balances of a period, selected
+//! by era and period or by Unix timestamp, and write them with `set_return_data`. With the `cpi`
+//! feature, the helpers in `views::cpi` invoke these instructions and decode their results.
+//!
+//! A balance at a single period can be gamed by locking just before the period starts.
+//! `get_average_escrow_balance`, `get_average_locker_balance` and `get_average_share` instead return
+//! the time-weighted average balances over a range of periods, which may span several eras. They take
+//! the histories of the first era as named accounts and those of each following era as remaining accounts.
+//!
+//! A [LockerHistory] or [EscrowHistory] may be given a [CumulativeBalances] account with
+//! `create_locker_cumulative_balances` or `create_escrow_cumulative_balances`. It records the running sum of the
+//! balances of each period once the period can no longer change, that is once it is final for the [LockerHistory]
+//! of the era. Anyone may extend the sums with `checkpoint_locker_cumulative_balances` and
+//! `checkpoint_escrow_cumulative_balances`. Other programs and off-chain readers can then get the total balance
+//! over any range of periods of an era from two entries with `CumulativeBalances::calculate_sum`, instead of
+//! summing every period. The average views read these sums for the histories whose [CumulativeBalances] are
+//! appended to their remaining accounts, whenever the sums cover the periods of the range in that era.
+//!
 //! # Program Addresses
 //!
 //! - **[snapshots]:** [StakeSSzfxn391k3LvdKbZP5WVwWd6AsY1DNiXHjQfK](https://anchor.so/programs/StakeSSzfxn391k3LvdKbZP5WVwWd6AsY1DNiXHjQfK)
 //!
+//! The [LockerHistory] and [EscrowHistory] layouts of this version differ from those of the original program.
+//! Existing histories cannot be resized in place, so this version must be deployed as a new program rather
+//! than as an upgrade of a deployment that already holds histories.
+//!
 //! # License
 //!
 //! The [snapshots] program is licensed under the Affero General Public License version 3.
@@ -33,13 +150,19 @@
 use anchor_lang::prelude::*;
 use vipers::prelude::*;
 
+mod history;
 mod instructions;
+mod spl_governance;
 mod state;
+pub mod views;
 
 pub use snapshots_math::*;
 pub use state::*;
+pub use views::*;
 
+use history::*;
 use instructions::*;
+use spl_governance::*;
 
 declare_id!("StakeSSzfxn391k3LvdKbZP5WVwWd6AsY1DNiXHjQfK");
 
@@ -50,14 +173,56 @@ pub mod snapshots {
 
     /// Creates a [EscrowHistory].
     #[access_control(ctx.accounts.validate())]
-    pub fn create_escrow_history(ctx: Context<CreateEscrowHistory>, era: u16) -> Result<()> {
-        create_escrow_history::handler(ctx, era)
+    pub fn create_escrow_history(
+        ctx: Context<CreateEscrowHistory>,
+        era: u16,
+        retention_seconds: u64,
+    ) -> Result<()> {
+        create_escrow_history::handler(ctx, era, retention_seconds)
+    }
+
+    /// Creates the [EscrowHistory]s of consecutive eras for each [locked_voter::Escrow] passed in the
+    /// remaining accounts, skipping those that already exist.
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_escrow_histories<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateEscrowHistories<'info>>,
+        first_era: u16,
+        num_eras: u8,
+        retention_seconds: u64,
+    ) -> Result<()> {
+        create_escrow_histories::handler(ctx, first_era, num_eras, retention_seconds)
+    }
+
+    /// Creates the [SnapshotConfig] of a [locked_voter::Locker].
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_snapshot_config(
+        ctx: Context<CreateSnapshotConfig>,
+        period_seconds: u64,
+        origin_ts: u64,
+    ) -> Result<()> {
+        create_snapshot_config::handler(ctx, period_seconds, origin_ts)
     }
 
     /// Creates a [LockerHistory].
     #[access_control(ctx.accounts.validate())]
-    pub fn create_locker_history(ctx: Context<CreateLockerHistory>, era: u16) -> Result<()> {
-        create_locker_history::handler(ctx, era)
+    pub fn create_locker_history(
+        ctx: Context<CreateLockerHistory>,
+        era: u16,
+        retention_seconds: u64,
+    ) -> Result<()> {
+        create_locker_history::handler(ctx, era, retention_seconds)
+    }
+
+    /// Closes an [EscrowHistory] once its era and retention window have passed, refunding its payer.
+    #[access_control(ctx.accounts.validate())]
+    pub fn close_escrow_history(ctx: Context<CloseEscrowHistory>) -> Result<()> {
+        close_escrow_history::handler(ctx)
+    }
+
+    /// Closes a [LockerHistory] once its era and retention window have passed, refunding its payer.
+    #[access_control(ctx.accounts.validate())]
+    pub fn close_locker_history(ctx: Context<CloseLockerHistory>) -> Result<()> {
+        close_locker_history::handler(ctx)
     }
 
     /// Synchronize an [locked_voter::Escrow] with the [LockerHistory]/[EscrowHistory].
@@ -65,6 +230,317 @@ pub mod snapshots {
     pub fn sync(ctx: Context<Sync>) -> Result<()> {
         sync::handler(ctx)
     }
+
+    /// Synchronize an [locked_voter::Escrow] with the [LockerHistory]/[EscrowHistory] of an era,
+    /// creating either history if it does not yet exist.
+    #[access_control(ctx.accounts.validate())]
+    pub fn sync_init_if_needed(
+        ctx: Context<SyncInitIfNeeded>,
+        era: u16,
+        retention_seconds: u64,
+    ) -> Result<()> {
+        sync_init_if_needed::handler(ctx, era, retention_seconds)
+    }
+
+    /// Synchronize an [locked_voter::Escrow] with the [LockerHistory]/[EscrowHistory] of every era
+    /// passed in the remaining accounts.
+    #[access_control(ctx.accounts.validate())]
+    pub fn sync_all_eras<'info>(ctx: Context<'_, '_, '_, 'info, SyncAllEras<'info>>) -> Result<()> {
+        sync_all_eras::handler(ctx)
+    }
+
+    /// Synchronize the [EscrowHistory]s of the [locked_voter::Escrow]s passed in the remaining accounts
+    /// with a [LockerHistory], skipping those that cannot be synced.
+    #[access_control(ctx.accounts.validate())]
+    pub fn sync_many<'info>(ctx: Context<'_, '_, '_, 'info, SyncMany<'info>>) -> Result<()> {
+        sync_many::handler(ctx)
+    }
+
+    /// Creates a [CompactEscrowHistory].
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_compact_escrow_history(
+        ctx: Context<CreateCompactEscrowHistory>,
+        era: u16,
+        retention_seconds: u64,
+    ) -> Result<()> {
+        create_compact_escrow_history::handler(ctx, era, retention_seconds)
+    }
+
+    /// Synchronize an [locked_voter::Escrow] with the [LockerHistory]/[CompactEscrowHistory].
+    #[access_control(ctx.accounts.validate())]
+    pub fn sync_compact(ctx: Context<SyncCompact>) -> Result<()> {
+        sync_compact::handler(ctx)
+    }
+
+    /// Replaces an [EscrowHistory] with a [CompactEscrowHistory], refunding the difference in rent.
+    #[access_control(ctx.accounts.validate())]
+    pub fn migrate_escrow_history(ctx: Context<MigrateEscrowHistory>, era: u16) -> Result<()> {
+        migrate_escrow_history::handler(ctx, era)
+    }
+
+    /// Replaces a [CompactEscrowHistory] with an [EscrowHistory], which can record any number of lockups.
+    #[access_control(ctx.accounts.validate())]
+    pub fn expand_compact_escrow_history(
+        ctx: Context<ExpandCompactEscrowHistory>,
+        era: u16,
+    ) -> Result<()> {
+        expand_compact_escrow_history::handler(ctx, era)
+    }
+
+    /// Closes a [CompactEscrowHistory] once its era and retention window have passed, refunding its payer.
+    #[access_control(ctx.accounts.validate())]
+    pub fn close_compact_escrow_history(ctx: Context<CloseCompactEscrowHistory>) -> Result<()> {
+        close_compact_escrow_history::handler(ctx)
+    }
+
+    /// Materializes the [LockerHistory] totals of all periods that have started.
+    #[access_control(ctx.accounts.validate())]
+    pub fn checkpoint_locker_history(ctx: Context<CheckpointLockerHistory>) -> Result<()> {
+        checkpoint_locker_history::handler(ctx)
+    }
+
+    /// Creates the [CumulativeBalances] of a [LockerHistory].
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_locker_cumulative_balances(
+        ctx: Context<CreateLockerCumulativeBalances>,
+    ) -> Result<()> {
+        create_locker_cumulative_balances::handler(ctx)
+    }
+
+    /// Creates the [CumulativeBalances] of an [EscrowHistory] or [CompactEscrowHistory].
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_escrow_cumulative_balances(
+        ctx: Context<CreateEscrowCumulativeBalances>,
+    ) -> Result<()> {
+        create_escrow_cumulative_balances::handler(ctx)
+    }
+
+    /// Records the sums of all final periods of a [LockerHistory] in its [CumulativeBalances].
+    #[access_control(ctx.accounts.validate())]
+    pub fn checkpoint_locker_cumulative_balances(
+        ctx: Context<CheckpointLockerCumulativeBalances>,
+    ) -> Result<()> {
+        checkpoint_locker_cumulative_balances::handler(ctx)
+    }
+
+    /// Records the sums of all materialized or final periods of an [EscrowHistory] or
+    /// [CompactEscrowHistory] in its [CumulativeBalances].
+    #[access_control(ctx.accounts.validate())]
+    pub fn checkpoint_escrow_cumulative_balances(
+        ctx: Context<CheckpointEscrowCumulativeBalances>,
+    ) -> Result<()> {
+        checkpoint_escrow_cumulative_balances::handler(ctx)
+    }
+
+    /// Creates a [LockerHistoryShard].
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_locker_history_shard(
+        ctx: Context<CreateLockerHistoryShard>,
+        index: u16,
+    ) -> Result<()> {
+        create_locker_history_shard::handler(ctx, index)
+    }
+
+    /// Closes a [LockerHistoryShard] once its era and retention window have passed
+    /// and it has been fully merged, refunding its payer.
+    #[access_control(ctx.accounts.validate())]
+    pub fn close_locker_history_shard(ctx: Context<CloseLockerHistoryShard>) -> Result<()> {
+        close_locker_history_shard::handler(ctx)
+    }
+
+    /// Synchronize an [locked_voter::Escrow] with a [LockerHistoryShard]/[EscrowHistory].
+    #[access_control(ctx.accounts.validate())]
+    pub fn sync_sharded(ctx: Context<SyncSharded>) -> Result<()> {
+        sync_sharded::handler(ctx)
+    }
+
+    /// Adds the balances of all started periods of a [LockerHistoryShard] to its [LockerHistory].
+    #[access_control(ctx.accounts.validate())]
+    pub fn merge_locker_history_shard(ctx: Context<MergeLockerHistoryShard>) -> Result<()> {
+        merge_locker_history_shard::handler(ctx)
+    }
+
+    /// Resets a [LockerHistory] after the [locked_voter::LockerParams] have changed.
+    #[access_control(ctx.accounts.validate())]
+    pub fn reset_locker_history(ctx: Context<ResetLockerHistory>) -> Result<()> {
+        reset_locker_history::handler(ctx)
+    }
+
+    /// Resets a [LockerHistoryShard] after the [locked_voter::LockerParams] have changed.
+    #[access_control(ctx.accounts.validate())]
+    pub fn reset_locker_history_shard(ctx: Context<ResetLockerHistoryShard>) -> Result<()> {
+        reset_locker_history_shard::handler(ctx)
+    }
+
+    /// Returns the veToken balance of an [EscrowHistory] or [CompactEscrowHistory] at a period.
+    #[access_control(ctx.accounts.validate())]
+    pub fn get_escrow_balance(ctx: Context<GetEscrowBalance>, query: PeriodQuery) -> Result<()> {
+        get_escrow_balance::handler(ctx, query)
+    }
+
+    /// Returns the total veToken balance of a [LockerHistory] at a period as a [LockerBalance].
+    #[access_control(ctx.accounts.validate())]
+    pub fn get_locker_balance(ctx: Context<GetLockerBalance>, query: PeriodQuery) -> Result<()> {
+        get_locker_balance::handler(ctx, query)
+    }
+
+    /// Returns the share of an [locked_voter::Escrow] in its locker's total at a period as an [EscrowShare].
+    #[access_control(ctx.accounts.validate())]
+    pub fn get_share(ctx: Context<GetShare>, query: PeriodQuery) -> Result<()> {
+        get_share::handler(ctx, query)
+    }
+
+    /// Returns the time-weighted average veToken balance of an [EscrowHistory] or
+    /// [CompactEscrowHistory] over a range of periods.
+    #[access_control(ctx.accounts.validate())]
+    pub fn get_average_escrow_balance<'info>(
+        ctx: Context<'_, '_, '_, 'info, GetAverageEscrowBalance<'info>>,
+        query: PeriodRangeQuery,
+    ) -> Result<()> {
+        get_average_escrow_balance::handler(ctx, query)
+    }
+
+    /// Returns the time-weighted average total veToken balance of a [LockerHistory] over a range
+    /// of periods as a [LockerBalance].
+    #[access_control(ctx.accounts.validate())]
+    pub fn get_average_locker_balance<'info>(
+        ctx: Context<'_, '_, '_, 'info, GetAverageLockerBalance<'info>>,
+        query: PeriodRangeQuery,
+    ) -> Result<()> {
+        get_average_locker_balance::handler(ctx, query)
+    }
+
+    /// Returns the time-weighted average balances of an [locked_voter::Escrow] and its locker over
+    /// a range of periods as an [EscrowShare].
+    #[access_control(ctx.accounts.validate())]
+    pub fn get_average_share<'info>(
+        ctx: Context<'_, '_, '_, 'info, GetAverageShare<'info>>,
+        query: PeriodRangeQuery,
+    ) -> Result<()> {
+        get_average_share::handler(ctx, query)
+    }
+
+    /// Creates a [Distributor] whose periods may be claimed for `claim_seconds` after they elapse.
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_distributor(ctx: Context<CreateDistributor>, claim_seconds: u64) -> Result<()> {
+        create_distributor::handler(ctx, claim_seconds)
+    }
+
+    /// Creates a [DistributorPeriod].
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_distributor_period(
+        ctx: Context<CreateDistributorPeriod>,
+        era: u16,
+        period: u8,
+    ) -> Result<()> {
+        create_distributor_period::handler(ctx, era, period)
+    }
+
+    /// Adds rewards to a [DistributorPeriod] that has not yet elapsed.
+    #[access_control(ctx.accounts.validate())]
+    pub fn fund_distributor_period(ctx: Context<FundDistributorPeriod>, amount: u64) -> Result<()> {
+        fund_distributor_period::handler(ctx, amount)
+    }
+
+    /// Claims the share of the rewards of an elapsed [DistributorPeriod] for an [locked_voter::Escrow].
+    #[access_control(ctx.accounts.validate())]
+    pub fn claim(ctx: Context<Claim>) -> Result<()> {
+        claim::handler(ctx)
+    }
+
+    /// Sends the unclaimed rewards of a [DistributorPeriod] to the [Distributor] authority
+    /// after the claim deadline.
+    #[access_control(ctx.accounts.validate())]
+    pub fn clawback_distributor_period(ctx: Context<ClawbackDistributorPeriod>) -> Result<()> {
+        clawback_distributor_period::handler(ctx)
+    }
+
+    /// Creates a [SnapshotAirdrop] of the tokens in the Merkle tree with the given root,
+    /// and deposits `max_total_claim` tokens into it.
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_snapshot_airdrop(
+        ctx: Context<CreateSnapshotAirdrop>,
+        era: u16,
+        period: u8,
+        root: [u8; 32],
+        max_total_claim: u64,
+        max_num_nodes: u64,
+        claim_deadline: i64,
+    ) -> Result<()> {
+        create_snapshot_airdrop::handler(
+            ctx,
+            era,
+            period,
+            root,
+            max_total_claim,
+            max_num_nodes,
+            claim_deadline,
+        )
+    }
+
+    /// Creates a [SnapshotAirdropBitmap].
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_snapshot_airdrop_bitmap(
+        ctx: Context<CreateSnapshotAirdropBitmap>,
+        page: u32,
+    ) -> Result<()> {
+        create_snapshot_airdrop_bitmap::handler(ctx, page)
+    }
+
+    /// Claims tokens from a [SnapshotAirdrop] with a Merkle proof.
+    #[access_control(ctx.accounts.validate())]
+    pub fn claim_snapshot_airdrop(
+        ctx: Context<ClaimSnapshotAirdrop>,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        claim_snapshot_airdrop::handler(ctx, index, amount, proof)
+    }
+
+    /// Sends the unclaimed tokens of a [SnapshotAirdrop] to its authority after the claim deadline.
+    #[access_control(ctx.accounts.validate())]
+    pub fn clawback_snapshot_airdrop(ctx: Context<ClawbackSnapshotAirdrop>) -> Result<()> {
+        clawback_snapshot_airdrop::handler(ctx)
+    }
+
+    /// Creates the [VoterWeightRegistrar] of an SPL Governance realm.
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_voter_weight_registrar(ctx: Context<CreateVoterWeightRegistrar>) -> Result<()> {
+        create_voter_weight_registrar::handler(ctx)
+    }
+
+    /// Records the period in which a draft SPL Governance proposal was created, at which votes on it are weighed.
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_proposal_snapshot(ctx: Context<CreateProposalSnapshot>) -> Result<()> {
+        create_proposal_snapshot::handler(ctx)
+    }
+
+    /// Creates the [VoterWeightRecord] of a governing token owner.
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_voter_weight_record(ctx: Context<CreateVoterWeightRecord>) -> Result<()> {
+        create_voter_weight_record::handler(ctx)
+    }
+
+    /// Sets the weight of a [VoterWeightRecord] to the escrow balance at a [ProposalSnapshot].
+    #[access_control(ctx.accounts.validate())]
+    pub fn update_voter_weight_record(ctx: Context<UpdateVoterWeightRecord>) -> Result<()> {
+        update_voter_weight_record::handler(ctx)
+    }
+
+    /// Records the voting power at the period a [govern::Proposal] was created.
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_govern_proposal_snapshot(
+        ctx: Context<CreateGovernProposalSnapshot>,
+    ) -> Result<()> {
+        create_govern_proposal_snapshot::handler(ctx)
+    }
+
+    /// Records the voting power of an [locked_voter::Escrow] at a [GovernProposalSnapshot].
+    #[access_control(ctx.accounts.validate())]
+    pub fn create_vote_weight_receipt(ctx: Context<CreateVoteWeightReceipt>) -> Result<()> {
+        create_vote_weight_receipt::handler(ctx)
+    }
 }
 
 /// Errors.
@@ -74,6 +550,66 @@ pub enum ErrorCode {
     LockerEscrowMismatch,
     #[msg("Era mismatch.")]
     EraMismatch,
+    /// Deprecated: syncs no longer fail when an escrow's balances decrease.
+    /// The variant is kept so that later errors keep their codes.
     #[msg("Escrow balances cannot decrease.")]
     EscrowBalanceDecreased,
+    #[msg("Escrow is tracked in a different shard.")]
+    ShardMismatch,
+    #[msg("Period has not elapsed.")]
+    PeriodNotElapsed,
+    #[msg("Period has already elapsed.")]
+    PeriodElapsed,
+    #[msg("Period totals have not been finalized.")]
+    PeriodNotFinal,
+    #[msg("History is still within its retention window.")]
+    HistoryRetained,
+    #[msg("History account does not match its expected address.")]
+    InvalidHistoryAddress,
+    #[msg("Locker parameters have changed since the history was last reset.")]
+    LockerParamsChanged,
+    #[msg("Locker parameters have not changed.")]
+    LockerParamsUnchanged,
+    #[msg("Escrows are still being resynced.")]
+    ResyncInProgress,
+    #[msg("Return data is missing or invalid.")]
+    InvalidReturnData,
+    #[msg("Airdrop claim deadline has passed.")]
+    AirdropClaimDeadlinePassed,
+    #[msg("Airdrop claim deadline has not passed.")]
+    AirdropClaimDeadlineNotPassed,
+    #[msg("Airdrop has already been claimed.")]
+    AirdropAlreadyClaimed,
+    #[msg("Invalid airdrop Merkle proof.")]
+    AirdropInvalidProof,
+    #[msg("Airdrop claims exceed the maximum.")]
+    AirdropExceededMaxClaim,
+    #[msg("Invalid period length or era origin.")]
+    InvalidEraGeometry,
+    #[msg("Period range ends before it starts.")]
+    InvalidPeriodRange,
+    #[msg("Account is not a valid SPL Governance account.")]
+    InvalidGovernanceAccount,
+    #[msg("Proposal is no longer a draft.")]
+    ProposalNotDraft,
+    #[msg("Escrow already has another history for the era.")]
+    EscrowHistoryExists,
+    #[msg("Compact escrow history has no checkpoints left.")]
+    CompactEscrowHistoryFull,
+    #[msg("Escrow history balances cannot be compacted.")]
+    EscrowHistoryNotCompactable,
+    #[msg("Shard has periods that have not been merged.")]
+    ShardNotMerged,
+    #[msg("Locker already has a history of the current era.")]
+    LockerHistoryExists,
+    #[msg("Accounts have different period lengths or era origins.")]
+    GeometryMismatch,
+    #[msg("Airdrop claim index is out of range.")]
+    AirdropIndexOutOfRange,
+    #[msg("Distributor claim deadline has passed.")]
+    DistributorClaimDeadlinePassed,
+    #[msg("Distributor claim deadline has not passed.")]
+    DistributorClaimDeadlineNotPassed,
+    #[msg("History would already be past its retention window.")]
+    HistoryRetentionExpired,
 }
@@ -0,0 +1,286 @@
+//! Decoding of the SPL Governance accounts read by the voter-weight addin.
+//!
+//! Only the leading fields of each account are decoded. Realms and governances have the same
+//! layout in every version of the SPL Governance program, while proposals are decoded up to
+//! `draft_at` according to their account type.
+
+use crate::*;
+
+/// [GovernanceAccountType](https://github.com/solana-labs/solana-program-library/blob/master/governance/program/src/state/enums.rs)s of realms.
+const REALM_ACCOUNT_TYPES: &[u8] = &[1, 16];
+/// Account types of governances of programs, mints, tokens and other accounts.
+const GOVERNANCE_ACCOUNT_TYPES: &[u8] = &[3, 4, 9, 10, 18, 19, 20, 21];
+/// Account type of `ProposalV1`.
+const PROPOSAL_V1_ACCOUNT_TYPE: u8 = 5;
+/// Account type of `ProposalV2`.
+const PROPOSAL_V2_ACCOUNT_TYPE: u8 = 14;
+
+/// `ProposalState::Draft`.
+const PROPOSAL_STATE_DRAFT: u8 = 0;
+/// `ProposalState::SigningOff`.
+const PROPOSAL_STATE_SIGNING_OFF: u8 = 1;
+
+/// Leading fields of a realm, up to its authority.
+#[derive(AnchorDeserialize)]
+pub(crate) struct RealmHeader {
+    _account_type: u8,
+    /// Mint of the community token.
+    pub community_mint: Pubkey,
+    _use_community_voter_weight_addin: bool,
+    _use_max_community_voter_weight_addin: bool,
+    _reserved: [u8; 6],
+    _min_community_weight_to_create_governance: u64,
+    _community_mint_max_vote_weight_source: (u8, u64),
+    _council_mint: Option<Pubkey>,
+    _reserved2: [u8; 6],
+    _voting_proposal_count: u16,
+    /// Account that may change the configuration of the realm.
+    pub authority: Option<Pubkey>,
+}
+
+/// Leading fields of a governance.
+#[derive(AnchorDeserialize)]
+pub(crate) struct GovernanceHeader {
+    _account_type: u8,
+    /// The realm of the governance.
+    pub realm: Pubkey,
+}
+
+/// Fields of a `ProposalV1`, up to `draft_at`.
+#[derive(AnchorDeserialize)]
+struct ProposalV1Header {
+    _account_type: u8,
+    governance: Pubkey,
+    governing_token_mint: Pubkey,
+    state: u8,
+    _token_owner_record: Pubkey,
+    _signatories_count: u8,
+    _signatories_signed_off_count: u8,
+    _yes_votes_count: u64,
+    _no_votes_count: u64,
+    _instructions_executed_count: u16,
+    _instructions_count: u16,
+    _instructions_next_index: u16,
+    draft_at: i64,
+}
+
+/// `VoteType` of a `ProposalV2`, in its v3 layout.
+#[derive(AnchorDeserialize)]
+enum VoteType {
+    SingleChoice,
+    MultiChoice {
+        _choice_type: u8,
+        _min_voter_options: u8,
+        _max_voter_options: u8,
+        _max_winning_options: u8,
+    },
+}
+
+/// `ProposalOption` of a `ProposalV2`.
+#[derive(AnchorDeserialize)]
+struct ProposalOption {
+    _label: String,
+    _vote_weight: u64,
+    _vote_result: u8,
+    _transactions_executed_count: u16,
+    _transactions_count: u16,
+    _transactions_next_index: u16,
+}
+
+/// Fields of a `ProposalV2`, up to `draft_at`.
+#[derive(AnchorDeserialize)]
+struct ProposalV2Header {
+    _account_type: u8,
+    governance: Pubkey,
+    governing_token_mint: Pubkey,
+    state: u8,
+    _token_owner_record: Pubkey,
+    _signatories_count: u8,
+    _signatories_signed_off_count: u8,
+    _vote_type: VoteType,
+    _options: Vec<ProposalOption>,
+    _deny_vote_weight: Option<u64>,
+    /// `reserved1: u8` in v3, which is always zero and so decodes as [None].
+    _veto_vote_weight: Option<u64>,
+    _abstain_vote_weight: Option<u64>,
+    _start_voting_at: Option<i64>,
+    draft_at: i64,
+}
+
+/// Fields of a proposal read by the voter-weight addin.
+pub(crate) struct ProposalHeader {
+    /// The governance of the proposal.
+    pub governance: Pubkey,
+    /// Mint of the token used to vote on the proposal.
+    pub governing_token_mint: Pubkey,
+    /// The `ProposalState`.
+    pub state: u8,
+    /// When the proposal was created.
+    pub draft_at: i64,
+}
+
+impl From<ProposalV1Header> for ProposalHeader {
+    fn from(proposal: ProposalV1Header) -> Self {
+        Self {
+            governance: proposal.governance,
+            governing_token_mint: proposal.governing_token_mint,
+            state: proposal.state,
+            draft_at: proposal.draft_at,
+        }
+    }
+}
+
+impl From<ProposalV2Header> for ProposalHeader {
+    fn from(proposal: ProposalV2Header) -> Self {
+        Self {
+            governance: proposal.governance,
+            governing_token_mint: proposal.governing_token_mint,
+            state: proposal.state,
+            draft_at: proposal.draft_at,
+        }
+    }
+}
+
+impl ProposalHeader {
+    /// Returns true if the proposal has not been signed off yet, so voting has not started.
+    pub fn is_draft(&self) -> bool {
+        self.state == PROPOSAL_STATE_DRAFT || self.state == PROPOSAL_STATE_SIGNING_OFF
+    }
+}
+
+/// Decodes the leading fields of an SPL Governance account of one of the given types.
+fn load_header<T: AnchorDeserialize>(
+    info: &AccountInfo,
+    governance_program: &Pubkey,
+    account_types: &[u8],
+) -> Result<T> {
+    assert_keys_eq!(*info.owner, *governance_program, InvalidGovernanceAccount);
+    let data = info.try_borrow_data()?;
+    invariant!(
+        data.first()
+            .map(|account_type| account_types.contains(account_type))
+            .unwrap_or(false),
+        InvalidGovernanceAccount
+    );
+    T::deserialize(&mut &data[..]).map_err(|_| error!(ErrorCode::InvalidGovernanceAccount))
+}
+
+/// Decodes a realm owned by `governance_program`.
+pub(crate) fn load_realm(info: &AccountInfo, governance_program: &Pubkey) -> Result<RealmHeader> {
+    load_header(info, governance_program, REALM_ACCOUNT_TYPES)
+}
+
+/// Decodes a governance owned by `governance_program`.
+pub(crate) fn load_governance(
+    info: &AccountInfo,
+    governance_program: &Pubkey,
+) -> Result<GovernanceHeader> {
+    load_header(info, governance_program, GOVERNANCE_ACCOUNT_TYPES)
+}
+
+/// Decodes a proposal owned by `governance_program`.
+pub(crate) fn load_proposal(
+    info: &AccountInfo,
+    governance_program: &Pubkey,
+) -> Result<ProposalHeader> {
+    let account_type = info.try_borrow_data()?.first().copied();
+    if account_type == Some(PROPOSAL_V1_ACCOUNT_TYPE) {
+        load_header::<ProposalV1Header>(info, governance_program, &[PROPOSAL_V1_ACCOUNT_TYPE])
+            .map(Into::into)
+    } else {
+        load_header::<ProposalV2Header>(info, governance_program, &[PROPOSAL_V2_ACCOUNT_TYPE])
+            .map(Into::into)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_realm_header() {
+        let community_mint = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let mut data = vec![16];
+        data.extend_from_slice(community_mint.as_ref());
+        data.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+        data.extend_from_slice(&1_u64.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(&10_000_000_000_u64.to_le_bytes());
+        // council mint
+        data.push(1);
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(&[0; 6]);
+        data.extend_from_slice(&3_u16.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(authority.as_ref());
+        // name and trailing fields are ignored
+        data.extend_from_slice(&4_u32.to_le_bytes());
+        data.extend_from_slice(b"DAO!");
+
+        let realm = RealmHeader::deserialize(&mut &data[..]).unwrap();
+        assert_eq!(realm.community_mint, community_mint);
+        assert_eq!(realm.authority, Some(authority));
+    }
+
+    fn push_proposal_prefix(data: &mut Vec<u8>, account_type: u8, governance: &Pubkey) {
+        data.push(account_type);
+        data.extend_from_slice(governance.as_ref());
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.push(1);
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(&[2, 1]);
+    }
+
+    #[test]
+    fn test_proposal_v1_header() {
+        let governance = Pubkey::new_unique();
+        let mut data = vec![];
+        push_proposal_prefix(&mut data, PROPOSAL_V1_ACCOUNT_TYPE, &governance);
+        data.extend_from_slice(&[0; 8 + 8 + 2 + 2 + 2]);
+        data.extend_from_slice(&1_650_000_000_i64.to_le_bytes());
+        // signing off and trailing fields are ignored
+        data.extend_from_slice(&[1; 32]);
+
+        let proposal: ProposalHeader = ProposalV1Header::deserialize(&mut &data[..])
+            .unwrap()
+            .into();
+        assert_eq!(proposal.governance, governance);
+        assert_eq!(proposal.state, 1);
+        assert!(proposal.is_draft());
+        assert_eq!(proposal.draft_at, 1_650_000_000);
+    }
+
+    #[test]
+    fn test_proposal_v2_header() {
+        let governance = Pubkey::new_unique();
+        let mut data = vec![];
+        push_proposal_prefix(&mut data, PROPOSAL_V2_ACCOUNT_TYPE, &governance);
+        // vote type: MultiChoice
+        data.extend_from_slice(&[1, 0, 1, 2, 2]);
+        // options
+        data.extend_from_slice(&2_u32.to_le_bytes());
+        for label in [&b"Yes"[..], &b"Maybe"[..]] {
+            data.extend_from_slice(&(label.len() as u32).to_le_bytes());
+            data.extend_from_slice(label);
+            data.extend_from_slice(&5_u64.to_le_bytes());
+            data.push(0);
+            data.extend_from_slice(&[0; 6]);
+        }
+        // deny vote weight
+        data.push(1);
+        data.extend_from_slice(&7_u64.to_le_bytes());
+        // v3 reserved byte, abstain vote weight and start voting at
+        data.extend_from_slice(&[0, 0, 0]);
+        data.extend_from_slice(&1_650_000_000_i64.to_le_bytes());
+        data.extend_from_slice(&[1; 32]);
+
+        let proposal: ProposalHeader = ProposalV2Header::deserialize(&mut &data[..])
+            .unwrap()
+            .into();
+        assert_eq!(proposal.governance, governance);
+        assert!(proposal.is_draft());
+        assert_eq!(proposal.draft_at, 1_650_000_000);
+    }
+}
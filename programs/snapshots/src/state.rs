@@ -6,7 +6,8 @@ use crate::*;
 
 /// Stores the total number of veTokens in circulation for each period.
 ///
-/// The [LockerHistory] account stores 256 periods, each 3 days each.
+/// The [LockerHistory] account stores 256 periods, each 3 days each by default.
+/// The length of the periods can be changed with a [SnapshotConfig].
 /// For a 5-year [locked_voter::Locker], there will be at least 3 of these accounts existing
 /// at any given time, since the maximum lock period is 5 years.
 #[account(zero_copy)]
@@ -14,16 +15,72 @@ use crate::*;
 pub struct LockerHistory {
     /// The [locked_voter::Locker] being tracked.
     pub locker: Pubkey,
-    /// The era. Multiplying this by [ERA_NUM_PERIODS] * [PERIOD_SECONDS];
+    /// The era. Multiplying this by [ERA_NUM_PERIODS] * [LockerHistory::period_seconds];
     pub era: u16,
     /// Bump seed.
     pub bump: u8,
+    /// The [locked_voter::LockerParams::max_stake_vote_multiplier] that balances in this history are computed with.
+    pub max_stake_vote_multiplier: u8,
+    /// Number of times this history has been reset after a change to the [locked_voter::LockerParams].
+    pub params_version: u16,
+    /// `1` while escrows synced before the last [LockerHistory::params_version] change have
+    /// not all been resynced, `0` otherwise.
+    ///
+    /// Totals of periods starting at [LockerHistory::resync_period] are incomplete while this is set.
+    pub resyncing: u8,
     /// Padding for aligning the struct to an 8-byte boundary.
-    pub _padding: [u8; 5],
+    pub _padding: [u8; 1],
     /// The sum of all tracked historical vote escrow balances.
     pub ve_balances: [u64; 256],
-    /// Number of voters with non-zero balances at each epoch.
+    /// Number of voters with active lockups at each epoch.
     pub ve_counts: [u64; 256],
+
+    /// The [locked_voter::LockerParams::max_stake_duration] that balances in this history are computed with.
+    pub max_stake_duration: u64,
+    /// Sum of `power_if_max_lockup * end_period` of every lockup tracked by slope.
+    ///
+    /// This is placed on a 16-byte boundary so the layout is identical on all targets.
+    pub bias: u128,
+    /// Sum of the voting power at max lockup of every lockup tracked by slope.
+    pub slope: u64,
+    /// Voting power lost to lockups tracked by slope ending partway through a period.
+    pub end_offset: u64,
+    /// Number of lockups tracked by slope.
+    pub voters: u64,
+    /// Number of periods, starting from period 0, whose [LockerHistory::ve_balances] and
+    /// [LockerHistory::ve_counts] have been materialized.
+    ///
+    /// Until a period is materialized, its balance and count only contain the contributions
+    /// of lockups that are not tracked by slope.
+    pub materialized_periods: u16,
+    /// First period whose totals are computed with the current parameters.
+    pub resync_period: u16,
+    /// Padding for aligning the struct to an 8-byte boundary.
+    pub _padding2: [u8; 4],
+    /// Amount to subtract from the [LockerHistory::slope] at the start of each period.
+    pub slope_changes: [u64; 256],
+    /// Amount to subtract from the [LockerHistory::end_offset] at the start of each period.
+    pub end_offset_changes: [u64; 256],
+    /// Amount to subtract from the [LockerHistory::voters] at the start of each period.
+    pub voter_changes: [u32; 256],
+    /// Number of [LockerHistoryShard]s merged into each period.
+    pub merged_shards: [u16; 256],
+    /// Number of [LockerHistoryShard]s created for this history.
+    pub num_shards: u16,
+    /// Padding for aligning the struct to a 4-byte boundary.
+    pub _padding3: [u8; 2],
+    /// Number of lockups that still need to be resynced after the history was last reset.
+    pub pending_resyncs: u32,
+    /// Number of seconds after the end of the era before this history may be closed.
+    pub retention_seconds: u64,
+    /// Account that paid for this history, which receives its rent when it is closed.
+    pub payer: Pubkey,
+    /// [SnapshotConfig::period_seconds] of the locker when this history was created.
+    ///
+    /// `0` stands for the [EraGeometry::DEFAULT].
+    pub period_seconds: u64,
+    /// [SnapshotConfig::origin_ts] of the locker when this history was created.
+    pub origin_ts: u64,
 }
 
 impl Default for LockerHistory {
@@ -32,16 +89,137 @@ impl Default for LockerHistory {
             locker: Default::default(),
             era: Default::default(),
             bump: Default::default(),
+            max_stake_vote_multiplier: Default::default(),
+            params_version: Default::default(),
+            resyncing: Default::default(),
             _padding: Default::default(),
             ve_balances: [0; ERA_NUM_PERIODS],
             ve_counts: [0; ERA_NUM_PERIODS],
+            max_stake_duration: Default::default(),
+            bias: Default::default(),
+            slope: Default::default(),
+            end_offset: Default::default(),
+            voters: Default::default(),
+            materialized_periods: Default::default(),
+            resync_period: Default::default(),
+            _padding2: Default::default(),
+            slope_changes: [0; ERA_NUM_PERIODS],
+            end_offset_changes: [0; ERA_NUM_PERIODS],
+            voter_changes: [0; ERA_NUM_PERIODS],
+            merged_shards: [0; ERA_NUM_PERIODS],
+            num_shards: Default::default(),
+            _padding3: Default::default(),
+            pending_resyncs: Default::default(),
+            retention_seconds: Default::default(),
+            payer: Default::default(),
+            period_seconds: Default::default(),
+            origin_ts: Default::default(),
         }
     }
 }
 
 impl LockerHistory {
     /// Number of bytes in a serialized [LockerHistory],
-    pub const LEN: usize = PUBKEY_BYTES + 2 + 1 + 5 + 8 * 256 + 8 * 256;
+    pub const LEN: usize = PUBKEY_BYTES
+        + 2
+        + 1
+        + 1
+        + 2
+        + 1
+        + 1
+        + 8 * 256
+        + 8 * 256
+        + 8
+        + 16
+        + 8
+        + 8
+        + 8
+        + 2
+        + 2
+        + 4
+        + 8 * 256
+        + 8 * 256
+        + 4 * 256
+        + 2 * 256
+        + 2
+        + 2
+        + 4
+        + 8
+        + PUBKEY_BYTES
+        + 8
+        + 8;
+
+    /// The [EraGeometry] of the periods of this history.
+    pub fn geometry(&self) -> EraGeometry {
+        stored_geometry(self.period_seconds, self.origin_ts)
+    }
+
+    /// Index of the [LockerHistoryShard] that an [locked_voter::Escrow] should be synced into.
+    pub fn calculate_shard_index(&self, escrow: Pubkey) -> Option<u16> {
+        let key = escrow.to_bytes();
+        u16::from_le_bytes([key[0], key[1]]).checked_rem(self.num_shards)
+    }
+
+    /// Returns true if balances in this history are computed with the given parameters.
+    pub fn has_params(&self, params: &locked_voter::LockerParams) -> bool {
+        self.max_stake_duration == params.max_stake_duration
+            && self.max_stake_vote_multiplier == params.max_stake_vote_multiplier
+    }
+
+    /// Returns true if the balance of the period has been materialized,
+    /// every [LockerHistoryShard] has been merged into it, and it is not being resynced.
+    pub fn is_period_final(&self, period: u8) -> bool {
+        usize::from(period) < usize::from(self.materialized_periods)
+            && self.merged_shards[usize::from(period)] == self.num_shards
+            && !self.is_period_resyncing(period)
+    }
+
+    /// Number of periods, starting from period 0, that are final.
+    ///
+    /// Once final, a period stays final, so this never decreases.
+    pub fn calculate_final_periods(&self) -> usize {
+        (0..=u8::MAX)
+            .take_while(|&period| self.is_period_final(period))
+            .count()
+    }
+
+    /// Returns true if the total of the period may still change because
+    /// lockups are being resynced after a change to the [locked_voter::LockerParams].
+    pub fn is_period_resyncing(&self, period: u8) -> bool {
+        self.resyncing != 0 && u16::from(period) >= self.resync_period
+    }
+}
+
+/// Accumulates the balances of a subset of the escrows of a [LockerHistory].
+///
+/// Syncing an escrow into a shard does not write to the [LockerHistory], so escrows
+/// in different shards can be synced in parallel. Shard balances are added to the
+/// [LockerHistory] with `merge_locker_history_shard` once their periods have started.
+///
+/// A shard is only merged into the periods that had not started when it was created,
+/// so creating a shard does not change which periods of the [LockerHistory] are final.
+#[account(zero_copy)]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LockerHistoryShard {
+    /// The [LockerHistory] this shard accumulates balances for.
+    pub locker_history: Pubkey,
+    /// Index of the shard.
+    pub index: u16,
+    /// Number of periods, starting from period 0, that have been merged into the [LockerHistory].
+    ///
+    /// This starts at the number of periods that had started when the shard was created.
+    pub merged_periods: u16,
+    /// Bump seed.
+    pub bump: u8,
+    /// Padding for aligning the struct to a 16-byte boundary.
+    pub _padding: [u8; 11],
+    /// Balances and counts of the escrows synced into this shard.
+    pub history: LockerHistory,
+}
+
+impl LockerHistoryShard {
+    /// Number of bytes in a serialized [LockerHistoryShard],
+    pub const LEN: usize = PUBKEY_BYTES + 2 + 2 + 1 + 11 + LockerHistory::LEN;
 }
 
 /// Stores the total veToken balance of an [locked_voter::Escrow]
@@ -62,6 +240,41 @@ pub struct EscrowHistory {
     pub _padding: [u8; 5],
     /// All tracked historical vote escrow balances for this [locked_voter::Escrow].
     pub ve_balances: [u64; 256],
+
+    /// Voting power at max lockup of the [locked_voter::Escrow] when it was last synced.
+    pub power: u64,
+    /// [locked_voter::Escrow::escrow_started_at] when the escrow was last synced.
+    pub started_at: u64,
+    /// [locked_voter::Escrow::escrow_ends_at] when the escrow was last synced.
+    pub ends_at: u64,
+    /// Number of periods, starting from period 0, whose [EscrowHistory::ve_balances] have been materialized.
+    ///
+    /// Balances of later periods are computed from the recorded lockup.
+    pub materialized_periods: u16,
+    /// Where the lockup is tracked: `0` for the [LockerHistory], or `n` for the [LockerHistoryShard] with index `n - 1`.
+    pub shard: u16,
+    /// [LockerHistory::params_version] of the history the lockup is tracked in when it was last synced.
+    pub params_version: u16,
+    /// [LockerHistory::max_stake_vote_multiplier] when the escrow was last synced.
+    pub max_stake_vote_multiplier: u8,
+    /// Padding for aligning the struct to an 8-byte boundary.
+    pub _padding2: [u8; 1],
+    /// Number of seconds after the end of the era before this history may be closed.
+    pub retention_seconds: u64,
+    /// Account that paid for this history, which receives its rent when it is closed.
+    pub payer: Pubkey,
+    /// [LockerHistory::max_stake_duration] when the escrow was last synced.
+    pub max_stake_duration: u64,
+    /// [SnapshotConfig::period_seconds] of the locker when this history was created,
+    /// then [LockerHistory::period_seconds] when the escrow was last synced.
+    ///
+    /// `0` stands for the [EraGeometry::DEFAULT].
+    pub period_seconds: u64,
+    /// [SnapshotConfig::origin_ts] of the locker when this history was created,
+    /// then [LockerHistory::origin_ts] when the escrow was last synced.
+    pub origin_ts: u64,
+    /// Padding for aligning the struct to a 16-byte boundary.
+    pub _padding3: [u8; 8],
 }
 
 impl Default for EscrowHistory {
@@ -72,18 +285,653 @@ impl Default for EscrowHistory {
             bump: Default::default(),
             _padding: Default::default(),
             ve_balances: [0; ERA_NUM_PERIODS],
+            power: Default::default(),
+            started_at: Default::default(),
+            ends_at: Default::default(),
+            materialized_periods: Default::default(),
+            shard: Default::default(),
+            params_version: Default::default(),
+            max_stake_vote_multiplier: Default::default(),
+            _padding2: Default::default(),
+            retention_seconds: Default::default(),
+            payer: Default::default(),
+            max_stake_duration: Default::default(),
+            period_seconds: Default::default(),
+            origin_ts: Default::default(),
+            _padding3: Default::default(),
         }
     }
 }
 
 impl EscrowHistory {
     /// Number of bytes in a serialized [LockerHistory],
-    pub const LEN: usize = PUBKEY_BYTES + 2 + 1 + 5 + 8 * 256;
+    pub const LEN: usize = PUBKEY_BYTES
+        + 2
+        + 1
+        + 5
+        + 8 * 256
+        + 8
+        + 8
+        + 8
+        + 2
+        + 2
+        + 2
+        + 1
+        + 1
+        + 8
+        + PUBKEY_BYTES
+        + 8
+        + 8
+        + 8
+        + 8;
+
+    /// The [EraGeometry] of the periods of this history.
+    pub fn geometry(&self) -> EraGeometry {
+        stored_geometry(self.period_seconds, self.origin_ts)
+    }
+}
+
+/// Running sums of the balances of a [LockerHistory], [EscrowHistory] or [CompactEscrowHistory],
+/// so that the total balance over any range of periods can be read with two lookups.
+///
+/// Sums only cover periods whose balances can no longer change: final periods of a
+/// [LockerHistory], and periods of an escrow history that are materialized or final in the
+/// [LockerHistory] of the era. They are extended by the permissionless
+/// `checkpoint_*_cumulative_balances` instructions.
+#[account(zero_copy)]
+#[derive(Debug, PartialEq, Eq)]
+pub struct CumulativeBalances {
+    /// The [LockerHistory], [EscrowHistory] or [CompactEscrowHistory] being summed.
+    pub history: Pubkey,
+    /// The era of the history.
+    pub era: u16,
+    /// Bump seed.
+    pub bump: u8,
+    /// Padding for aligning the struct to a 2-byte boundary.
+    pub _padding: [u8; 1],
+    /// Number of periods, starting from period 0, whose sums have been recorded.
+    pub num_periods: u16,
+    /// Padding for aligning the struct to a 16-byte boundary.
+    pub _padding2: [u8; 10],
+    /// `ve_balance_sums[p]` is the sum of the balances of periods `0..=p`.
+    ///
+    /// This is placed on a 16-byte boundary so the layout is identical on all targets.
+    pub ve_balance_sums: [u128; 256],
+}
+
+impl Default for CumulativeBalances {
+    fn default() -> Self {
+        Self {
+            history: Default::default(),
+            era: Default::default(),
+            bump: Default::default(),
+            _padding: Default::default(),
+            num_periods: Default::default(),
+            _padding2: Default::default(),
+            ve_balance_sums: [0; ERA_NUM_PERIODS],
+        }
+    }
+}
+
+impl CumulativeBalances {
+    /// Number of bytes in a serialized [CumulativeBalances].
+    pub const LEN: usize = PUBKEY_BYTES + 2 + 1 + 1 + 2 + 10 + 16 * 256;
+
+    /// Records the sums of the periods from [CumulativeBalances::num_periods] up to `num_periods`,
+    /// reading the balance of each period with `ve_balance`.
+    pub(crate) fn extend(
+        &mut self,
+        num_periods: usize,
+        ve_balance: impl Fn(usize) -> Option<u64>,
+    ) -> Option<()> {
+        let start = usize::from(self.num_periods);
+        if start >= num_periods {
+            return Some(());
+        }
+        let mut sum = self.calculate_sum_before(start)?;
+        for (period, ve_balance_sum) in
+            (start..num_periods).zip(self.ve_balance_sums.get_mut(start..num_periods)?)
+        {
+            sum = sum.checked_add(ve_balance(period)?.into())?;
+            *ve_balance_sum = sum;
+        }
+        self.num_periods = num_periods.try_into().ok()?;
+        Some(())
+    }
+
+    /// Sum of the balances of the periods before `period`.
+    fn calculate_sum_before(&self, period: usize) -> Option<u128> {
+        match period.checked_sub(1) {
+            Some(last) => self.ve_balance_sums.get(last).copied(),
+            None => Some(0),
+        }
+    }
+
+    /// Sum of the balances of the periods from `first` to `last`, inclusive,
+    /// or [None] if the sum of `last` has not been recorded.
+    pub fn calculate_sum(&self, first: u8, last: u8) -> Option<u128> {
+        if first > last || u16::from(last) >= self.num_periods {
+            return None;
+        }
+        self.ve_balance_sums[usize::from(last)]
+            .checked_sub(self.calculate_sum_before(usize::from(first))?)
+    }
+}
+
+/// Number of lockups that a [CompactEscrowHistory] can record.
+pub const COMPACT_ESCROW_HISTORY_CHECKPOINTS: usize = 8;
+
+/// Stores the lockups of an [locked_voter::Escrow] over an era, from which its
+/// veToken balance at each period is computed.
+///
+/// Unlike an [EscrowHistory], which stores the balance of every period, a compact history
+/// only stores a checkpoint each time the lockup changes, which takes about 22% of its rent.
+/// The checkpoint at index `i` determines the balances of the periods from
+/// `checkpoint_periods[i]` until the next checkpoint. Periods before the first checkpoint
+/// have no balance.
+///
+/// An escrow has either an [EscrowHistory] or a [CompactEscrowHistory] for each era.
+/// Once all checkpoints are used, the history must be expanded into an [EscrowHistory]
+/// with `expand_compact_escrow_history` before its lockup can change again.
+#[account(zero_copy)]
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CompactEscrowHistory {
+    /// The [locked_voter::Escrow] being tracked.
+    pub escrow: Pubkey,
+    /// The era.
+    pub era: u16,
+    /// Bump seed.
+    pub bump: u8,
+    /// Number of checkpoints that have been recorded.
+    pub num_checkpoints: u8,
+    /// Number of periods, starting from period 0, whose balances can no longer change.
+    pub materialized_periods: u16,
+    /// Where the lockup is tracked: `0` for the [LockerHistory], or `n` for the [LockerHistoryShard] with index `n - 1`.
+    pub shard: u16,
+    /// [LockerHistory::params_version] of the history the lockup is tracked in when it was last synced.
+    pub params_version: u16,
+    /// [LockerHistory::max_stake_vote_multiplier] when the escrow was last synced.
+    pub max_stake_vote_multiplier: u8,
+    /// Padding for aligning the struct to an 8-byte boundary.
+    pub _padding: [u8; 5],
+    /// Number of seconds after the end of the era before this history may be closed.
+    pub retention_seconds: u64,
+    /// Account that paid for this history, which receives its rent when it is closed.
+    pub payer: Pubkey,
+    /// [SnapshotConfig::period_seconds] of the locker when this history was created,
+    /// then [LockerHistory::period_seconds] when the escrow was last synced.
+    ///
+    /// `0` stands for the [EraGeometry::DEFAULT].
+    pub period_seconds: u64,
+    /// [SnapshotConfig::origin_ts] of the locker when this history was created,
+    /// then [LockerHistory::origin_ts] when the escrow was last synced.
+    pub origin_ts: u64,
+    /// First period of each checkpoint.
+    pub checkpoint_periods: [u16; 8],
+    /// Voting power at max lockup of each checkpoint.
+    pub checkpoint_powers: [u64; 8],
+    /// [locked_voter::Escrow::escrow_started_at] of each checkpoint.
+    pub checkpoint_started_at: [u64; 8],
+    /// [locked_voter::Escrow::escrow_ends_at] of each checkpoint.
+    pub checkpoint_ends_at: [u64; 8],
+    /// [LockerHistory::max_stake_duration] of each checkpoint.
+    pub checkpoint_max_stake_durations: [u64; 8],
+}
+
+impl CompactEscrowHistory {
+    /// Number of bytes in a serialized [CompactEscrowHistory].
+    pub const LEN: usize = PUBKEY_BYTES
+        + 2
+        + 1
+        + 1
+        + 2
+        + 2
+        + 2
+        + 1
+        + 5
+        + 8
+        + PUBKEY_BYTES
+        + 8
+        + 8
+        + 2 * 8
+        + 8 * 8
+        + 8 * 8
+        + 8 * 8
+        + 8 * 8;
+
+    /// The [EraGeometry] of the periods of this history.
+    pub fn geometry(&self) -> EraGeometry {
+        stored_geometry(self.period_seconds, self.origin_ts)
+    }
+}
+
+/// Length of the periods and start of era 0 of the histories of a [locked_voter::Locker].
+///
+/// Lockers without a [SnapshotConfig] use the [EraGeometry::DEFAULT]. A config cannot be changed
+/// once created. Each [LockerHistory] records the geometry when it is created, so the config
+/// should be created before any history of the locker. It cannot be created once the
+/// [LockerHistory] of the current era of the [EraGeometry::DEFAULT] exists.
+#[account]
+#[derive(Copy, Debug, Default)]
+pub struct SnapshotConfig {
+    /// The [locked_voter::Locker].
+    pub locker: Pubkey,
+    /// Bump seed.
+    pub bump: u8,
+    /// Number of seconds in a period.
+    pub period_seconds: u64,
+    /// The Unix timestamp of the start of era 0.
+    pub origin_ts: u64,
+}
+
+impl SnapshotConfig {
+    /// Number of bytes in a serialized [SnapshotConfig].
+    pub const LEN: usize = PUBKEY_BYTES + 1 + 8 + 8;
+
+    /// The [EraGeometry] of the histories of the locker.
+    pub fn geometry(&self) -> EraGeometry {
+        stored_geometry(self.period_seconds, self.origin_ts)
+    }
+}
+
+/// The [EraGeometry] recorded in an account. A `period_seconds` of `0` stands for the [EraGeometry::DEFAULT].
+fn stored_geometry(period_seconds: u64, origin_ts: u64) -> EraGeometry {
+    if period_seconds == 0 {
+        EraGeometry::DEFAULT
+    } else {
+        EraGeometry {
+            period_seconds,
+            origin_ts,
+        }
+    }
+}
+
+/// Distributes tokens to the [locked_voter::Escrow]s of a [locked_voter::Locker]
+/// in proportion to their veToken balance at each period.
+#[account]
+#[derive(Copy, Debug, Default)]
+pub struct Distributor {
+    /// The [locked_voter::Locker] whose escrows receive the rewards.
+    pub locker: Pubkey,
+    /// Mint of the token being distributed.
+    pub mint: Pubkey,
+    /// Bump seed.
+    pub bump: u8,
+    /// Token account holding the undistributed rewards.
+    pub vault: Pubkey,
+    /// [SnapshotConfig::period_seconds] of the locker when the distributor was created.
+    ///
+    /// `0` stands for the [EraGeometry::DEFAULT].
+    pub period_seconds: u64,
+    /// [SnapshotConfig::origin_ts] of the locker when the distributor was created.
+    pub origin_ts: u64,
+    /// Account that created the distributor and may claw back unclaimed rewards.
+    pub authority: Pubkey,
+    /// Seconds after a period elapses during which its rewards may be claimed.
+    pub claim_seconds: u64,
+}
+
+impl Distributor {
+    /// Number of bytes in a serialized [Distributor].
+    pub const LEN: usize =
+        PUBKEY_BYTES + PUBKEY_BYTES + 1 + PUBKEY_BYTES + 8 + 8 + PUBKEY_BYTES + 8;
+
+    /// The [EraGeometry] of the periods of this distributor.
+    pub fn geometry(&self) -> EraGeometry {
+        stored_geometry(self.period_seconds, self.origin_ts)
+    }
+
+    /// Unix timestamp after which the rewards of a [DistributorPeriod] can no longer be claimed,
+    /// and may be clawed back.
+    pub fn calculate_claim_deadline(&self, distributor_period: &DistributorPeriod) -> Option<i64> {
+        let period = PeriodId::from((distributor_period.era, distributor_period.period));
+        i64::try_from(
+            self.geometry()
+                .period_end_ts(period)?
+                .checked_add(self.claim_seconds)?,
+        )
+        .ok()
+    }
+}
+
+/// Rewards of a [Distributor] for a single period.
+#[account]
+#[derive(Copy, Debug, Default)]
+pub struct DistributorPeriod {
+    /// The [Distributor].
+    pub distributor: Pubkey,
+    /// The era.
+    pub era: u16,
+    /// The period within the era.
+    pub period: u8,
+    /// Bump seed.
+    pub bump: u8,
+    /// Total amount of tokens to distribute for this period.
+    pub rewards: u64,
+    /// Amount of tokens that have been claimed.
+    pub claimed_rewards: u64,
+    /// Amount of unclaimed tokens that were clawed back after the claim deadline.
+    pub clawed_back_rewards: u64,
+}
+
+impl DistributorPeriod {
+    /// Number of bytes in a serialized [DistributorPeriod].
+    pub const LEN: usize = PUBKEY_BYTES + 2 + 1 + 1 + 8 + 8 + 8;
+}
+
+/// Records that an [locked_voter::Escrow] has claimed its rewards for a [DistributorPeriod].
+#[account]
+#[derive(Copy, Debug, Default)]
+pub struct ClaimReceipt {
+    /// The [DistributorPeriod].
+    pub distributor_period: Pubkey,
+    /// The [locked_voter::Escrow] that claimed.
+    pub escrow: Pubkey,
+    /// Bump seed.
+    pub bump: u8,
+    /// Amount of tokens claimed.
+    pub amount: u64,
+}
+
+impl ClaimReceipt {
+    /// Number of bytes in a serialized [ClaimReceipt].
+    pub const LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + 1 + 8;
+}
+
+/// Number of claims tracked by each [SnapshotAirdropBitmap].
+pub const SNAPSHOT_AIRDROP_BITMAP_CLAIMS: u64 = 64 * 1024;
+
+/// A Merkle airdrop to the escrow owners of a [locked_voter::Locker], weighted by their
+/// veToken balances at a period.
+///
+/// The airdrop records the [LockerHistory] total of the period when it is created,
+/// so claimants can check that the tree was generated from the same snapshot.
+#[account]
+#[derive(Copy, Debug, Default)]
+pub struct SnapshotAirdrop {
+    /// Account that published the airdrop and may claw back unclaimed tokens.
+    pub authority: Pubkey,
+    /// The [locked_voter::Locker] whose escrow owners may claim.
+    pub locker: Pubkey,
+    /// The era of the snapshot.
+    pub era: u16,
+    /// The period of the snapshot within the era.
+    pub period: u8,
+    /// Bump seed.
+    pub bump: u8,
+    /// Mint of the token being airdropped.
+    pub mint: Pubkey,
+    /// Token account holding the unclaimed tokens.
+    pub vault: Pubkey,
+    /// Root of the Merkle tree of claims.
+    pub root: [u8; 32],
+    /// [LockerHistory::ve_balances] of the period when the airdrop was created.
+    pub locker_ve_balance: u64,
+    /// Sum of the amounts of all claims, which is deposited when the airdrop is created.
+    pub max_total_claim: u64,
+    /// Number of claims in the tree.
+    pub max_num_nodes: u64,
+    /// Amount of tokens that have been claimed.
+    pub total_amount_claimed: u64,
+    /// Number of claims that have been made.
+    pub num_nodes_claimed: u64,
+    /// Unix timestamp after which tokens can no longer be claimed, and may be clawed back.
+    pub claim_deadline: i64,
+}
+
+impl SnapshotAirdrop {
+    /// Number of bytes in a serialized [SnapshotAirdrop].
+    pub const LEN: usize = PUBKEY_BYTES
+        + PUBKEY_BYTES
+        + 2
+        + 1
+        + 1
+        + PUBKEY_BYTES
+        + PUBKEY_BYTES
+        + 32
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8;
+}
+
+/// Records which claims of a [SnapshotAirdrop] have been made.
+///
+/// Each bitmap tracks [SNAPSHOT_AIRDROP_BITMAP_CLAIMS] consecutive claim indices,
+/// starting at `page * SNAPSHOT_AIRDROP_BITMAP_CLAIMS`.
+#[account(zero_copy)]
+#[derive(Debug, PartialEq, Eq)]
+pub struct SnapshotAirdropBitmap {
+    /// The [SnapshotAirdrop].
+    pub airdrop: Pubkey,
+    /// Index of the bitmap.
+    pub page: u32,
+    /// Bump seed.
+    pub bump: u8,
+    /// Padding for aligning the struct to an 8-byte boundary.
+    pub _padding: [u8; 3],
+    /// One bit per claim, set once the claim has been made.
+    pub claimed: [u64; 1024],
+}
+
+impl SnapshotAirdropBitmap {
+    /// Number of bytes in a serialized [SnapshotAirdropBitmap].
+    pub const LEN: usize = PUBKEY_BYTES + 4 + 1 + 3 + 8 * 1024;
+
+    /// Index of the bitmap that tracks the claim with the given index.
+    pub fn calculate_page(index: u64) -> Option<u32> {
+        index
+            .checked_div(SNAPSHOT_AIRDROP_BITMAP_CLAIMS)?
+            .try_into()
+            .ok()
+    }
+
+    /// Word of [SnapshotAirdropBitmap::claimed] and mask of the bit that tracks a claim,
+    /// or [None] if the claim is tracked by another bitmap.
+    fn claim_bit(&self, index: u64) -> Option<(usize, u64)> {
+        let offset =
+            index.checked_sub(u64::from(self.page).checked_mul(SNAPSHOT_AIRDROP_BITMAP_CLAIMS)?)?;
+        if offset >= SNAPSHOT_AIRDROP_BITMAP_CLAIMS {
+            return None;
+        }
+        Some((
+            offset.checked_div(64)?.try_into().ok()?,
+            1_u64.checked_shl(offset.checked_rem(64)?.try_into().ok()?)?,
+        ))
+    }
+
+    /// Returns true if the claim has been made.
+    pub fn is_claimed(&self, index: u64) -> Option<bool> {
+        let (word, mask) = self.claim_bit(index)?;
+        Some(self.claimed[word] & mask != 0)
+    }
+
+    /// Records that the claim has been made.
+    pub fn set_claimed(&mut self, index: u64) -> Option<()> {
+        let (word, mask) = self.claim_bit(index)?;
+        self.claimed[word] |= mask;
+        Some(())
+    }
+}
+
+/// Lets an SPL Governance realm weigh votes by the [EscrowHistory] balances of a [locked_voter::Locker].
+///
+/// There is one registrar per realm, which is created by the realm authority.
+#[account]
+#[derive(Copy, Debug, Default)]
+pub struct VoterWeightRegistrar {
+    /// The SPL Governance realm.
+    pub realm: Pubkey,
+    /// The SPL Governance program that owns the realm.
+    pub governance_program: Pubkey,
+    /// Community mint of the realm, which is the [locked_voter::Locker::token_mint].
+    pub governing_token_mint: Pubkey,
+    /// The [locked_voter::Locker] whose escrows vote in the realm.
+    pub locker: Pubkey,
+    /// Bump seed.
+    pub bump: u8,
+    /// [SnapshotConfig::period_seconds] of the locker when the registrar was created.
+    ///
+    /// `0` stands for the [EraGeometry::DEFAULT].
+    pub period_seconds: u64,
+    /// [SnapshotConfig::origin_ts] of the locker when the registrar was created.
+    pub origin_ts: u64,
+}
+
+impl VoterWeightRegistrar {
+    /// Number of bytes in a serialized [VoterWeightRegistrar].
+    pub const LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + PUBKEY_BYTES + PUBKEY_BYTES + 1 + 8 + 8;
+
+    /// The [EraGeometry] of the periods of this registrar.
+    pub fn geometry(&self) -> EraGeometry {
+        stored_geometry(self.period_seconds, self.origin_ts)
+    }
+}
+
+/// The period whose [EscrowHistory] balances are used to vote on an SPL Governance proposal.
+///
+/// The snapshot is taken while the proposal is still a draft, and records the period containing
+/// the proposal's `draft_at`. Since started periods are never modified, locking tokens after the
+/// proposal was drafted does not change the weight of any vote on the proposal.
+#[account]
+#[derive(Copy, Debug, Default)]
+pub struct ProposalSnapshot {
+    /// The [VoterWeightRegistrar].
+    pub registrar: Pubkey,
+    /// The SPL Governance proposal.
+    pub proposal: Pubkey,
+    /// The era of the snapshot.
+    pub era: u16,
+    /// The period of the snapshot within the era.
+    pub period: u8,
+    /// Bump seed.
+    pub bump: u8,
+}
+
+impl ProposalSnapshot {
+    /// Number of bytes in a serialized [ProposalSnapshot].
+    pub const LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + 2 + 1 + 1;
+}
+
+/// Action that a [VoterWeightRecord] may be used for, as defined by the SPL Governance addin interface.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoterWeightAction {
+    /// Cast a vote on a proposal.
+    CastVote,
+    /// Comment on a proposal.
+    CommentProposal,
+    /// Create a governance.
+    CreateGovernance,
+    /// Create a proposal.
+    CreateProposal,
+    /// Sign off a proposal.
+    SignOffProposal,
+}
+
+/// Voter weight of a governing token owner, in the layout of the SPL Governance addin interface.
+///
+/// The account discriminator is the one expected by SPL Governance, so the name of this
+/// struct and the order of its fields must not change.
+#[account]
+#[derive(Debug, Default)]
+pub struct VoterWeightRecord {
+    /// The SPL Governance realm.
+    pub realm: Pubkey,
+    /// The mint of the governing token.
+    pub governing_token_mint: Pubkey,
+    /// The owner of the governing tokens, which is the [locked_voter::Escrow::owner].
+    pub governing_token_owner: Pubkey,
+    /// The voter weight.
+    pub voter_weight: u64,
+    /// Slot after which the voter weight must be updated again.
+    pub voter_weight_expiry: Option<u64>,
+    /// The action the voter weight may be used for.
+    pub weight_action: Option<VoterWeightAction>,
+    /// The account the action may be applied to, such as the proposal being voted on.
+    pub weight_action_target: Option<Pubkey>,
+    /// Reserved space for future versions of the interface.
+    pub reserved: [u8; 8],
+}
+
+impl VoterWeightRecord {
+    /// Number of bytes in a serialized [VoterWeightRecord].
+    pub const LEN: usize =
+        PUBKEY_BYTES + PUBKEY_BYTES + PUBKEY_BYTES + 8 + (1 + 8) + (1 + 1) + (1 + PUBKEY_BYTES) + 8;
+}
+
+/// The voting power of the escrows of a [locked_voter::Locker] when a [govern::Proposal] was created.
+///
+/// The snapshot uses the period containing [govern::Proposal::created_at]. Since started periods
+/// are never modified, locking tokens after the proposal was created does not change its votes.
+#[account]
+#[derive(Copy, Debug, Default)]
+pub struct GovernProposalSnapshot {
+    /// The [govern::Proposal].
+    pub proposal: Pubkey,
+    /// The [locked_voter::Locker] of the [govern::Governor].
+    pub locker: Pubkey,
+    /// The era of the snapshot.
+    pub era: u16,
+    /// The period of the snapshot within the era.
+    pub period: u8,
+    /// Bump seed.
+    pub bump: u8,
+    /// [LockerHistory::ve_balances] of the period, which is the total voting power.
+    pub total_voting_power: u64,
+    /// [LockerHistory::ve_counts] of the period.
+    pub num_voters: u64,
+    /// [govern::Proposal::quorum_votes], which votes in support must reach out of the
+    /// [GovernProposalSnapshot::total_voting_power].
+    ///
+    /// The quorum is not derived from the total: the [govern::Governor] sets it as an absolute
+    /// number of votes, in the same units as the [EscrowHistory] balances, and records no
+    /// reference total that a fraction could be taken of.
+    pub quorum_votes: u64,
+    /// [SnapshotConfig::period_seconds] of the locker when the snapshot was created.
+    /// `0` stands for the [EraGeometry::DEFAULT].
+    pub period_seconds: u64,
+    /// [SnapshotConfig::origin_ts] of the locker when the snapshot was created.
+    pub origin_ts: u64,
+}
+
+impl GovernProposalSnapshot {
+    /// Number of bytes in a serialized [GovernProposalSnapshot].
+    pub const LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + 2 + 1 + 1 + 8 + 8 + 8 + 8 + 8;
+
+    /// The [EraGeometry] of the period of this snapshot.
+    pub fn geometry(&self) -> EraGeometry {
+        stored_geometry(self.period_seconds, self.origin_ts)
+    }
+}
+
+/// The voting power of an [locked_voter::Escrow] at a [GovernProposalSnapshot].
+#[account]
+#[derive(Copy, Debug, Default)]
+pub struct VoteWeightReceipt {
+    /// The [GovernProposalSnapshot].
+    pub snapshot: Pubkey,
+    /// The [locked_voter::Escrow].
+    pub escrow: Pubkey,
+    /// The [locked_voter::Escrow::owner].
+    pub owner: Pubkey,
+    /// Bump seed.
+    pub bump: u8,
+    /// [EscrowHistory] balance of the escrow at the period of the snapshot.
+    pub voting_power: u64,
+}
+
+impl VoteWeightReceipt {
+    /// Number of bytes in a serialized [VoteWeightReceipt].
+    pub const LEN: usize = PUBKEY_BYTES + PUBKEY_BYTES + PUBKEY_BYTES + 1 + 8;
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use anchor_lang::Discriminator;
     use std::mem::size_of;
 
     #[test]
@@ -91,8 +939,163 @@ mod tests {
         assert_eq!(size_of::<LockerHistory>(), LockerHistory::LEN);
     }
 
+    #[test]
+    fn test_locker_history_shard_len() {
+        assert_eq!(size_of::<LockerHistoryShard>(), LockerHistoryShard::LEN);
+    }
+
     #[test]
     fn test_escrow_history_len() {
         assert_eq!(size_of::<EscrowHistory>(), EscrowHistory::LEN);
     }
+
+    #[test]
+    fn test_cumulative_balances_len() {
+        assert_eq!(size_of::<CumulativeBalances>(), CumulativeBalances::LEN);
+    }
+
+    #[test]
+    fn test_cumulative_balances_sums() {
+        let mut ve_balances = [0; ERA_NUM_PERIODS];
+        for (period, ve_balance) in ve_balances.iter_mut().enumerate() {
+            *ve_balance = u64::MAX - period as u64;
+        }
+        let mut cumulative = CumulativeBalances::default();
+        assert_eq!(cumulative.calculate_sum(0, 0), None);
+
+        let read = |ve_balances: [u64; ERA_NUM_PERIODS]| {
+            move |period: usize| ve_balances.get(period).copied()
+        };
+        assert_eq!(cumulative.extend(3, read(ve_balances)), Some(()));
+        assert_eq!(cumulative.num_periods, 3);
+        assert_eq!(
+            cumulative.calculate_sum(0, 2),
+            Some(3 * u128::from(u64::MAX) - 3)
+        );
+        assert_eq!(cumulative.calculate_sum(0, 3), None);
+
+        // extending never rewrites recorded periods
+        ve_balances[0] = 0;
+        assert_eq!(cumulative.extend(2, read(ve_balances)), Some(()));
+        assert_eq!(cumulative.num_periods, 3);
+        assert_eq!(
+            cumulative.extend(ERA_NUM_PERIODS, read(ve_balances)),
+            Some(())
+        );
+        assert_eq!(cumulative.num_periods, 256);
+        assert_eq!(
+            cumulative.calculate_sum(1, 1),
+            Some(u128::from(u64::MAX - 1))
+        );
+        assert_eq!(
+            cumulative.calculate_sum(255, 255),
+            Some(u128::from(u64::MAX - 255))
+        );
+        assert_eq!(
+            cumulative.calculate_sum(0, 255),
+            Some(
+                (0..256)
+                    .map(|period| u128::from(u64::MAX - period))
+                    .sum::<u128>()
+            )
+        );
+        assert_eq!(cumulative.calculate_sum(2, 1), None);
+        assert_eq!(
+            cumulative.extend(ERA_NUM_PERIODS + 1, read(ve_balances)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_voter_weight_record_layout() {
+        // discriminator of the SPL Governance addin interface
+        assert_eq!(
+            VoterWeightRecord::discriminator(),
+            [46, 249, 155, 75, 153, 248, 116, 9]
+        );
+        let record = VoterWeightRecord {
+            voter_weight_expiry: Some(1),
+            weight_action: Some(VoterWeightAction::CastVote),
+            weight_action_target: Some(Pubkey::new_unique()),
+            ..Default::default()
+        };
+        assert_eq!(record.try_to_vec().unwrap().len(), VoterWeightRecord::LEN);
+    }
+
+    #[test]
+    fn test_distributor_len() {
+        assert_eq!(
+            Distributor::default().try_to_vec().unwrap().len(),
+            Distributor::LEN
+        );
+        assert_eq!(
+            DistributorPeriod::default().try_to_vec().unwrap().len(),
+            DistributorPeriod::LEN
+        );
+    }
+
+    #[test]
+    fn test_distributor_claim_deadline() {
+        let distributor = Distributor {
+            period_seconds: 10,
+            origin_ts: 1_000,
+            claim_seconds: 60,
+            ..Default::default()
+        };
+        let distributor_period = DistributorPeriod {
+            era: 1,
+            period: 2,
+            ..Default::default()
+        };
+        // era 1 starts after 256 periods, and period 2 ends 3 periods later
+        assert_eq!(
+            distributor.calculate_claim_deadline(&distributor_period),
+            Some(1_000 + (256 + 3) * 10 + 60)
+        );
+        let distributor = Distributor {
+            claim_seconds: u64::MAX,
+            ..distributor
+        };
+        assert_eq!(
+            distributor.calculate_claim_deadline(&distributor_period),
+            None
+        );
+    }
+
+    #[test]
+    fn test_compact_escrow_history_len() {
+        assert_eq!(size_of::<CompactEscrowHistory>(), CompactEscrowHistory::LEN);
+    }
+
+    #[test]
+    fn test_snapshot_airdrop_bitmap_len() {
+        assert_eq!(
+            size_of::<SnapshotAirdropBitmap>(),
+            SnapshotAirdropBitmap::LEN
+        );
+    }
+
+    #[test]
+    fn test_snapshot_airdrop_bitmap_claims() {
+        let mut bitmap: SnapshotAirdropBitmap =
+            anchor_lang::__private::bytemuck::Zeroable::zeroed();
+        bitmap.page = 1;
+        let first = SNAPSHOT_AIRDROP_BITMAP_CLAIMS;
+        let last = 2 * SNAPSHOT_AIRDROP_BITMAP_CLAIMS - 1;
+        assert_eq!(SnapshotAirdropBitmap::calculate_page(first), Some(1));
+        assert_eq!(SnapshotAirdropBitmap::calculate_page(last), Some(1));
+        assert_eq!(SnapshotAirdropBitmap::calculate_page(last + 1), Some(2));
+
+        assert_eq!(bitmap.is_claimed(first + 70), Some(false));
+        assert_eq!(bitmap.set_claimed(first + 70), Some(()));
+        assert_eq!(bitmap.set_claimed(last), Some(()));
+        assert_eq!(bitmap.is_claimed(first + 70), Some(true));
+        assert_eq!(bitmap.is_claimed(first + 71), Some(false));
+        assert_eq!(bitmap.is_claimed(last), Some(true));
+        assert_eq!(bitmap.claimed[1], 1 << 6);
+
+        // claims of other pages are not tracked
+        assert_eq!(bitmap.is_claimed(first - 1), None);
+        assert_eq!(bitmap.set_claimed(last + 1), None);
+    }
 }
@@ -0,0 +1,227 @@
+//! Types returned by the read-only instructions of the [crate::snapshots] program.
+//!
+//! These instructions write their result with `set_return_data`. Other programs should call them
+//! through the helpers in `views::cpi`, which decode the return data into the types below.
+
+use crate::*;
+use anchor_lang::Discriminator;
+use num_traits::ToPrimitive;
+use std::ops::RangeInclusive;
+
+/// Selects the period that a read-only instruction looks up.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeriodQuery {
+    /// A period of an era.
+    Period {
+        /// The era.
+        era: u16,
+        /// The period within the era.
+        period: u8,
+    },
+    /// The period containing a Unix timestamp.
+    Timestamp {
+        /// The Unix timestamp.
+        ts: i64,
+    },
+}
+
+impl PeriodQuery {
+    /// Calculates the era and period being selected, with timestamps resolved by the [EraGeometry] of the history.
+    pub fn calculate_era_and_period(&self, geometry: &EraGeometry) -> Option<(u16, u8)> {
+        match *self {
+            PeriodQuery::Period { era, period } => Some((era, period)),
+            PeriodQuery::Timestamp { ts } => Some(geometry.period_of_ts(ts.to_u64()?)?.into()),
+        }
+    }
+}
+
+/// Selects an inclusive range of periods, which may span several eras.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PeriodRangeQuery {
+    /// The first period of the range.
+    pub start: PeriodQuery,
+    /// The last period of the range.
+    pub end: PeriodQuery,
+}
+
+impl PeriodRangeQuery {
+    /// Calculates the first and last period being selected.
+    pub fn calculate_period_range(&self, geometry: &EraGeometry) -> Option<(PeriodId, PeriodId)> {
+        Some((
+            self.start.calculate_era_and_period(geometry)?.into(),
+            self.end.calculate_era_and_period(geometry)?.into(),
+        ))
+    }
+}
+
+/// Periods of `era` between `start` and `end`, inclusive.
+pub(crate) fn calculate_periods_of_era(
+    start: PeriodId,
+    end: PeriodId,
+    era: u16,
+) -> RangeInclusive<u8> {
+    let first = if era == start.era.0 {
+        start.period.0
+    } else {
+        0
+    };
+    let last = if era == end.era.0 {
+        end.period.0
+    } else {
+        u8::MAX
+    };
+    first..=last
+}
+
+/// The [CumulativeBalances] given to an average view, from which the total balance over the
+/// periods they have recorded is read instead of being summed period by period.
+pub(crate) struct ViewCumulativeBalances<'info>(Vec<AccountLoader<'info, CumulativeBalances>>);
+
+impl<'info> ViewCumulativeBalances<'info> {
+    /// Separates the [CumulativeBalances] from the histories in the remaining accounts of a view.
+    pub(crate) fn split<'a>(
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<(Vec<&'a AccountInfo<'info>>, Self)> {
+        let mut histories = vec![];
+        let mut cumulative_balances = vec![];
+        for info in remaining_accounts {
+            let is_cumulative_balances = info.owner == &crate::ID
+                && info.try_borrow_data()?.get(..8) == Some(&CumulativeBalances::discriminator());
+            if is_cumulative_balances {
+                cumulative_balances.push(AccountLoader::try_from(info)?);
+            } else {
+                histories.push(info);
+            }
+        }
+        Ok((histories, Self(cumulative_balances)))
+    }
+
+    /// Total balance of `history` over `periods`, or [None] if its [CumulativeBalances]
+    /// were not given or have not recorded all of these periods.
+    pub(crate) fn calculate_sum(
+        &self,
+        history: &Pubkey,
+        periods: &RangeInclusive<u8>,
+    ) -> Result<Option<u128>> {
+        for loader in &self.0 {
+            let cumulative_balances = loader.load()?;
+            if cumulative_balances.history == *history {
+                return Ok(cumulative_balances.calculate_sum(*periods.start(), *periods.end()));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Total veToken balance of a [LockerHistory] at a period.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LockerBalance {
+    /// Sum of all veToken balances.
+    pub ve_balance: u64,
+    /// Number of lockups with voting power.
+    pub ve_count: u64,
+    /// True if the total can no longer change. See [LockerHistory::is_period_final].
+    pub is_final: bool,
+}
+
+/// Share of an [locked_voter::Escrow] in the total veToken balance of its locker at a period.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EscrowShare {
+    /// veToken balance of the escrow.
+    pub escrow_ve_balance: u64,
+    /// Sum of all veToken balances of the locker.
+    pub locker_ve_balance: u64,
+    /// True if the total can no longer change. See [LockerHistory::is_period_final].
+    pub is_final: bool,
+}
+
+/// Writes the result of a read-only instruction.
+pub(crate) fn set_view_return_data<T: AnchorSerialize>(value: &T) -> Result<()> {
+    anchor_lang::solana_program::program::set_return_data(&value.try_to_vec()?);
+    Ok(())
+}
+
+/// Helpers for calling the read-only instructions from other programs.
+#[cfg(feature = "cpi")]
+pub mod cpi {
+    use super::*;
+
+    /// Reads the result of the read-only instruction that was just invoked.
+    pub fn get_view_return_data<T: AnchorDeserialize>() -> Result<T> {
+        let (program_id, data) = unwrap_opt!(
+            anchor_lang::solana_program::program::get_return_data(),
+            InvalidReturnData
+        );
+        assert_keys_eq!(program_id, crate::ID, InvalidReturnData);
+        T::try_from_slice(&data).map_err(|_| error!(ErrorCode::InvalidReturnData))
+    }
+
+    /// Invokes [crate::snapshots::get_escrow_balance] and returns the veToken balance of the escrow.
+    pub fn get_escrow_balance<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, crate::cpi::accounts::GetEscrowBalance<'info>>,
+        query: PeriodQuery,
+    ) -> Result<u64> {
+        crate::cpi::get_escrow_balance(ctx, query)?;
+        get_view_return_data()
+    }
+
+    /// Invokes [crate::snapshots::get_locker_balance] and returns the total veToken balance.
+    pub fn get_locker_balance<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, crate::cpi::accounts::GetLockerBalance<'info>>,
+        query: PeriodQuery,
+    ) -> Result<LockerBalance> {
+        crate::cpi::get_locker_balance(ctx, query)?;
+        get_view_return_data()
+    }
+
+    /// Invokes [crate::snapshots::get_share] and returns the share of the escrow.
+    pub fn get_share<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, crate::cpi::accounts::GetShare<'info>>,
+        query: PeriodQuery,
+    ) -> Result<EscrowShare> {
+        crate::cpi::get_share(ctx, query)?;
+        get_view_return_data()
+    }
+
+    /// Invokes [crate::snapshots::get_average_escrow_balance] and returns the average veToken balance of the escrow.
+    pub fn get_average_escrow_balance<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, crate::cpi::accounts::GetAverageEscrowBalance<'info>>,
+        query: PeriodRangeQuery,
+    ) -> Result<u64> {
+        crate::cpi::get_average_escrow_balance(ctx, query)?;
+        get_view_return_data()
+    }
+
+    /// Invokes [crate::snapshots::get_average_locker_balance] and returns the average total veToken balance.
+    pub fn get_average_locker_balance<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, crate::cpi::accounts::GetAverageLockerBalance<'info>>,
+        query: PeriodRangeQuery,
+    ) -> Result<LockerBalance> {
+        crate::cpi::get_average_locker_balance(ctx, query)?;
+        get_view_return_data()
+    }
+
+    /// Invokes [crate::snapshots::get_average_share] and returns the average share of the escrow.
+    pub fn get_average_share<'info>(
+        ctx: CpiContext<'_, '_, '_, 'info, crate::cpi::accounts::GetAverageShare<'info>>,
+        query: PeriodRangeQuery,
+    ) -> Result<EscrowShare> {
+        crate::cpi::get_average_share(ctx, query)?;
+        get_view_return_data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_periods_of_era() {
+        let start = PeriodId::from((1, 250));
+        let end = PeriodId::from((3, 4));
+        assert_eq!(calculate_periods_of_era(start, end, 1), 250..=255);
+        assert_eq!(calculate_periods_of_era(start, end, 2), 0..=255);
+        assert_eq!(calculate_periods_of_era(start, end, 3), 0..=4);
+        assert_eq!(calculate_periods_of_era(start, start, 1), 250..=250);
+    }
+}
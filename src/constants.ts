@@ -46,3 +46,8 @@ export const PERIOD_SECONDS = 86_400 * 3;
  * The Unix timestamp of the start of the first era.
  */
 export const COMMON_ERA_UNIX_TS = 1640995200;
+
+/**
+ * Number of claims tracked by each SnapshotAirdropBitmap.
+ */
+export const SNAPSHOT_AIRDROP_BITMAP_CLAIMS = 64 * 1024;
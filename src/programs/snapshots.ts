@@ -9,6 +9,20 @@ export type SnapshotsTypes = AnchorTypes<
   {
     lockerHistory: LockerHistoryData;
     escrowHistory: EscrowHistoryData;
+    compactEscrowHistory: CompactEscrowHistoryData;
+    lockerHistoryShard: LockerHistoryShardData;
+    distributor: DistributorData;
+    distributorPeriod: DistributorPeriodData;
+    claimReceipt: ClaimReceiptData;
+    snapshotConfig: SnapshotConfigData;
+    cumulativeBalances: CumulativeBalancesData;
+    voterWeightRegistrar: VoterWeightRegistrarData;
+    proposalSnapshot: ProposalSnapshotData;
+    voterWeightRecord: VoterWeightRecordData;
+    governProposalSnapshot: GovernProposalSnapshotData;
+    voteWeightReceipt: VoteWeightReceiptData;
+    snapshotAirdrop: SnapshotAirdropData;
+    snapshotAirdropBitmap: SnapshotAirdropBitmapData;
   }
 >;
 
@@ -16,5 +30,19 @@ type Accounts = SnapshotsTypes["Accounts"];
 
 export type LockerHistoryData = Accounts["LockerHistory"];
 export type EscrowHistoryData = Accounts["EscrowHistory"];
+export type CompactEscrowHistoryData = Accounts["CompactEscrowHistory"];
+export type LockerHistoryShardData = Accounts["LockerHistoryShard"];
+export type DistributorData = Accounts["Distributor"];
+export type DistributorPeriodData = Accounts["DistributorPeriod"];
+export type ClaimReceiptData = Accounts["ClaimReceipt"];
+export type SnapshotConfigData = Accounts["SnapshotConfig"];
+export type CumulativeBalancesData = Accounts["CumulativeBalances"];
+export type VoterWeightRegistrarData = Accounts["VoterWeightRegistrar"];
+export type ProposalSnapshotData = Accounts["ProposalSnapshot"];
+export type VoterWeightRecordData = Accounts["VoterWeightRecord"];
+export type GovernProposalSnapshotData = Accounts["GovernProposalSnapshot"];
+export type VoteWeightReceiptData = Accounts["VoteWeightReceipt"];
+export type SnapshotAirdropData = Accounts["SnapshotAirdrop"];
+export type SnapshotAirdropBitmapData = Accounts["SnapshotAirdropBitmap"];
 
 export type SnapshotsProgram = SnapshotsTypes["Program"];
@@ -0,0 +1,124 @@
+import type { PublicKey } from "@solana/web3.js";
+import BN from "bn.js";
+import { keccak_256 } from "js-sha3";
+import invariant from "tiny-invariant";
+
+import { SNAPSHOT_AIRDROP_BITMAP_CLAIMS } from "../../constants";
+
+/**
+ * A claim of a SnapshotAirdrop.
+ */
+export interface AirdropClaim {
+  index: number;
+  claimant: PublicKey;
+  amount: BN;
+}
+
+const keccak = (...data: Buffer[]): Buffer =>
+  Buffer.from(keccak_256.arrayBuffer(Buffer.concat(data)));
+
+/**
+ * Computes the leaf of a claim, like `snapshots_airdrop::compute_leaf`.
+ * @returns
+ */
+export const computeAirdropLeaf = ({
+  index,
+  claimant,
+  amount,
+}: AirdropClaim): Buffer => {
+  return keccak(
+    new BN(index).toArrayLike(Buffer, "le", 8),
+    claimant.toBuffer(),
+    amount.toArrayLike(Buffer, "le", 8)
+  );
+};
+
+const hashPair = (a: Buffer, b: Buffer): Buffer =>
+  Buffer.compare(a, b) <= 0 ? keccak(a, b) : keccak(b, a);
+
+/**
+ * Index of the SnapshotAirdropBitmap that tracks a claim.
+ * @returns
+ */
+export const calculateAirdropBitmapPage = (index: number): number =>
+  Math.floor(index / SNAPSHOT_AIRDROP_BITMAP_CLAIMS);
+
+/**
+ * A Merkle tree of airdrop claims, built like `snapshots_airdrop::MerkleTree`.
+ */
+export class AirdropMerkleTree {
+  /**
+   * Layers of the tree, from the sorted leaves to the root.
+   */
+  readonly layers: Buffer[][];
+
+  constructor(claims: readonly AirdropClaim[]) {
+    // duplicate leaves are removed
+    const leaves = claims
+      .map(computeAirdropLeaf)
+      .sort(Buffer.compare)
+      .filter((leaf, i, sorted) => {
+        const prev = sorted[i - 1];
+        return !prev || !leaf.equals(prev);
+      });
+    this.layers = [leaves];
+    let layer = leaves;
+    while (layer.length > 1) {
+      const next: Buffer[] = [];
+      for (let i = 0; i < layer.length; i += 2) {
+        const a = layer[i];
+        const b = layer[i + 1];
+        invariant(a);
+        // the last node of an odd layer is carried up
+        next.push(b ? hashPair(a, b) : a);
+      }
+      this.layers.push(next);
+      layer = next;
+    }
+  }
+
+  /**
+   * The root of the tree.
+   */
+  get root(): Buffer {
+    const root = this.layers[this.layers.length - 1]?.[0];
+    invariant(root, "tree has no claims");
+    return root;
+  }
+
+  /**
+   * Computes the proof that a claim is part of the tree.
+   * @returns
+   */
+  getProof(claim: AirdropClaim): Buffer[] {
+    const leaf = computeAirdropLeaf(claim);
+    let index = this.layers[0]?.findIndex((node) => node.equals(leaf)) ?? -1;
+    invariant(index !== -1, "claim is not part of the tree");
+    const proof: Buffer[] = [];
+    for (const layer of this.layers) {
+      const sibling = layer[index ^ 1];
+      if (sibling) {
+        proof.push(sibling);
+      }
+      index = Math.floor(index / 2);
+    }
+    return proof;
+  }
+}
+
+/**
+ * Returns true if the proof shows that a claim is part of the tree with the
+ * given root, like `snapshots_airdrop::verify_proof`.
+ * @returns
+ */
+export const verifyAirdropProof = (
+  proof: readonly Buffer[],
+  root: Buffer,
+  claim: AirdropClaim
+): boolean =>
+  proof
+    .reduce(
+      (node, sibling) => hashPair(node, sibling),
+      computeAirdropLeaf(claim)
+    )
+    .equals(root);
@@ -0,0 +1,173 @@
+import BN from "bn.js";
+
+import {
+  COMMON_ERA_UNIX_TS,
+  ERA_NUM_PERIODS,
+  PERIOD_SECONDS,
+} from "../../constants";
+import type { EscrowHistoryData, LockerHistoryData } from "../../programs";
+
+/**
+ * Period length and start of era 0 of a history.
+ */
+export interface HistoryGeometry {
+  periodSeconds: BN;
+  originTs: BN;
+}
+
+/**
+ * Returns the geometry that a history was created with.
+ *
+ * Histories store a `periodSeconds` of zero for the default geometry.
+ * @param history
+ * @returns
+ */
+export const getHistoryGeometry = ({
+  periodSeconds,
+  originTs,
+}: {
+  periodSeconds: BN;
+  originTs: BN;
+}): HistoryGeometry =>
+  periodSeconds.isZero()
+    ? {
+        periodSeconds: new BN(PERIOD_SECONDS),
+        originTs: new BN(COMMON_ERA_UNIX_TS),
+      }
+    : { periodSeconds, originTs };
+
+/**
+ * Calculates the Unix timestamp at which a period of an era starts.
+ * @returns
+ */
+export const calculateHistoryPeriodStartTs = (
+  geometry: HistoryGeometry,
+  era: number,
+  period: number
+): BN =>
+  geometry.originTs.add(
+    geometry.periodSeconds.muln(era * ERA_NUM_PERIODS + period)
+  );
+
+/**
+ * Calculates the voting power of a lockup at the start of a period,
+ * as the Snapshots program does.
+ * @returns
+ */
+export const calculateVoterPowerForPeriod = ({
+  powerIfMaxLockup,
+  periodStartTs,
+  startedAt,
+  endsAt,
+  maxStakeDuration,
+}: {
+  powerIfMaxLockup: BN;
+  periodStartTs: BN;
+  startedAt: BN;
+  endsAt: BN;
+  maxStakeDuration: BN;
+}): BN => {
+  if (
+    startedAt.isZero() ||
+    periodStartTs.lt(startedAt) ||
+    periodStartTs.gte(endsAt)
+  ) {
+    return new BN(0);
+  }
+  const secondsUntilExpiry = BN.min(
+    endsAt.sub(periodStartTs),
+    maxStakeDuration
+  );
+  return powerIfMaxLockup.mul(secondsUntilExpiry).div(maxStakeDuration);
+};
+
+/**
+ * Calculates the veBalance of an escrow at a period.
+ *
+ * `veBalances` only holds the balances of materialized periods; those of later periods
+ * are computed from the lockup recorded in the history.
+ * @param history
+ * @param period
+ * @returns
+ */
+export const calculateEscrowVeBalance = (
+  history: EscrowHistoryData,
+  period: number
+): BN => {
+  if (period < history.materializedPeriods) {
+    return history.veBalances[period] as BN;
+  }
+  return calculateVoterPowerForPeriod({
+    powerIfMaxLockup: history.power,
+    periodStartTs: calculateHistoryPeriodStartTs(
+      getHistoryGeometry(history),
+      history.era,
+      period
+    ),
+    startedAt: history.startedAt,
+    endsAt: history.endsAt,
+    maxStakeDuration: history.maxStakeDuration,
+  });
+};
+
+/**
+ * Calculates the total veBalance of a locker at a period.
+ *
+ * `veBalances` only holds the totals of materialized periods; those of later periods
+ * are computed from the slope and bias of the history. Balances of shards that have
+ * not been merged into the period are not included.
+ * @param history
+ * @param period
+ * @returns
+ */
+export const calculateLockerVeBalance = (
+  history: LockerHistoryData,
+  period: number
+): BN => {
+  const direct = history.veBalances[period] as BN;
+  if (period < history.materializedPeriods) {
+    return direct;
+  }
+  let slope = history.slope;
+  let bias = history.bias;
+  let endOffset = history.endOffset;
+  for (let p = history.materializedPeriods; p <= period; p++) {
+    const endedSlope = history.slopeChanges[p] as BN;
+    slope = slope.sub(endedSlope);
+    bias = bias.sub(endedSlope.muln(p));
+    endOffset = endOffset.sub(history.endOffsetChanges[p] as BN);
+  }
+  if (slope.isZero()) {
+    return direct;
+  }
+  const slopeBalance = bias
+    .sub(slope.muln(period))
+    .mul(getHistoryGeometry(history).periodSeconds)
+    .div(history.maxStakeDuration)
+    .sub(endOffset);
+  return direct.add(slopeBalance);
+};
+
+/**
+ * Calculates the veBalance of an escrow at every period of the era.
+ * @param history
+ * @returns
+ */
+export const calculateEscrowVeBalances = (
+  history: EscrowHistoryData
+): BN[] =>
+  Array(ERA_NUM_PERIODS)
+    .fill(null)
+    .map((_, period) => calculateEscrowVeBalance(history, period));
+
+/**
+ * Calculates the total veBalance of a locker at every period of the era.
+ * @param history
+ * @returns
+ */
+export const calculateLockerVeBalances = (
+  history: LockerHistoryData
+): BN[] =>
+  Array(ERA_NUM_PERIODS)
+    .fill(null)
+    .map((_, period) => calculateLockerVeBalance(history, period));
@@ -1,2 +1,5 @@
+export * from "./airdrop";
+export * from "./balances";
 export * from "./pda";
 export * from "./snapshots";
+export * from "./views";
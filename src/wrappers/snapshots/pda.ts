@@ -9,6 +9,12 @@ const encodeU16 = (num: number): Buffer => {
   return buf;
 };
 
+const encodeU8 = (num: number): Buffer => {
+  const buf = Buffer.alloc(1);
+  buf.writeUInt8(num);
+  return buf;
+};
+
 /**
  * Finds the address of an EscrowHistory.
  */
@@ -26,6 +32,35 @@ export const findEscrowHistoryAddress = async (
   );
 };
 
+/**
+ * Finds the address of the CompactEscrowHistory of an era.
+ */
+export const findCompactEscrowHistoryAddress = async (
+  escrow: PublicKey,
+  era: number
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("CompactEscrowHistory"),
+      escrow.toBuffer(),
+      encodeU16(era),
+    ],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of the SnapshotConfig of a Locker.
+ */
+export const findSnapshotConfigAddress = async (
+  locker: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("SnapshotConfig"), locker.toBuffer()],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
 /**
  * Finds the address of a LockerHistory.
  */
@@ -42,3 +77,234 @@ export const findLockerHistoryAddress = async (
     SNAPSHOTS_ADDRESSES.Snapshots
   );
 };
+
+/**
+ * Finds the address of a LockerHistoryShard.
+ */
+export const findLockerHistoryShardAddress = async (
+  lockerHistory: PublicKey,
+  index: number
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("LockerHistoryShard"),
+      lockerHistory.toBuffer(),
+      encodeU16(index),
+    ],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of the CumulativeBalances of a LockerHistory or EscrowHistory.
+ */
+export const findCumulativeBalancesAddress = async (
+  history: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("CumulativeBalances"), history.toBuffer()],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of a Distributor.
+ */
+export const findDistributorAddress = async (
+  locker: PublicKey,
+  mint: PublicKey,
+  authority: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("Distributor"),
+      locker.toBuffer(),
+      mint.toBuffer(),
+      authority.toBuffer(),
+    ],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of the token vault of a Distributor.
+ */
+export const findDistributorVaultAddress = async (
+  distributor: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("DistributorVault"), distributor.toBuffer()],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of a DistributorPeriod.
+ */
+export const findDistributorPeriodAddress = async (
+  distributor: PublicKey,
+  era: number,
+  period: number
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("DistributorPeriod"),
+      distributor.toBuffer(),
+      encodeU16(era),
+      encodeU8(period),
+    ],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of a ClaimReceipt.
+ */
+export const findClaimReceiptAddress = async (
+  distributorPeriod: PublicKey,
+  escrow: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("ClaimReceipt"),
+      distributorPeriod.toBuffer(),
+      escrow.toBuffer(),
+    ],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of the VoterWeightRegistrar of a realm.
+ */
+export const findVoterWeightRegistrarAddress = async (
+  realm: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("VoterWeightRegistrar"), realm.toBuffer()],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of the ProposalSnapshot of a proposal.
+ */
+export const findProposalSnapshotAddress = async (
+  registrar: PublicKey,
+  proposal: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("ProposalSnapshot"),
+      registrar.toBuffer(),
+      proposal.toBuffer(),
+    ],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of the VoterWeightRecord of a governing token owner.
+ */
+export const findVoterWeightRecordAddress = async (
+  registrar: PublicKey,
+  governingTokenOwner: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("VoterWeightRecord"),
+      registrar.toBuffer(),
+      governingTokenOwner.toBuffer(),
+    ],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of the GovernProposalSnapshot of a Govern proposal.
+ */
+export const findGovernProposalSnapshotAddress = async (
+  proposal: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("GovernProposalSnapshot"), proposal.toBuffer()],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of the VoteWeightReceipt of an Escrow.
+ */
+export const findVoteWeightReceiptAddress = async (
+  snapshot: PublicKey,
+  escrow: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("VoteWeightReceipt"),
+      snapshot.toBuffer(),
+      escrow.toBuffer(),
+    ],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of a SnapshotAirdrop.
+ */
+export const findSnapshotAirdropAddress = async ({
+  locker,
+  era,
+  period,
+  mint,
+  authority,
+}: {
+  locker: PublicKey;
+  era: number;
+  period: number;
+  mint: PublicKey;
+  authority: PublicKey;
+}): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("SnapshotAirdrop"),
+      locker.toBuffer(),
+      encodeU16(era),
+      encodeU8(period),
+      mint.toBuffer(),
+      authority.toBuffer(),
+    ],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of the token vault of a SnapshotAirdrop.
+ */
+export const findSnapshotAirdropVaultAddress = async (
+  airdrop: PublicKey
+): Promise<[PublicKey, number]> => {
+  return await PublicKey.findProgramAddress(
+    [utils.bytes.utf8.encode("SnapshotAirdropVault"), airdrop.toBuffer()],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
+
+/**
+ * Finds the address of a SnapshotAirdropBitmap.
+ */
+export const findSnapshotAirdropBitmapAddress = async (
+  airdrop: PublicKey,
+  page: number
+): Promise<[PublicKey, number]> => {
+  const pageBuf = Buffer.alloc(4);
+  pageBuf.writeUInt32LE(page);
+  return await PublicKey.findProgramAddress(
+    [
+      utils.bytes.utf8.encode("SnapshotAirdropBitmap"),
+      airdrop.toBuffer(),
+      pageBuf,
+    ],
+    SNAPSHOTS_ADDRESSES.Snapshots
+  );
+};
@@ -1,15 +1,59 @@
 import type { TransactionEnvelope } from "@saberhq/solana-contrib";
-import type { PublicKey } from "@solana/web3.js";
-import { SystemProgram } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@saberhq/token-utils";
+import type { PublicKey, TransactionInstruction } from "@solana/web3.js";
+import { SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
+import BN from "bn.js";
 import { findEscrowAddress } from "@tribecahq/tribeca-sdk";
 
+import { calculateEra } from "../../math";
 import type {
+  ClaimReceiptData,
+  CompactEscrowHistoryData,
+  CumulativeBalancesData,
+  DistributorPeriodData,
   EscrowHistoryData,
+  GovernProposalSnapshotData,
   LockerHistoryData,
+  LockerHistoryShardData,
+  SnapshotAirdropBitmapData,
+  SnapshotAirdropData,
+  SnapshotConfigData,
   SnapshotsProgram,
+  VoteWeightReceiptData,
 } from "../../programs/snapshots";
 import type { SnapshotsSDK } from "../../sdk";
-import { findEscrowHistoryAddress, findLockerHistoryAddress } from ".";
+import type {
+  AirdropClaim,
+  EscrowShare,
+  LockerBalance,
+  PeriodQuery,
+  PeriodRangeQuery,
+} from ".";
+import {
+  calculateAirdropBitmapPage,
+  decodeAverageEscrowBalance,
+  decodeAverageLockerBalance,
+  decodeAverageShare,
+  decodeEscrowBalance,
+  decodeEscrowShare,
+  decodeLockerBalance,
+  findClaimReceiptAddress,
+  findCompactEscrowHistoryAddress,
+  findCumulativeBalancesAddress,
+  findDistributorAddress,
+  findDistributorPeriodAddress,
+  findDistributorVaultAddress,
+  findEscrowHistoryAddress,
+  findGovernProposalSnapshotAddress,
+  findLockerHistoryAddress,
+  findLockerHistoryShardAddress,
+  findSnapshotAirdropAddress,
+  findSnapshotAirdropBitmapAddress,
+  findSnapshotAirdropVaultAddress,
+  findSnapshotConfigAddress,
+  findVoteWeightReceiptAddress,
+  parseReturnData,
+} from ".";
 
 /**
  * Handles interacting with the Snapshots program.
@@ -37,6 +81,320 @@ export class SnapshotsWrapper {
     return await this.program.account.escrowHistory.fetchNullable(key);
   }
 
+  async fetchCompactEscrowHistory(
+    key: PublicKey
+  ): Promise<CompactEscrowHistoryData | null> {
+    return await this.program.account.compactEscrowHistory.fetchNullable(key);
+  }
+
+  async fetchLockerHistoryShard(
+    key: PublicKey
+  ): Promise<LockerHistoryShardData | null> {
+    return await this.program.account.lockerHistoryShard.fetchNullable(key);
+  }
+
+  async fetchCumulativeBalances(
+    key: PublicKey
+  ): Promise<CumulativeBalancesData | null> {
+    return await this.program.account.cumulativeBalances.fetchNullable(key);
+  }
+
+  async fetchSnapshotConfig(
+    key: PublicKey
+  ): Promise<SnapshotConfigData | null> {
+    return await this.program.account.snapshotConfig.fetchNullable(key);
+  }
+
+  async fetchGovernProposalSnapshot(
+    key: PublicKey
+  ): Promise<GovernProposalSnapshotData | null> {
+    return await this.program.account.governProposalSnapshot.fetchNullable(
+      key
+    );
+  }
+
+  async fetchVoteWeightReceipt(
+    key: PublicKey
+  ): Promise<VoteWeightReceiptData | null> {
+    return await this.program.account.voteWeightReceipt.fetchNullable(key);
+  }
+
+  async fetchDistributorPeriod(
+    key: PublicKey
+  ): Promise<DistributorPeriodData | null> {
+    return await this.program.account.distributorPeriod.fetchNullable(key);
+  }
+
+  async fetchClaimReceipt(key: PublicKey): Promise<ClaimReceiptData | null> {
+    return await this.program.account.claimReceipt.fetchNullable(key);
+  }
+
+  async fetchSnapshotAirdrop(
+    key: PublicKey
+  ): Promise<SnapshotAirdropData | null> {
+    return await this.program.account.snapshotAirdrop.fetchNullable(key);
+  }
+
+  async fetchSnapshotAirdropBitmap(
+    key: PublicKey
+  ): Promise<SnapshotAirdropBitmapData | null> {
+    return await this.program.account.snapshotAirdropBitmap.fetchNullable(
+      key
+    );
+  }
+
+  /**
+   * Simulates a view instruction and returns the data that it returned.
+   * @returns
+   */
+  async simulateView(ix: TransactionInstruction): Promise<Buffer> {
+    const { value } = await this.provider.newTX([ix]).simulate();
+    if (value.err) {
+      throw new Error(`view failed: ${JSON.stringify(value.err)}`);
+    }
+    const data = parseReturnData(this.program.programId, value.logs ?? []);
+    if (!data) {
+      throw new Error("view returned no data");
+    }
+    return data;
+  }
+
+  /**
+   * Fetches the veToken balance of an EscrowHistory or CompactEscrowHistory
+   * at a period.
+   * @returns
+   */
+  async getEscrowBalance({
+    escrowHistory,
+    query,
+  }: {
+    escrowHistory: PublicKey;
+    query: PeriodQuery;
+  }): Promise<BN> {
+    return decodeEscrowBalance(
+      await this.simulateView(
+        this.program.instruction.getEscrowBalance(query, {
+          accounts: {
+            escrowHistory,
+          },
+        })
+      )
+    );
+  }
+
+  /**
+   * Fetches the total veToken balance of a LockerHistory at a period.
+   * @returns
+   */
+  async getLockerBalance({
+    locker,
+    era,
+    query,
+  }: {
+    locker: PublicKey;
+    era: number;
+    query: PeriodQuery;
+  }): Promise<LockerBalance> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    return decodeLockerBalance(
+      await this.simulateView(
+        this.program.instruction.getLockerBalance(query, {
+          accounts: {
+            lockerHistory,
+          },
+        })
+      )
+    );
+  }
+
+  /**
+   * Fetches the share of an Escrow in the total of its locker at a period.
+   * @returns
+   */
+  async getShare({
+    locker,
+    escrow,
+    escrowHistory,
+    era,
+    query,
+  }: {
+    locker: PublicKey;
+    escrow: PublicKey;
+    /**
+     * EscrowHistory or CompactEscrowHistory of the era.
+     */
+    escrowHistory: PublicKey;
+    era: number;
+    query: PeriodQuery;
+  }): Promise<EscrowShare> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    return decodeEscrowShare(
+      await this.simulateView(
+        this.program.instruction.getShare(query, {
+          accounts: {
+            escrow,
+            lockerHistory,
+            escrowHistory,
+          },
+        })
+      )
+    );
+  }
+
+  /**
+   * Fetches the time-weighted average veToken balance of an escrow over a
+   * range of periods.
+   * @returns
+   */
+  async getAverageEscrowBalance({
+    escrowHistories,
+    query,
+    cumulativeBalances = [],
+  }: {
+    /**
+     * EscrowHistory or CompactEscrowHistory of each era of the range, in order.
+     */
+    escrowHistories: PublicKey[];
+    query: PeriodRangeQuery;
+    /**
+     * CumulativeBalances of any of the histories, read instead of the
+     * balances of the periods that they have recorded.
+     */
+    cumulativeBalances?: PublicKey[];
+  }): Promise<BN> {
+    const [escrowHistory, ...nextEscrowHistories] = escrowHistories;
+    if (!escrowHistory) {
+      throw new Error("no escrow history");
+    }
+    const remainingAccounts = [
+      ...nextEscrowHistories,
+      ...cumulativeBalances,
+    ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: false }));
+    return decodeAverageEscrowBalance(
+      await this.simulateView(
+        this.program.instruction.getAverageEscrowBalance(query, {
+          accounts: {
+            escrowHistory,
+          },
+          remainingAccounts,
+        })
+      )
+    );
+  }
+
+  /**
+   * Fetches the time-weighted average total veToken balance of a locker over
+   * a range of periods.
+   * @returns
+   */
+  async getAverageLockerBalance({
+    locker,
+    firstEra,
+    numEras,
+    query,
+    cumulativeBalances = [],
+  }: {
+    locker: PublicKey;
+    /**
+     * Era of the first period of the range.
+     */
+    firstEra: number;
+    /**
+     * Number of eras spanned by the range.
+     */
+    numEras: number;
+    query: PeriodRangeQuery;
+    /**
+     * CumulativeBalances of any of the histories, read instead of the
+     * totals of the periods that they have recorded.
+     */
+    cumulativeBalances?: PublicKey[];
+  }): Promise<LockerBalance> {
+    const lockerHistories: PublicKey[] = [];
+    for (let era = firstEra; era < firstEra + numEras; era++) {
+      const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+      lockerHistories.push(lockerHistory);
+    }
+    const [lockerHistory, ...nextLockerHistories] = lockerHistories;
+    if (!lockerHistory) {
+      throw new Error("no locker history");
+    }
+    const remainingAccounts = [
+      ...nextLockerHistories,
+      ...cumulativeBalances,
+    ].map((pubkey) => ({ pubkey, isSigner: false, isWritable: false }));
+    return decodeAverageLockerBalance(
+      await this.simulateView(
+        this.program.instruction.getAverageLockerBalance(query, {
+          accounts: {
+            lockerHistory,
+          },
+          remainingAccounts,
+        })
+      )
+    );
+  }
+
+  /**
+   * Fetches the time-weighted average share of an Escrow in the total of its
+   * locker over a range of periods.
+   * @returns
+   */
+  async getAverageShare({
+    locker,
+    escrow,
+    escrowHistories,
+    firstEra,
+    query,
+    cumulativeBalances = [],
+  }: {
+    locker: PublicKey;
+    escrow: PublicKey;
+    /**
+     * EscrowHistory or CompactEscrowHistory of each era of the range, in order.
+     */
+    escrowHistories: PublicKey[];
+    /**
+     * Era of the first period of the range.
+     */
+    firstEra: number;
+    query: PeriodRangeQuery;
+    /**
+     * CumulativeBalances of any of the histories, read instead of the
+     * balances of the periods that they have recorded.
+     */
+    cumulativeBalances?: PublicKey[];
+  }): Promise<EscrowShare> {
+    const historyPairs: PublicKey[][] = [];
+    for (const [i, escrowHistory] of escrowHistories.entries()) {
+      const [lockerHistory] = await findLockerHistoryAddress(
+        locker,
+        firstEra + i
+      );
+      historyPairs.push([lockerHistory, escrowHistory]);
+    }
+    const [firstPair, ...nextPairs] = historyPairs;
+    const [lockerHistory, escrowHistory] = firstPair ?? [];
+    if (!lockerHistory || !escrowHistory) {
+      throw new Error("no escrow history");
+    }
+    const remainingAccounts = [...nextPairs.flat(), ...cumulativeBalances].map(
+      (pubkey) => ({ pubkey, isSigner: false, isWritable: false })
+    );
+    return decodeAverageShare(
+      await this.simulateView(
+        this.program.instruction.getAverageShare(query, {
+          accounts: {
+            escrow,
+            lockerHistory,
+            escrowHistory,
+          },
+          remainingAccounts,
+        })
+      )
+    );
+  }
+
   /**
    * Creates a Locker History.
    * @returns
@@ -44,22 +402,33 @@ export class SnapshotsWrapper {
   async createLockerHistory({
     locker,
     era,
+    retentionSeconds = 0,
   }: {
     locker: PublicKey;
     era: number;
+    /**
+     * Seconds after the end of the era before the history may be closed.
+     */
+    retentionSeconds?: number;
   }): Promise<{ lockerHistory: PublicKey; tx: TransactionEnvelope }> {
     const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [snapshotConfig] = await findSnapshotConfigAddress(locker);
     return {
       lockerHistory,
       tx: this.provider.newTX([
-        this.program.instruction.createLockerHistory(era, {
-          accounts: {
-            locker,
-            lockerHistory,
-            payer: this.provider.wallet.publicKey,
-            systemProgram: SystemProgram.programId,
-          },
-        }),
+        this.program.instruction.createLockerHistory(
+          era,
+          new BN(retentionSeconds),
+          {
+            accounts: {
+              locker,
+              snapshotConfig,
+              lockerHistory,
+              payer: this.provider.wallet.publicKey,
+              systemProgram: SystemProgram.programId,
+            },
+          }
+        ),
       ]),
     };
   }
@@ -69,28 +438,229 @@ export class SnapshotsWrapper {
    * @returns
    */
   async createEscrowHistory({
+    locker,
     escrow,
     era,
+    retentionSeconds = 0,
   }: {
+    /**
+     * Locker of the escrow, whose geometry the history records.
+     */
+    locker: PublicKey;
     escrow: PublicKey;
     era: number;
+    /**
+     * Seconds after the end of the era before the history may be closed.
+     */
+    retentionSeconds?: number;
   }): Promise<{ escrowHistory: PublicKey; tx: TransactionEnvelope }> {
+    const [snapshotConfig] = await findSnapshotConfigAddress(locker);
     const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+    const [compactEscrowHistory] = await findCompactEscrowHistoryAddress(
+      escrow,
+      era
+    );
     return {
       escrowHistory,
       tx: this.provider.newTX([
-        this.program.instruction.createEscrowHistory(era, {
+        this.program.instruction.createEscrowHistory(
+          era,
+          new BN(retentionSeconds),
+          {
+            accounts: {
+              escrow,
+              snapshotConfig,
+              compactEscrowHistory,
+              escrowHistory,
+              payer: this.provider.wallet.publicKey,
+              systemProgram: SystemProgram.programId,
+            },
+          }
+        ),
+      ]),
+    };
+  }
+
+  /**
+   * Creates the Escrow Histories of `numEras` consecutive eras for each of the given escrows
+   * of a locker, skipping those that already exist.
+   * @returns
+   */
+  async createEscrowHistories({
+    locker,
+    escrows,
+    firstEra,
+    numEras = 1,
+    retentionSeconds = 0,
+  }: {
+    locker: PublicKey;
+    escrows: PublicKey[];
+    firstEra: number;
+    numEras?: number;
+    /**
+     * Seconds after the end of the era before the histories may be closed.
+     */
+    retentionSeconds?: number;
+  }): Promise<TransactionEnvelope> {
+    const [snapshotConfig] = await findSnapshotConfigAddress(locker);
+    const remainingAccounts = [];
+    for (const escrow of escrows) {
+      remainingAccounts.push({
+        pubkey: escrow,
+        isSigner: false,
+        isWritable: false,
+      });
+      for (let era = firstEra; era < firstEra + numEras; era++) {
+        const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+        const [compactEscrowHistory] = await findCompactEscrowHistoryAddress(
+          escrow,
+          era
+        );
+        remainingAccounts.push(
+          { pubkey: escrowHistory, isSigner: false, isWritable: true },
+          { pubkey: compactEscrowHistory, isSigner: false, isWritable: false }
+        );
+      }
+    }
+    return this.provider.newTX([
+      this.program.instruction.createEscrowHistories(
+        firstEra,
+        numEras,
+        new BN(retentionSeconds),
+        {
           accounts: {
-            escrow,
-            escrowHistory,
+            locker,
+            snapshotConfig,
             payer: this.provider.wallet.publicKey,
             systemProgram: SystemProgram.programId,
           },
-        }),
+          remainingAccounts,
+        }
+      ),
+    ]);
+  }
+
+  /**
+   * Creates a Compact Escrow History, which costs less rent than an Escrow History.
+   * @returns
+   */
+  async createCompactEscrowHistory({
+    locker,
+    escrow,
+    era,
+    retentionSeconds = 0,
+  }: {
+    /**
+     * Locker of the escrow, whose geometry the history records.
+     */
+    locker: PublicKey;
+    escrow: PublicKey;
+    era: number;
+    /**
+     * Seconds after the end of the era before the history may be closed.
+     */
+    retentionSeconds?: number;
+  }): Promise<{ compactEscrowHistory: PublicKey; tx: TransactionEnvelope }> {
+    const [snapshotConfig] = await findSnapshotConfigAddress(locker);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+    const [compactEscrowHistory] = await findCompactEscrowHistoryAddress(
+      escrow,
+      era
+    );
+    return {
+      compactEscrowHistory,
+      tx: this.provider.newTX([
+        this.program.instruction.createCompactEscrowHistory(
+          era,
+          new BN(retentionSeconds),
+          {
+            accounts: {
+              escrow,
+              snapshotConfig,
+              escrowHistory,
+              compactEscrowHistory,
+              payer: this.provider.wallet.publicKey,
+              systemProgram: SystemProgram.programId,
+            },
+          }
+        ),
       ]),
     };
   }
 
+  /**
+   * Replaces an Escrow History paid for by the wallet with a Compact Escrow History.
+   * @returns
+   */
+  async migrateEscrowHistory({
+    escrow,
+    era,
+  }: {
+    escrow: PublicKey;
+    era: number;
+  }): Promise<TransactionEnvelope> {
+    const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+    const [compactEscrowHistory] = await findCompactEscrowHistoryAddress(
+      escrow,
+      era
+    );
+    return this.provider.newTX([
+      this.program.instruction.migrateEscrowHistory(era, {
+        accounts: {
+          escrow,
+          escrowHistory,
+          compactEscrowHistory,
+          payer: this.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Closes a Locker History, refunding its rent to the payer.
+   * @returns
+   */
+  async closeLockerHistory({
+    locker,
+    era,
+  }: {
+    locker: PublicKey;
+    era: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    return this.provider.newTX([
+      this.program.instruction.closeLockerHistory({
+        accounts: {
+          lockerHistory,
+          payer: this.provider.wallet.publicKey,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Closes an Escrow History, refunding its rent to the payer.
+   * @returns
+   */
+  async closeEscrowHistory({
+    escrow,
+    era,
+  }: {
+    escrow: PublicKey;
+    era: number;
+  }): Promise<TransactionEnvelope> {
+    const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+    return this.provider.newTX([
+      this.program.instruction.closeEscrowHistory({
+        accounts: {
+          escrowHistory,
+          payer: this.provider.wallet.publicKey,
+        },
+      }),
+    ]);
+  }
+
   /**
    * Synchronizes an EscrowHistory.
    * @returns
@@ -118,4 +688,1017 @@ export class SnapshotsWrapper {
       }),
     ]);
   }
+
+  /**
+   * Synchronizes a CompactEscrowHistory.
+   * @returns
+   */
+  async syncCompact({
+    locker,
+    owner,
+    era,
+  }: {
+    locker: PublicKey;
+    owner: PublicKey;
+    era: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [escrow] = await findEscrowAddress(locker, owner);
+    const [compactEscrowHistory] = await findCompactEscrowHistoryAddress(
+      escrow,
+      era
+    );
+    return this.provider.newTX([
+      this.program.instruction.syncCompact({
+        accounts: {
+          locker,
+          escrow,
+          lockerHistory,
+          compactEscrowHistory,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Synchronizes an EscrowHistory, creating it and its LockerHistory if they do not exist.
+   * @returns
+   */
+  async syncInitIfNeeded({
+    locker,
+    owner,
+    era,
+    retentionSeconds = 0,
+  }: {
+    locker: PublicKey;
+    owner: PublicKey;
+    era: number;
+    /**
+     * Seconds after the end of the era before a created history may be closed.
+     */
+    retentionSeconds?: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [escrow] = await findEscrowAddress(locker, owner);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+    const [compactEscrowHistory] = await findCompactEscrowHistoryAddress(
+      escrow,
+      era
+    );
+    const [snapshotConfig] = await findSnapshotConfigAddress(locker);
+    return this.provider.newTX([
+      this.program.instruction.syncInitIfNeeded(era, new BN(retentionSeconds), {
+        accounts: {
+          locker,
+          snapshotConfig,
+          escrow,
+          lockerHistory,
+          compactEscrowHistory,
+          escrowHistory,
+          payer: this.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Synchronizes the EscrowHistory of every given era in a single instruction.
+   * @returns
+   */
+  async syncAllEras({
+    locker,
+    owner,
+    eras,
+  }: {
+    locker: PublicKey;
+    owner: PublicKey;
+    eras: number[];
+  }): Promise<TransactionEnvelope> {
+    const [escrow] = await findEscrowAddress(locker, owner);
+    const remainingAccounts = [];
+    for (const era of eras) {
+      const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+      const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+      remainingAccounts.push(
+        { pubkey: lockerHistory, isSigner: false, isWritable: true },
+        { pubkey: escrowHistory, isSigner: false, isWritable: true }
+      );
+    }
+    return this.provider.newTX([
+      this.program.instruction.syncAllEras({
+        accounts: {
+          locker,
+          escrow,
+        },
+        remainingAccounts,
+      }),
+    ]);
+  }
+
+  /**
+   * Synchronizes the EscrowHistory of each of the given owners in a single instruction.
+   * Escrows that cannot be synced are skipped.
+   * @returns
+   */
+  async syncMany({
+    locker,
+    owners,
+    era,
+  }: {
+    locker: PublicKey;
+    owners: PublicKey[];
+    era: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const remainingAccounts = [];
+    for (const owner of owners) {
+      const [escrow] = await findEscrowAddress(locker, owner);
+      const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+      remainingAccounts.push(
+        { pubkey: escrow, isSigner: false, isWritable: false },
+        { pubkey: escrowHistory, isSigner: false, isWritable: true }
+      );
+    }
+    return this.provider.newTX([
+      this.program.instruction.syncMany({
+        accounts: {
+          locker,
+          lockerHistory,
+        },
+        remainingAccounts,
+      }),
+    ]);
+  }
+
+  /**
+   * Materializes the balances of all periods of a LockerHistory that have started.
+   * @returns
+   */
+  async checkpointLockerHistory({
+    locker,
+    era,
+  }: {
+    locker: PublicKey;
+    era: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    return this.provider.newTX([
+      this.program.instruction.checkpointLockerHistory({
+        accounts: {
+          lockerHistory,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Resets a LockerHistory after the Locker parameters have changed.
+   * @returns
+   */
+  async resetLockerHistory({
+    locker,
+    era,
+  }: {
+    locker: PublicKey;
+    era: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    return this.provider.newTX([
+      this.program.instruction.resetLockerHistory({
+        accounts: {
+          locker,
+          lockerHistory,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Resets a LockerHistoryShard after the Locker parameters have changed.
+   * @returns
+   */
+  async resetLockerHistoryShard({
+    locker,
+    era,
+    index,
+  }: {
+    locker: PublicKey;
+    era: number;
+    index: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [shard] = await findLockerHistoryShardAddress(lockerHistory, index);
+    return this.provider.newTX([
+      this.program.instruction.resetLockerHistoryShard({
+        accounts: {
+          locker,
+          lockerHistory,
+          shard,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Creates a Locker History Shard.
+   * @returns
+   */
+  async createLockerHistoryShard({
+    locker,
+    era,
+    index,
+  }: {
+    locker: PublicKey;
+    era: number;
+    index: number;
+  }): Promise<{ shard: PublicKey; tx: TransactionEnvelope }> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [shard] = await findLockerHistoryShardAddress(lockerHistory, index);
+    return {
+      shard,
+      tx: this.provider.newTX([
+        this.program.instruction.createLockerHistoryShard(index, {
+          accounts: {
+            lockerHistory,
+            shard,
+            payer: this.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+        }),
+      ]),
+    };
+  }
+
+  /**
+   * Synchronizes an EscrowHistory into a Locker History Shard.
+   * @returns
+   */
+  async syncSharded({
+    locker,
+    owner,
+    era,
+    index,
+  }: {
+    locker: PublicKey;
+    owner: PublicKey;
+    era: number;
+    index: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [shard] = await findLockerHistoryShardAddress(lockerHistory, index);
+    const [escrow] = await findEscrowAddress(locker, owner);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+    return this.provider.newTX([
+      this.program.instruction.syncSharded({
+        accounts: {
+          locker,
+          escrow,
+          lockerHistory,
+          shard,
+          escrowHistory,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Merges the started periods of a Locker History Shard into its Locker History.
+   * @returns
+   */
+  async mergeLockerHistoryShard({
+    locker,
+    era,
+    index,
+  }: {
+    locker: PublicKey;
+    era: number;
+    index: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [shard] = await findLockerHistoryShardAddress(lockerHistory, index);
+    return this.provider.newTX([
+      this.program.instruction.mergeLockerHistoryShard({
+        accounts: {
+          lockerHistory,
+          shard,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Closes a fully merged Locker History Shard, refunding its rent to the payer.
+   * @returns
+   */
+  async closeLockerHistoryShard({
+    locker,
+    era,
+    index,
+  }: {
+    locker: PublicKey;
+    era: number;
+    index: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [shard] = await findLockerHistoryShardAddress(lockerHistory, index);
+    return this.provider.newTX([
+      this.program.instruction.closeLockerHistoryShard({
+        accounts: {
+          lockerHistory,
+          shard,
+          payer: this.provider.wallet.publicKey,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Creates the CumulativeBalances of a LockerHistory.
+   * @returns
+   */
+  async createLockerCumulativeBalances({
+    locker,
+    era,
+  }: {
+    locker: PublicKey;
+    era: number;
+  }): Promise<{ cumulativeBalances: PublicKey; tx: TransactionEnvelope }> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [cumulativeBalances] = await findCumulativeBalancesAddress(
+      lockerHistory
+    );
+    return {
+      cumulativeBalances,
+      tx: this.provider.newTX([
+        this.program.instruction.createLockerCumulativeBalances({
+          accounts: {
+            lockerHistory,
+            cumulativeBalances,
+            payer: this.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+        }),
+      ]),
+    };
+  }
+
+  /**
+   * Creates the CumulativeBalances of an EscrowHistory or CompactEscrowHistory.
+   * @returns
+   */
+  async createEscrowCumulativeBalances({
+    locker,
+    escrow,
+    escrowHistory,
+    era,
+  }: {
+    locker: PublicKey;
+    escrow: PublicKey;
+    /**
+     * EscrowHistory or CompactEscrowHistory of the era.
+     */
+    escrowHistory: PublicKey;
+    era: number;
+  }): Promise<{ cumulativeBalances: PublicKey; tx: TransactionEnvelope }> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [cumulativeBalances] = await findCumulativeBalancesAddress(
+      escrowHistory
+    );
+    return {
+      cumulativeBalances,
+      tx: this.provider.newTX([
+        this.program.instruction.createEscrowCumulativeBalances({
+          accounts: {
+            escrow,
+            escrowHistory,
+            lockerHistory,
+            cumulativeBalances,
+            payer: this.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+        }),
+      ]),
+    };
+  }
+
+  /**
+   * Records the sums of all final periods of a LockerHistory in its
+   * CumulativeBalances.
+   * @returns
+   */
+  async checkpointLockerCumulativeBalances({
+    locker,
+    era,
+  }: {
+    locker: PublicKey;
+    era: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [cumulativeBalances] = await findCumulativeBalancesAddress(
+      lockerHistory
+    );
+    return this.provider.newTX([
+      this.program.instruction.checkpointLockerCumulativeBalances({
+        accounts: {
+          lockerHistory,
+          cumulativeBalances,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Records the sums of all materialized or final periods of an EscrowHistory
+   * or CompactEscrowHistory in its CumulativeBalances.
+   * @returns
+   */
+  async checkpointEscrowCumulativeBalances({
+    locker,
+    escrow,
+    escrowHistory,
+    era,
+  }: {
+    locker: PublicKey;
+    escrow: PublicKey;
+    /**
+     * EscrowHistory or CompactEscrowHistory of the era.
+     */
+    escrowHistory: PublicKey;
+    era: number;
+  }): Promise<TransactionEnvelope> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [cumulativeBalances] = await findCumulativeBalancesAddress(
+      escrowHistory
+    );
+    return this.provider.newTX([
+      this.program.instruction.checkpointEscrowCumulativeBalances({
+        accounts: {
+          escrow,
+          escrowHistory,
+          lockerHistory,
+          cumulativeBalances,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Creates the instruction that sets the period length and origin of the
+   * histories of a Locker.
+   *
+   * The instruction must be executed by the smart wallet of the Governor,
+   * before the LockerHistory of the current era is created.
+   * @returns
+   */
+  async createSnapshotConfig({
+    locker,
+    governor,
+    smartWallet,
+    periodSeconds,
+    originTs,
+  }: {
+    locker: PublicKey;
+    governor: PublicKey;
+    smartWallet: PublicKey;
+    periodSeconds: number;
+    /**
+     * Unix timestamp at which era 0 starts.
+     */
+    originTs: number;
+  }): Promise<{
+    snapshotConfig: PublicKey;
+    instruction: TransactionInstruction;
+  }> {
+    const [snapshotConfig] = await findSnapshotConfigAddress(locker);
+    const [lockerHistory] = await findLockerHistoryAddress(
+      locker,
+      calculateEra(new Date())
+    );
+    return {
+      snapshotConfig,
+      instruction: this.program.instruction.createSnapshotConfig(
+        new BN(periodSeconds),
+        new BN(originTs),
+        {
+          accounts: {
+            locker,
+            governor,
+            smartWallet,
+            lockerHistory,
+            snapshotConfig,
+            payer: this.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+        }
+      ),
+    };
+  }
+
+  /**
+   * Records the voting power at the period a Govern proposal was created.
+   * @returns
+   */
+  async createGovernProposalSnapshot({
+    locker,
+    proposal,
+    era,
+  }: {
+    locker: PublicKey;
+    proposal: PublicKey;
+    /**
+     * Era containing the creation of the proposal.
+     */
+    era: number;
+  }): Promise<{ snapshot: PublicKey; tx: TransactionEnvelope }> {
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [snapshotConfig] = await findSnapshotConfigAddress(locker);
+    const [snapshot] = await findGovernProposalSnapshotAddress(proposal);
+    return {
+      snapshot,
+      tx: this.provider.newTX([
+        this.program.instruction.createGovernProposalSnapshot({
+          accounts: {
+            locker,
+            proposal,
+            snapshotConfig,
+            lockerHistory,
+            snapshot,
+            payer: this.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+        }),
+      ]),
+    };
+  }
+
+  /**
+   * Records the voting power of an Escrow at a GovernProposalSnapshot.
+   * @returns
+   */
+  async createVoteWeightReceipt({
+    locker,
+    owner,
+    proposal,
+    era,
+  }: {
+    locker: PublicKey;
+    owner: PublicKey;
+    proposal: PublicKey;
+    /**
+     * Era of the GovernProposalSnapshot.
+     */
+    era: number;
+  }): Promise<{ receipt: PublicKey; tx: TransactionEnvelope }> {
+    const [snapshot] = await findGovernProposalSnapshotAddress(proposal);
+    const [escrow] = await findEscrowAddress(locker, owner);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+    const [receipt] = await findVoteWeightReceiptAddress(snapshot, escrow);
+    return {
+      receipt,
+      tx: this.provider.newTX([
+        this.program.instruction.createVoteWeightReceipt({
+          accounts: {
+            snapshot,
+            escrow,
+            escrowHistory,
+            receipt,
+            payer: this.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+        }),
+      ]),
+    };
+  }
+
+  /**
+   * Creates a Distributor of the given mint to the escrows of a Locker, with
+   * the provider wallet as its authority.
+   * @returns
+   */
+  async createDistributor({
+    locker,
+    mint,
+    claimSeconds,
+  }: {
+    locker: PublicKey;
+    mint: PublicKey;
+    /**
+     * Seconds after a period elapses during which its rewards may be claimed.
+     */
+    claimSeconds: number;
+  }): Promise<{ distributor: PublicKey; tx: TransactionEnvelope }> {
+    const authority = this.provider.wallet.publicKey;
+    const [snapshotConfig] = await findSnapshotConfigAddress(locker);
+    const [distributor] = await findDistributorAddress(
+      locker,
+      mint,
+      authority
+    );
+    const [vault] = await findDistributorVaultAddress(distributor);
+    return {
+      distributor,
+      tx: this.provider.newTX([
+        this.program.instruction.createDistributor(new BN(claimSeconds), {
+          accounts: {
+            locker,
+            snapshotConfig,
+            mint,
+            authority,
+            distributor,
+            vault,
+            payer: this.provider.wallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            rent: SYSVAR_RENT_PUBKEY,
+          },
+        }),
+      ]),
+    };
+  }
+
+  /**
+   * Creates a DistributorPeriod.
+   * @returns
+   */
+  async createDistributorPeriod({
+    locker,
+    mint,
+    authority = this.provider.wallet.publicKey,
+    era,
+    period,
+  }: {
+    locker: PublicKey;
+    mint: PublicKey;
+    /**
+     * Authority of the Distributor. Defaults to the provider wallet.
+     */
+    authority?: PublicKey;
+    era: number;
+    period: number;
+  }): Promise<{ distributorPeriod: PublicKey; tx: TransactionEnvelope }> {
+    const [distributor] = await findDistributorAddress(
+      locker,
+      mint,
+      authority
+    );
+    const [distributorPeriod] = await findDistributorPeriodAddress(
+      distributor,
+      era,
+      period
+    );
+    return {
+      distributorPeriod,
+      tx: this.provider.newTX([
+        this.program.instruction.createDistributorPeriod(era, period, {
+          accounts: {
+            distributor,
+            distributorPeriod,
+            payer: this.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+        }),
+      ]),
+    };
+  }
+
+  /**
+   * Funds a DistributorPeriod with tokens of the wallet.
+   * @returns
+   */
+  async fundDistributorPeriod({
+    locker,
+    mint,
+    authority = this.provider.wallet.publicKey,
+    era,
+    period,
+    source,
+    amount,
+  }: {
+    locker: PublicKey;
+    mint: PublicKey;
+    /**
+     * Authority of the Distributor. Defaults to the provider wallet.
+     */
+    authority?: PublicKey;
+    era: number;
+    period: number;
+    /**
+     * Token account of the wallet to fund the period with.
+     */
+    source: PublicKey;
+    amount: BN;
+  }): Promise<TransactionEnvelope> {
+    const [distributor] = await findDistributorAddress(
+      locker,
+      mint,
+      authority
+    );
+    const [distributorPeriod] = await findDistributorPeriodAddress(
+      distributor,
+      era,
+      period
+    );
+    const [vault] = await findDistributorVaultAddress(distributor);
+    return this.provider.newTX([
+      this.program.instruction.fundDistributorPeriod(amount, {
+        accounts: {
+          distributor,
+          distributorPeriod,
+          vault,
+          source,
+          sourceAuthority: this.provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Claims the rewards of a DistributorPeriod for the Escrow of an owner.
+   * @returns
+   */
+  async claim({
+    locker,
+    mint,
+    authority = this.provider.wallet.publicKey,
+    era,
+    period,
+    owner,
+    destination,
+  }: {
+    locker: PublicKey;
+    mint: PublicKey;
+    /**
+     * Authority of the Distributor. Defaults to the provider wallet.
+     */
+    authority?: PublicKey;
+    era: number;
+    period: number;
+    owner: PublicKey;
+    /**
+     * Token account of the owner to send the rewards to.
+     */
+    destination: PublicKey;
+  }): Promise<{ claimReceipt: PublicKey; tx: TransactionEnvelope }> {
+    const [distributor] = await findDistributorAddress(
+      locker,
+      mint,
+      authority
+    );
+    const [distributorPeriod] = await findDistributorPeriodAddress(
+      distributor,
+      era,
+      period
+    );
+    const [vault] = await findDistributorVaultAddress(distributor);
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [escrow] = await findEscrowAddress(locker, owner);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+    const [claimReceipt] = await findClaimReceiptAddress(
+      distributorPeriod,
+      escrow
+    );
+    return {
+      claimReceipt,
+      tx: this.provider.newTX([
+        this.program.instruction.claim({
+          accounts: {
+            distributor,
+            distributorPeriod,
+            lockerHistory,
+            escrow,
+            escrowHistory,
+            claimReceipt,
+            vault,
+            destination,
+            payer: this.provider.wallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          },
+        }),
+      ]),
+    };
+  }
+
+  /**
+   * Sends the unclaimed rewards of a DistributorPeriod of the provider wallet
+   * to a token account, once claims have closed.
+   * @returns
+   */
+  async clawbackDistributorPeriod({
+    locker,
+    mint,
+    era,
+    period,
+    destination,
+  }: {
+    locker: PublicKey;
+    mint: PublicKey;
+    era: number;
+    period: number;
+    /**
+     * Token account to send the unclaimed rewards to.
+     */
+    destination: PublicKey;
+  }): Promise<TransactionEnvelope> {
+    const authority = this.provider.wallet.publicKey;
+    const [distributor] = await findDistributorAddress(
+      locker,
+      mint,
+      authority
+    );
+    const [distributorPeriod] = await findDistributorPeriodAddress(
+      distributor,
+      era,
+      period
+    );
+    const [vault] = await findDistributorVaultAddress(distributor);
+    return this.provider.newTX([
+      this.program.instruction.clawbackDistributorPeriod({
+        accounts: {
+          distributor,
+          distributorPeriod,
+          vault,
+          authority,
+          destination,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+      }),
+    ]);
+  }
+
+  /**
+   * Creates a SnapshotAirdrop of the provider wallet, funded with the
+   * `maxTotalClaim` of the tree.
+   * @returns
+   */
+  async createSnapshotAirdrop({
+    locker,
+    era,
+    period,
+    mint,
+    source,
+    root,
+    maxTotalClaim,
+    maxNumNodes,
+    claimDeadline,
+  }: {
+    locker: PublicKey;
+    era: number;
+    period: number;
+    mint: PublicKey;
+    /**
+     * Token account of the wallet to fund the airdrop with.
+     */
+    source: PublicKey;
+    /**
+     * Root of the Merkle tree of claims.
+     */
+    root: Buffer;
+    maxTotalClaim: BN;
+    maxNumNodes: number;
+    /**
+     * Unix timestamp after which tokens can no longer be claimed.
+     */
+    claimDeadline: number;
+  }): Promise<{ airdrop: PublicKey; tx: TransactionEnvelope }> {
+    const authority = this.provider.wallet.publicKey;
+    const [snapshotConfig] = await findSnapshotConfigAddress(locker);
+    const [lockerHistory] = await findLockerHistoryAddress(locker, era);
+    const [airdrop] = await findSnapshotAirdropAddress({
+      locker,
+      era,
+      period,
+      mint,
+      authority,
+    });
+    const [vault] = await findSnapshotAirdropVaultAddress(airdrop);
+    return {
+      airdrop,
+      tx: this.provider.newTX([
+        this.program.instruction.createSnapshotAirdrop(
+          era,
+          period,
+          [...root],
+          maxTotalClaim,
+          new BN(maxNumNodes),
+          new BN(claimDeadline),
+          {
+            accounts: {
+              locker,
+              snapshotConfig,
+              lockerHistory,
+              mint,
+              airdrop,
+              vault,
+              authority,
+              source,
+              payer: this.provider.wallet.publicKey,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+              rent: SYSVAR_RENT_PUBKEY,
+            },
+          }
+        ),
+      ]),
+    };
+  }
+
+  /**
+   * Creates the SnapshotAirdropBitmap that tracks the claim with the
+   * given index.
+   * @returns
+   */
+  async createSnapshotAirdropBitmap({
+    airdrop,
+    index,
+  }: {
+    airdrop: PublicKey;
+    index: number;
+  }): Promise<{ bitmap: PublicKey; tx: TransactionEnvelope }> {
+    const page = calculateAirdropBitmapPage(index);
+    const [bitmap] = await findSnapshotAirdropBitmapAddress(airdrop, page);
+    return {
+      bitmap,
+      tx: this.provider.newTX([
+        this.program.instruction.createSnapshotAirdropBitmap(page, {
+          accounts: {
+            airdrop,
+            bitmap,
+            payer: this.provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          },
+        }),
+      ]),
+    };
+  }
+
+  /**
+   * Claims tokens from a SnapshotAirdrop. The claimant must sign.
+   * @returns
+   */
+  async claimSnapshotAirdrop({
+    airdrop,
+    claim: { index, claimant, amount },
+    proof,
+    destination,
+  }: {
+    airdrop: PublicKey;
+    claim: AirdropClaim;
+    proof: readonly Buffer[];
+    /**
+     * Token account of the claimant to send the tokens to.
+     */
+    destination: PublicKey;
+  }): Promise<TransactionEnvelope> {
+    const [bitmap] = await findSnapshotAirdropBitmapAddress(
+      airdrop,
+      calculateAirdropBitmapPage(index)
+    );
+    const [vault] = await findSnapshotAirdropVaultAddress(airdrop);
+    return this.provider.newTX([
+      this.program.instruction.claimSnapshotAirdrop(
+        new BN(index),
+        amount,
+        proof.map((node) => [...node]),
+        {
+          accounts: {
+            airdrop,
+            bitmap,
+            vault,
+            claimant,
+            destination,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+        }
+      ),
+    ]);
+  }
+
+  /**
+   * Sends the unclaimed tokens of a SnapshotAirdrop of the provider wallet
+   * to a token account.
+   * @returns
+   */
+  async clawbackSnapshotAirdrop({
+    airdrop,
+    destination,
+  }: {
+    airdrop: PublicKey;
+    destination: PublicKey;
+  }): Promise<TransactionEnvelope> {
+    const [vault] = await findSnapshotAirdropVaultAddress(airdrop);
+    return this.provider.newTX([
+      this.program.instruction.clawbackSnapshotAirdrop({
+        accounts: {
+          airdrop,
+          vault,
+          authority: this.provider.wallet.publicKey,
+          destination,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+      }),
+    ]);
+  }
 }
@@ -0,0 +1,110 @@
+import type { PublicKey } from "@solana/web3.js";
+import BN from "bn.js";
+
+/**
+ * Selects a period of an era, or the period containing a Unix timestamp.
+ */
+export type PeriodQuery =
+  | { period: { era: number; period: number } }
+  | { timestamp: { ts: BN } };
+
+/**
+ * Selects an inclusive range of periods, which may span several eras.
+ */
+export interface PeriodRangeQuery {
+  start: PeriodQuery;
+  end: PeriodQuery;
+}
+
+/**
+ * Total veToken balance of a locker at a period, returned by
+ * `get_locker_balance`, or its average over a range of periods, returned by
+ * `get_average_locker_balance`.
+ */
+export interface LockerBalance {
+  veBalance: BN;
+  veCount: BN;
+  /**
+   * True if the total can no longer change. For an average, true if none of
+   * the totals can change.
+   */
+  isFinal: boolean;
+}
+
+/**
+ * Share of an escrow in the total veToken balance of its locker at a period,
+ * returned by `get_share`, or its average over a range of periods, returned
+ * by `get_average_share`.
+ */
+export interface EscrowShare {
+  escrowVeBalance: BN;
+  lockerVeBalance: BN;
+  /**
+   * True if the total can no longer change. For an average, true if none of
+   * the totals can change.
+   */
+  isFinal: boolean;
+}
+
+/**
+ * Finds the data that a program returned in the logs of a transaction.
+ * @returns
+ */
+export const parseReturnData = (
+  programId: PublicKey,
+  logs: readonly string[]
+): Buffer | null => {
+  const prefix = `Program return: ${programId.toString()} `;
+  const log = [...logs].reverse().find((log) => log.startsWith(prefix));
+  return log ? Buffer.from(log.slice(prefix.length), "base64") : null;
+};
+
+const decodeU64 = (data: Buffer, offset: number): BN =>
+  new BN(data.subarray(offset, offset + 8), "le");
+
+/**
+ * Decodes the result of `get_escrow_balance`.
+ * @returns
+ */
+export const decodeEscrowBalance = (data: Buffer): BN => decodeU64(data, 0);
+
+/**
+ * Decodes the result of `get_locker_balance`.
+ * @returns
+ */
+export const decodeLockerBalance = (data: Buffer): LockerBalance => ({
+  veBalance: decodeU64(data, 0),
+  veCount: decodeU64(data, 8),
+  isFinal: data[16] === 1,
+});
+
+/**
+ * Decodes the result of `get_share`.
+ * @returns
+ */
+export const decodeEscrowShare = (data: Buffer): EscrowShare => ({
+  escrowVeBalance: decodeU64(data, 0),
+  lockerVeBalance: decodeU64(data, 8),
+  isFinal: data[16] === 1,
+});
+
+/**
+ * Decodes the result of `get_average_escrow_balance`, which is encoded like
+ * that of `get_escrow_balance`.
+ * @returns
+ */
+export const decodeAverageEscrowBalance = decodeEscrowBalance;
+
+/**
+ * Decodes the result of `get_average_locker_balance`, which is encoded like
+ * that of `get_locker_balance`.
+ * @returns
+ */
+export const decodeAverageLockerBalance = decodeLockerBalance;
+
+/**
+ * Decodes the result of `get_average_share`, which is encoded like that of
+ * `get_share`.
+ * @returns
+ */
+export const decodeAverageShare = decodeEscrowShare;
@@ -1,9 +1,22 @@
 /* eslint-disable @typescript-eslint/no-misused-promises */
 import { GokiSDK } from "@gokiprotocol/client";
 import { expectTX, expectTXTable } from "@saberhq/chai-solana";
-import { createMint, sleep, u64 } from "@saberhq/token-utils";
+import {
+  createMint,
+  createMintAndVault,
+  getOrCreateATA,
+  getTokenAccount,
+  sleep,
+  TOKEN_PROGRAM_ID,
+  u64,
+} from "@saberhq/token-utils";
 import type { PublicKey, Signer } from "@solana/web3.js";
-import type { LockerWrapper } from "@tribecahq/tribeca-sdk";
+import { Keypair } from "@solana/web3.js";
+import BN from "bn.js";
+import type {
+  GovernorWrapper,
+  LockerWrapper,
+} from "@tribecahq/tribeca-sdk";
 import {
   createLocker,
   DEFAULT_LOCKER_PARAMS,
@@ -15,13 +28,64 @@ import { zip } from "lodash";
 import invariant from "tiny-invariant";
 
 import {
+  ERA_NUM_PERIODS,
+  SNAPSHOT_AIRDROP_BITMAP_CLAIMS,
+} from "../src/constants";
+import { calculateEra, calculatePeriodStart } from "../src/math";
+import type { AirdropClaim } from "../src/wrappers/snapshots/airdrop";
+import {
+  AirdropMerkleTree,
+  verifyAirdropProof,
+} from "../src/wrappers/snapshots/airdrop";
+import {
+  calculateEscrowVeBalances,
+  calculateLockerVeBalances,
+} from "../src/wrappers/snapshots/balances";
+import {
+  findCumulativeBalancesAddress,
+  findDistributorVaultAddress,
   findEscrowHistoryAddress,
   findLockerHistoryAddress,
 } from "../src/wrappers/snapshots/pda";
-import { createUser, makeSDK } from "./workspace";
+import type { PeriodRangeQuery } from "../src/wrappers/snapshots/views";
+import type { GovernedLocker, ShortPeriodLocker } from "./workspace";
+import {
+  calculateShortPeriod,
+  confirmEvents,
+  createGovernedLocker,
+  createLockedUser,
+  createShortPeriodLocker,
+  createSnapshotConfig,
+  createUser,
+  DUMMY_INSTRUCTIONS,
+  makeSDK,
+  proposeBySmartWallet,
+} from "./workspace";
 
 export const INITIAL_MINT_AMOUNT = new u64(1_000_000_000000);
 
+/**
+ * Checks that the locker totals match the sum of the escrow balances
+ * of each period.
+ *
+ * Each lockup is rounded down separately, so a total may exceed the sum
+ * by less than two per lockup.
+ */
+const expectTotalsMatch = (totals: BN[], escrowBalances: BN[][]) => {
+  totals.forEach((total, i) => {
+    const sum = escrowBalances
+      .map((balances) => balances[i])
+      .reduce((acc: BN, balance) => {
+        invariant(balance);
+        return acc.add(balance);
+      }, new u64(0));
+    expect(total, `period ${i}`).to.bignumber.at.least(sum);
+    expect(total, `period ${i}`).to.bignumber.below(
+      sum.addn(2 * escrowBalances.length)
+    );
+  });
+};
+
 describe("Locked Voter", () => {
   const sdk = makeSDK();
   const gokiSDK = GokiSDK.load({ provider: sdk.provider });
@@ -31,6 +95,7 @@ describe("Locked Voter", () => {
 
   let lockerW: LockerWrapper;
   let user: Signer;
+  let era: number;
 
   beforeEach(async () => {
     govTokenMint = await createMint(sdk.provider);
@@ -52,11 +117,12 @@ describe("Locked Voter", () => {
     }
 
     lockerW = lockerWrapper;
+    era = calculateEra(new Date());
 
     const { tx: createLockerHistoryTX } =
       await sdk.snapshots.createLockerHistory({
         locker: lockerW.locker,
-        era: 0,
+        era,
       });
     await expectTX(createLockerHistoryTX).to.be.fulfilled;
   });
@@ -76,11 +142,12 @@ describe("Locked Voter", () => {
     await Promise.all(
       Array(6)
         .fill(null)
-        .map(async (_, era) => {
+        .map(async (_, i) => {
           const { tx: createEscrowHistoryTX } =
             await sdk.snapshots.createEscrowHistory({
+              locker: lockerW.locker,
               escrow: escrowKey,
-              era,
+              era: era + i,
             });
           await expectTX(createEscrowHistoryTX, "create escrow history").to.be
             .fulfilled;
@@ -91,13 +158,13 @@ describe("Locked Voter", () => {
   it("syncs single escrow", async () => {
     const [escrowKey] = await findEscrowAddress(lockerW.locker, user.publicKey);
 
-    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, 0);
-    const [escrowHistory] = await findEscrowHistoryAddress(escrowKey, 0);
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrowKey, era);
 
     const syncTX = await sdk.snapshots.sync({
       locker: lockerW.locker,
       owner: user.publicKey,
-      era: 0,
+      era,
     });
     await expectTXTable(syncTX, "snapshots").to.be.fulfilled;
 
@@ -110,23 +177,38 @@ describe("Locked Voter", () => {
 
     invariant(lockerHistoryData && escrowHistoryData);
 
-    expect(lockerHistoryData.veBalances).to.deep.eq(
-      escrowHistoryData.veBalances
+    // periods that have started are materialized, the others are computed
+    expect(lockerHistoryData.materializedPeriods).to.be.above(0);
+    expect(escrowHistoryData.materializedPeriods).to.eq(
+      lockerHistoryData.materializedPeriods
     );
+    const escrowBalances = calculateEscrowVeBalances(escrowHistoryData);
+    escrowHistoryData.veBalances
+      .slice(0, escrowHistoryData.materializedPeriods)
+      .forEach((balance, i) => {
+        expect(escrowBalances[i], `period ${i}`).to.bignumber.eq(balance);
+      });
+    const nextPeriod = escrowHistoryData.materializedPeriods;
+    expect(escrowHistoryData.veBalances[nextPeriod]).to.bignumber.zero;
+    expect(escrowBalances[nextPeriod]).to.bignumber.above(new u64(0));
+
+    expectTotalsMatch(calculateLockerVeBalances(lockerHistoryData), [
+      escrowBalances,
+    ]);
   });
 
   it("sync multiple times should have no effect", async () => {
     const syncTX1 = await sdk.snapshots.sync({
       locker: lockerW.locker,
       owner: user.publicKey,
-      era: 0,
+      era,
     });
     await expectTXTable(syncTX1, "sync").to.be.fulfilled;
 
     const [escrowKey] = await findEscrowAddress(lockerW.locker, user.publicKey);
 
-    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, 0);
-    const [escrowHistory] = await findEscrowHistoryAddress(escrowKey, 0);
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrowKey, era);
 
     const lockerHistoryData1 = await sdk.snapshots.fetchLockerHistory(
       lockerHistory
@@ -138,7 +220,7 @@ describe("Locked Voter", () => {
     const syncTX2 = await sdk.snapshots.sync({
       locker: lockerW.locker,
       owner: user.publicKey,
-      era: 0,
+      era,
     });
     await expectTXTable(syncTX2, "sync again").to.be.fulfilled;
 
@@ -162,10 +244,16 @@ describe("Locked Voter", () => {
     expect(escrowHistoryData1.veBalances).to.deep.eq(
       escrowHistoryData2.veBalances
     );
+    expect(calculateLockerVeBalances(lockerHistoryData1)).to.deep.eq(
+      calculateLockerVeBalances(lockerHistoryData2)
+    );
+    expect(calculateEscrowVeBalances(escrowHistoryData1)).to.deep.eq(
+      calculateEscrowVeBalances(escrowHistoryData2)
+    );
   });
 
   it("syncs multiple escrows", async () => {
-    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, 0);
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
     const initialLockerHistoryData = await sdk.snapshots.fetchLockerHistory(
       lockerHistory
     );
@@ -196,31 +284,32 @@ describe("Locked Voter", () => {
     await Promise.all(
       Array(6)
         .fill(null)
-        .map(async (_, era) => {
+        .map(async (_, i) => {
           const { tx: createEscrowHistoryTX } = await sdk
             .withSigner(user2)
             .snapshots.createEscrowHistory({
+              locker: lockerW.locker,
               escrow: escrow2Key,
-              era,
+              era: era + i,
             });
           await expectTX(createEscrowHistoryTX, "create escrow history").to.be
             .fulfilled;
         })
     );
 
-    const [escrow2History] = await findEscrowHistoryAddress(escrow2Key, 0);
+    const [escrow2History] = await findEscrowHistoryAddress(escrow2Key, era);
 
     const syncTX = await sdk.snapshots.sync({
       locker: lockerW.locker,
       owner: user.publicKey,
-      era: 0,
+      era,
     });
     await expectTXTable(syncTX, "snapshots 1").to.be.fulfilled;
 
     const sync2TX = await sdk.withSigner(user2).snapshots.sync({
       locker: lockerW.locker,
       owner: user2.publicKey,
-      era: 0,
+      era,
     });
     await expectTXTable(sync2TX, "snapshots 2", {
       verbosity: "always",
@@ -234,7 +323,7 @@ describe("Locked Voter", () => {
       lockerW.locker,
       user.publicKey
     );
-    const [escrow1History] = await findEscrowHistoryAddress(escrow1Key, 0);
+    const [escrow1History] = await findEscrowHistoryAddress(escrow1Key, era);
     const escrow1HistoryData = await sdk.snapshots.fetchEscrowHistory(
       escrow1History
     );
@@ -244,32 +333,557 @@ describe("Locked Voter", () => {
 
     invariant(lockerHistoryData && escrow1HistoryData && escrow2HistoryData);
 
-    zip(escrow1HistoryData.veBalances, escrow2HistoryData.veBalances).map(
-      ([e1, e2], i) => {
+    const escrow1Balances = calculateEscrowVeBalances(escrow1HistoryData);
+    const escrow2Balances = calculateEscrowVeBalances(escrow2HistoryData);
+    expect(escrow1Balances[255]).to.bignumber.above(new u64(0));
+    expectTotalsMatch(calculateLockerVeBalances(lockerHistoryData), [
+      escrow1Balances,
+      escrow2Balances,
+    ]);
+
+    zip(escrow1Balances, escrow2Balances)
+      .slice(0, lockerHistoryData.materializedPeriods)
+      .map(([e1, e2], i) => {
         invariant(e1 && e2);
-        const expected = e1.add(e2);
-        expect(lockerHistoryData.veBalances[i], `period ${i}`).to.bignumber.eq(
-          expected
-        );
         if (!e1.isZero() && !e2.isZero()) {
           expect(lockerHistoryData.veCounts[i], `count ${i}`).to.bignumber.eq(
             "2"
           );
         }
-      }
+      });
+  });
+
+  it("syncs every era in a single instruction", async () => {
+    const eras = [era, era + 1];
+    const { tx: createLockerHistoryTX } =
+      await sdk.snapshots.createLockerHistory({
+        locker: lockerW.locker,
+        era: era + 1,
+      });
+    await expectTX(createLockerHistoryTX, "create next locker history").to.be
+      .fulfilled;
+
+    const syncTX = await sdk.snapshots.syncAllEras({
+      locker: lockerW.locker,
+      owner: user.publicKey,
+      eras,
+    });
+    await expectTXTable(syncTX, "sync all eras").to.be.fulfilled;
+
+    const [escrowKey] = await findEscrowAddress(lockerW.locker, user.publicKey);
+    for (const syncedEra of eras) {
+      const [lockerHistory] = await findLockerHistoryAddress(
+        lockerW.locker,
+        syncedEra
+      );
+      const [escrowHistory] = await findEscrowHistoryAddress(
+        escrowKey,
+        syncedEra
+      );
+      const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+        lockerHistory
+      );
+      const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+        escrowHistory
+      );
+      invariant(lockerHistoryData && escrowHistoryData);
+
+      const escrowBalances = calculateEscrowVeBalances(escrowHistoryData);
+      // the lockup lasts longer than both eras
+      expect(escrowBalances[0], `era ${syncedEra}`).to.bignumber.above(
+        new u64(0)
+      );
+      expect(escrowBalances[255], `era ${syncedEra}`).to.bignumber.above(
+        new u64(0)
+      );
+      expectTotalsMatch(calculateLockerVeBalances(lockerHistoryData), [
+        escrowBalances,
+      ]);
+    }
+
+    // syncing an era alone afterwards changes nothing
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const lockerHistoryData1 = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    const syncAgainTX = await sdk.snapshots.sync({
+      locker: lockerW.locker,
+      owner: user.publicKey,
+      era,
+    });
+    await expectTXTable(syncAgainTX, "sync again").to.be.fulfilled;
+    const lockerHistoryData2 = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    invariant(lockerHistoryData1 && lockerHistoryData2);
+    expect(calculateLockerVeBalances(lockerHistoryData2)).to.deep.eq(
+      calculateLockerVeBalances(lockerHistoryData1)
+    );
+
+    const emptyTX = await sdk.snapshots.syncAllEras({
+      locker: lockerW.locker,
+      owner: user.publicKey,
+      eras: [],
+    });
+    await expectTX(emptyTX, "sync no eras").to.be.rejected;
+  });
+
+  it("creates missing histories when syncing", async () => {
+    const nextEra = era + 1;
+    const user2 = await createUser(sdk.provider, govTokenMint);
+    const lockTx = await lockerW.lockTokens({
+      amount: INITIAL_MINT_AMOUNT,
+      duration: DEFAULT_LOCKER_PARAMS.maxStakeDuration,
+      authority: user2.publicKey,
+    });
+    lockTx.addSigners(user2);
+    await expectTX(lockTx, "lock tokens").to.be.fulfilled;
+
+    const [escrow2Key] = await findEscrowAddress(
+      lockerW.locker,
+      user2.publicKey
+    );
+    const [lockerHistory] = await findLockerHistoryAddress(
+      lockerW.locker,
+      nextEra
+    );
+    const [escrow2History] = await findEscrowHistoryAddress(
+      escrow2Key,
+      nextEra
+    );
+    expect(await sdk.snapshots.fetchLockerHistory(lockerHistory)).to.be.null;
+    expect(await sdk.snapshots.fetchEscrowHistory(escrow2History)).to.be.null;
+
+    const syncTX = await sdk.snapshots.syncInitIfNeeded({
+      locker: lockerW.locker,
+      owner: user2.publicKey,
+      era: nextEra,
+    });
+    await expectTXTable(syncTX, "sync init if needed").to.be.fulfilled;
+
+    const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+      escrow2History
+    );
+    invariant(lockerHistoryData && escrowHistoryData);
+    expect(lockerHistoryData.locker).to.eqAddress(lockerW.locker);
+    expect(lockerHistoryData.era).to.eq(nextEra);
+    expect(escrowHistoryData.escrow).to.eqAddress(escrow2Key);
+    expect(escrowHistoryData.era).to.eq(nextEra);
+    const escrowBalances = calculateEscrowVeBalances(escrowHistoryData);
+    expect(escrowBalances[0]).to.bignumber.above(new u64(0));
+    expectTotalsMatch(calculateLockerVeBalances(lockerHistoryData), [
+      escrowBalances,
+    ]);
+
+    // once the histories exist, it is a plain sync
+    const syncAgainTX = await sdk.snapshots.syncInitIfNeeded({
+      locker: lockerW.locker,
+      owner: user2.publicKey,
+      era: nextEra,
+    });
+    await expectTXTable(syncAgainTX, "sync init if needed again").to.be
+      .fulfilled;
+    const lockerHistoryData2 = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    invariant(lockerHistoryData2);
+    expect(calculateLockerVeBalances(lockerHistoryData2)).to.deep.eq(
+      calculateLockerVeBalances(lockerHistoryData)
+    );
+
+    // an escrow may not have both kinds of history for an era
+    const compactEra = era + 2;
+    const { tx: createCompactTX } =
+      await sdk.snapshots.createCompactEscrowHistory({
+        locker: lockerW.locker,
+        escrow: escrow2Key,
+        era: compactEra,
+      });
+    await expectTX(createCompactTX, "create compact escrow history").to.be
+      .fulfilled;
+    const syncCompactEraTX = await sdk.snapshots.syncInitIfNeeded({
+      locker: lockerW.locker,
+      owner: user2.publicKey,
+      era: compactEra,
+    });
+    await expectTX(syncCompactEraTX, "sync init beside compact history").to.be
+      .rejected;
+  });
+
+  it("emits events when creating and syncing histories", async () => {
+    const nextEra = era + 1;
+    const user2 = await createUser(sdk.provider, govTokenMint);
+    const lockTx = await lockerW.lockTokens({
+      amount: INITIAL_MINT_AMOUNT,
+      duration: DEFAULT_LOCKER_PARAMS.maxStakeDuration,
+      authority: user2.publicKey,
+    });
+    lockTx.addSigners(user2);
+    await expectTX(lockTx, "lock tokens").to.be.fulfilled;
+    const [escrow2Key] = await findEscrowAddress(
+      lockerW.locker,
+      user2.publicKey
+    );
+    const [lockerHistory] = await findLockerHistoryAddress(
+      lockerW.locker,
+      nextEra
+    );
+    const [escrow2History] = await findEscrowHistoryAddress(
+      escrow2Key,
+      nextEra
+    );
+
+    const events = await confirmEvents(
+      await sdk.snapshots.syncInitIfNeeded({
+        locker: lockerW.locker,
+        owner: user2.publicKey,
+        era: nextEra,
+      }),
+      sdk.snapshots.program
+    );
+    expect(events.map((event) => event.name)).to.deep.eq([
+      "LockerHistoryCreated",
+      "EscrowHistoryCreated",
+      "EscrowSynced",
+    ]);
+    const [lockerHistoryCreated, escrowHistoryCreated, escrowSynced] = events;
+    invariant(lockerHistoryCreated && escrowHistoryCreated && escrowSynced);
+    expect(lockerHistoryCreated.data.locker).to.eqAddress(lockerW.locker);
+    expect(lockerHistoryCreated.data.lockerHistory).to.eqAddress(
+      lockerHistory
+    );
+    expect(lockerHistoryCreated.data.era).to.eq(nextEra);
+    expect(escrowHistoryCreated.data.escrow).to.eqAddress(escrow2Key);
+    expect(escrowHistoryCreated.data.escrowHistory).to.eqAddress(
+      escrow2History
+    );
+    expect(escrowHistoryCreated.data.era).to.eq(nextEra);
+
+    const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+      escrow2History
+    );
+    invariant(escrowHistoryData);
+    expect(escrowSynced.data.locker).to.eqAddress(lockerW.locker);
+    expect(escrowSynced.data.escrow).to.eqAddress(escrow2Key);
+    expect(escrowSynced.data.era).to.eq(nextEra);
+    expect(escrowSynced.data.resynced).to.be.false;
+    // the escrow had no lockup before it was first synced
+    expect(escrowSynced.data.prevPower).to.bignumber.zero;
+    expect(escrowSynced.data.power).to.bignumber.eq(escrowHistoryData.power);
+    expect(escrowSynced.data.startedAt).to.bignumber.eq(
+      escrowHistoryData.startedAt
+    );
+    expect(escrowSynced.data.endsAt).to.bignumber.eq(escrowHistoryData.endsAt);
+    expect(escrowSynced.data.firstPeriod).to.eq(0);
+    // the lockup lasts beyond the era
+    expect(escrowSynced.data.lastPeriod).to.eq(255);
+
+    // syncing an unchanged lockup reports it as both the previous and new one
+    const [resyncEvent, ...otherEvents] = await confirmEvents(
+      await sdk.snapshots.sync({
+        locker: lockerW.locker,
+        owner: user2.publicKey,
+        era: nextEra,
+      }),
+      sdk.snapshots.program
+    );
+    expect(otherEvents).to.be.empty;
+    invariant(resyncEvent);
+    expect(resyncEvent.name).to.eq("EscrowSynced");
+    expect(resyncEvent.data.prevPower).to.bignumber.eq(
+      escrowHistoryData.power
+    );
+    expect(resyncEvent.data.prevEndsAt).to.bignumber.eq(
+      escrowHistoryData.endsAt
     );
+    expect(resyncEvent.data.power).to.bignumber.eq(escrowHistoryData.power);
+  });
+
+  it("returns balances from the views", async () => {
+    const syncTX = await sdk.snapshots.sync({
+      locker: lockerW.locker,
+      owner: user.publicKey,
+      era,
+    });
+    await expectTXTable(syncTX, "sync").to.be.fulfilled;
+
+    const [escrowKey] = await findEscrowAddress(lockerW.locker, user.publicKey);
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrowKey, era);
+    const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+      escrowHistory
+    );
+    invariant(lockerHistoryData && escrowHistoryData);
+    const lockerBalances = calculateLockerVeBalances(lockerHistoryData);
+    const escrowBalances = calculateEscrowVeBalances(escrowHistoryData);
+
+    // the current period has started, so its total is final
+    const period = lockerHistoryData.materializedPeriods - 1;
+    const query = { period: { era, period } };
+
+    const lockerBalance = await sdk.snapshots.getLockerBalance({
+      locker: lockerW.locker,
+      era,
+      query,
+    });
+    expect(lockerBalance.veBalance).to.bignumber.eq(lockerBalances[period]);
+    expect(lockerBalance.veBalance).to.bignumber.above(new u64(0));
+    expect(lockerBalance.veCount).to.bignumber.eq(
+      lockerHistoryData.veCounts[period]
+    );
+    expect(lockerBalance.isFinal).to.be.true;
+
+    const escrowBalance = await sdk.snapshots.getEscrowBalance({
+      escrowHistory,
+      query,
+    });
+    expect(escrowBalance).to.bignumber.eq(escrowBalances[period]);
+
+    const share = await sdk.snapshots.getShare({
+      locker: lockerW.locker,
+      escrow: escrowKey,
+      escrowHistory,
+      era,
+      query,
+    });
+    expect(share.escrowVeBalance).to.bignumber.eq(escrowBalance);
+    expect(share.lockerVeBalance).to.bignumber.eq(lockerBalance.veBalance);
+    expect(share.isFinal).to.be.true;
+
+    // a timestamp selects the period that contains it
+    const ts = new BN(calculatePeriodStart(era, period).getTime() / 1_000);
+    const lockerBalanceAtTs = await sdk.snapshots.getLockerBalance({
+      locker: lockerW.locker,
+      era,
+      query: { timestamp: { ts: ts.addn(1) } },
+    });
+    expect(lockerBalanceAtTs).to.deep.eq(lockerBalance);
+
+    // escrow balances of future periods are computed from the lockup,
+    // but locker totals only exist once a period has started
+    const nextQuery = { period: { era, period: period + 1 } };
+    if (period + 1 < 256) {
+      const nextEscrowBalance = await sdk.snapshots.getEscrowBalance({
+        escrowHistory,
+        query: nextQuery,
+      });
+      expect(nextEscrowBalance).to.bignumber.eq(escrowBalances[period + 1]);
+      await expect(
+        sdk.snapshots.getLockerBalance({
+          locker: lockerW.locker,
+          era,
+          query: nextQuery,
+        })
+      ).to.be.rejected;
+    }
+
+    // the era of the query must match the history
+    await expect(
+      sdk.snapshots.getEscrowBalance({
+        escrowHistory,
+        query: { period: { era: era + 1, period: 0 } },
+      })
+    ).to.be.rejected;
+  });
+
+  it("syncs many escrows, skipping those that cannot be synced", async () => {
+    const lockUser = async (): Promise<Signer> => {
+      const newUser = await createUser(sdk.provider, govTokenMint);
+      const lockTx = await lockerW.lockTokens({
+        amount: INITIAL_MINT_AMOUNT,
+        duration: DEFAULT_LOCKER_PARAMS.maxStakeDuration,
+        authority: newUser.publicKey,
+      });
+      lockTx.addSigners(newUser);
+      await expectTX(lockTx, "lock tokens").to.be.fulfilled;
+      return newUser;
+    };
+    const user2 = await lockUser();
+    const [escrow2Key] = await findEscrowAddress(
+      lockerW.locker,
+      user2.publicKey
+    );
+    const { tx: createEscrowHistoryTX } =
+      await sdk.snapshots.createEscrowHistory({
+        locker: lockerW.locker,
+        escrow: escrow2Key,
+        era,
+      });
+    await expectTX(createEscrowHistoryTX, "create escrow history").to.be
+      .fulfilled;
+    // has no EscrowHistory, so it cannot be synced
+    const user3 = await lockUser();
+    const [escrow3Key] = await findEscrowAddress(
+      lockerW.locker,
+      user3.publicKey
+    );
+    const [escrow3History] = await findEscrowHistoryAddress(escrow3Key, era);
+
+    const events = await confirmEvents(
+      await sdk.snapshots.syncMany({
+        locker: lockerW.locker,
+        owners: [user.publicKey, user3.publicKey, user2.publicKey],
+        era,
+      }),
+      sdk.snapshots.program
+    );
+    expect(events.map((event) => event.name)).to.deep.eq([
+      "EscrowSynced",
+      "EscrowSyncSkipped",
+      "EscrowSynced",
+    ]);
+    const [, skipped] = events;
+    invariant(skipped);
+    expect(skipped.data.locker).to.eqAddress(lockerW.locker);
+    expect(skipped.data.escrow).to.eqAddress(escrow3Key);
+    expect(skipped.data.escrowHistory).to.eqAddress(escrow3History);
+    expect(skipped.data.error).to.bignumber.above(new u64(0));
+
+    // the skipped escrow is not counted in the totals
+    const [escrow1Key] = await findEscrowAddress(
+      lockerW.locker,
+      user.publicKey
+    );
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const [escrow1History] = await findEscrowHistoryAddress(escrow1Key, era);
+    const [escrow2History] = await findEscrowHistoryAddress(escrow2Key, era);
+    const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    const escrow1HistoryData = await sdk.snapshots.fetchEscrowHistory(
+      escrow1History
+    );
+    const escrow2HistoryData = await sdk.snapshots.fetchEscrowHistory(
+      escrow2History
+    );
+    invariant(lockerHistoryData && escrow1HistoryData && escrow2HistoryData);
+    const lockerBalances = calculateLockerVeBalances(lockerHistoryData);
+    expectTotalsMatch(lockerBalances, [
+      calculateEscrowVeBalances(escrow1HistoryData),
+      calculateEscrowVeBalances(escrow2HistoryData),
+    ]);
+
+    // once it has a history, the escrow is counted
+    const { tx: createEscrow3HistoryTX } =
+      await sdk.snapshots.createEscrowHistory({
+        locker: lockerW.locker,
+        escrow: escrow3Key,
+        era,
+      });
+    await expectTX(createEscrow3HistoryTX, "create escrow history").to.be
+      .fulfilled;
+    const syncTX = await sdk.snapshots.syncMany({
+      locker: lockerW.locker,
+      owners: [user3.publicKey],
+      era,
+    });
+    await expectTXTable(syncTX, "sync many").to.be.fulfilled;
+    const lockerHistoryData2 = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    invariant(lockerHistoryData2);
+    const lockerBalances2 = calculateLockerVeBalances(lockerHistoryData2);
+    expect(lockerBalances2[255]).to.bignumber.above(
+      lockerBalances[255] ?? new u64(0)
+    );
+  });
+
+  it("batch creates escrow histories, skipping existing ones", async () => {
+    const user2 = await createUser(sdk.provider, govTokenMint);
+    const lockTx = await lockerW.lockTokens({
+      amount: INITIAL_MINT_AMOUNT,
+      duration: DEFAULT_LOCKER_PARAMS.maxStakeDuration,
+      authority: user2.publicKey,
+    });
+    lockTx.addSigners(user2);
+    await expectTX(lockTx, "lock tokens").to.be.fulfilled;
+    const [escrow1Key] = await findEscrowAddress(
+      lockerW.locker,
+      user.publicKey
+    );
+    const [escrow2Key] = await findEscrowAddress(
+      lockerW.locker,
+      user2.publicKey
+    );
+    // the escrow may not also get an EscrowHistory for this era
+    const { compactEscrowHistory, tx: createCompactTX } =
+      await sdk.snapshots.createCompactEscrowHistory({
+        locker: lockerW.locker,
+        escrow: escrow2Key,
+        era: era + 1,
+      });
+    await expectTX(createCompactTX, "create compact escrow history").to.be
+      .fulfilled;
+
+    // the histories of the first escrow all exist already
+    const createTX = await sdk.snapshots.createEscrowHistories({
+      locker: lockerW.locker,
+      escrows: [escrow1Key, escrow2Key],
+      firstEra: era,
+      numEras: 3,
+    });
+    const events = await confirmEvents(createTX, sdk.snapshots.program);
+    expect(events.map((event) => event.name)).to.deep.eq([
+      "EscrowHistoryCreated",
+      "EscrowHistoryCreated",
+    ]);
+    expect(events.map((event) => event.data.escrow.toString())).to.deep.eq([
+      escrow2Key.toString(),
+      escrow2Key.toString(),
+    ]);
+    expect(events.map((event) => event.data.era)).to.deep.eq([era, era + 2]);
+
+    for (const createdEra of [era, era + 2]) {
+      const [escrowHistory] = await findEscrowHistoryAddress(
+        escrow2Key,
+        createdEra
+      );
+      const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+        escrowHistory
+      );
+      invariant(escrowHistoryData, `era ${createdEra}`);
+      expect(escrowHistoryData.escrow).to.eqAddress(escrow2Key);
+      expect(escrowHistoryData.era).to.eq(createdEra);
+    }
+    const [skippedHistory] = await findEscrowHistoryAddress(
+      escrow2Key,
+      era + 1
+    );
+    const compactEscrowHistoryData =
+      await sdk.snapshots.fetchCompactEscrowHistory(compactEscrowHistory);
+    expect(await sdk.snapshots.fetchEscrowHistory(skippedHistory)).to.be.null;
+    expect(compactEscrowHistoryData).to.not.be.null;
+
+    // creating them again does nothing
+    const createAgainTX = await sdk.snapshots.createEscrowHistories({
+      locker: lockerW.locker,
+      escrows: [escrow1Key, escrow2Key],
+      firstEra: era,
+      numEras: 3,
+    });
+    const eventsAgain = await confirmEvents(
+      createAgainTX,
+      sdk.snapshots.program
+    );
+    expect(eventsAgain).to.be.empty;
   });
 
   it("changes with a refresh", async () => {
     const [escrowKey] = await findEscrowAddress(lockerW.locker, user.publicKey);
 
-    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, 0);
-    const [escrowHistory] = await findEscrowHistoryAddress(escrowKey, 0);
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrowKey, era);
 
     const syncTX = await sdk.snapshots.sync({
       locker: lockerW.locker,
       owner: user.publicKey,
-      era: 0,
+      era,
     });
     await expectTXTable(syncTX, "snapshots").to.be.fulfilled;
 
@@ -280,9 +894,10 @@ describe("Locked Voter", () => {
       escrowHistory
     );
     invariant(lockerHistoryData && escrowHistoryData);
-    expect(lockerHistoryData.veBalances).to.deep.eq(
-      escrowHistoryData.veBalances
-    );
+    const escrowBalances = calculateEscrowVeBalances(escrowHistoryData);
+    expectTotalsMatch(calculateLockerVeBalances(lockerHistoryData), [
+      escrowBalances,
+    ]);
 
     // sleep so we can get more lock
     await sleep(1_000);
@@ -298,7 +913,7 @@ describe("Locked Voter", () => {
     const sync2TX = await sdk.snapshots.sync({
       locker: lockerW.locker,
       owner: user.publicKey,
-      era: 0,
+      era,
     });
     await expectTXTable(sync2TX, "snapshots sync v2").to.be.fulfilled;
 
@@ -309,16 +924,1706 @@ describe("Locked Voter", () => {
       escrowHistory
     );
     invariant(lockerHistoryData2 && escrowHistoryData2);
-    expect(lockerHistoryData2.veBalances).to.deep.eq(
-      escrowHistoryData2.veBalances
-    );
+    const escrowBalances2 = calculateEscrowVeBalances(escrowHistoryData2);
+    const lockerBalances2 = calculateLockerVeBalances(lockerHistoryData2);
+    expectTotalsMatch(lockerBalances2, [escrowBalances2]);
 
     // should have changed
-    expect(lockerHistoryData2.veBalances).to.not.deep.eq(
-      lockerHistoryData.veBalances
+    expect(lockerBalances2).to.not.deep.eq(
+      calculateLockerVeBalances(lockerHistoryData)
+    );
+    expect(escrowBalances2).to.not.deep.eq(escrowBalances);
+  });
+});
+
+describe("Snapshot Config", () => {
+  const sdk = makeSDK();
+
+  let governedLocker: GovernedLocker;
+  let lockerW: LockerWrapper;
+
+  beforeEach(async () => {
+    governedLocker = await createGovernedLocker({ sdk });
+    ({ lockerW } = governedLocker);
+  });
+
+  /**
+   * Returns the transaction that executes a SnapshotConfig proposal.
+   */
+  const proposeSnapshotConfig = async ({
+    periodSeconds,
+    originTs,
+  }: {
+    periodSeconds: number;
+    originTs: number;
+  }) => {
+    const { instruction } = await sdk.snapshots.createSnapshotConfig({
+      locker: lockerW.locker,
+      governor: governedLocker.governorW.governorKey,
+      smartWallet: governedLocker.smartWalletW.key,
+      periodSeconds,
+      originTs,
+    });
+    return await proposeBySmartWallet({
+      smartWalletWrapper: governedLocker.smartWalletW,
+      instructions: [instruction],
+    });
+  };
+
+  it("must be created by the smart wallet of the governor", async () => {
+    const { instruction } = await sdk.snapshots.createSnapshotConfig({
+      locker: lockerW.locker,
+      governor: governedLocker.governorW.governorKey,
+      smartWallet: sdk.provider.wallet.publicKey,
+      periodSeconds: 86_400,
+      originTs: Math.floor(Date.now() / 1_000),
+    });
+    await expectTX(
+      sdk.provider.newTX([instruction]),
+      "create config without the smart wallet"
+    ).to.be.rejected;
+  });
+
+  it("rejects periods of zero seconds", async () => {
+    const executeTX = await proposeSnapshotConfig({
+      periodSeconds: 0,
+      originTs: Math.floor(Date.now() / 1_000),
+    });
+    await expectTX(executeTX, "create config of empty periods").to.be
+      .rejected;
+  });
+
+  it("cannot be created once the current LockerHistory exists", async () => {
+    const { tx: createLockerHistoryTX } =
+      await sdk.snapshots.createLockerHistory({
+        locker: lockerW.locker,
+        era: calculateEra(new Date()),
+      });
+    await expectTX(createLockerHistoryTX, "create locker history").to.be
+      .fulfilled;
+
+    const executeTX = await proposeSnapshotConfig({
+      periodSeconds: 86_400,
+      originTs: Math.floor(Date.now() / 1_000),
+    });
+    await expectTX(executeTX, "create config after history").to.be.rejected;
+  });
+
+  for (const periodSeconds of [86_400, 86_400 * 7]) {
+    it(`syncs histories of ${periodSeconds} second periods`, async () => {
+      // era 0 starts just before the test, so only period 0 has started
+      const originTs = Math.floor(Date.now() / 1_000) - 10;
+      const snapshotConfig = await createSnapshotConfig({
+        sdk,
+        governedLocker,
+        periodSeconds,
+        originTs,
+      });
+      const configData = await sdk.snapshots.fetchSnapshotConfig(
+        snapshotConfig
+      );
+      invariant(configData);
+      expect(configData.periodSeconds).to.bignumber.eq(new BN(periodSeconds));
+      expect(configData.originTs).to.bignumber.eq(new BN(originTs));
+
+      const { lockerHistory, tx: createLockerHistoryTX } =
+        await sdk.snapshots.createLockerHistory({
+          locker: lockerW.locker,
+          era: 0,
+        });
+      await expectTX(createLockerHistoryTX, "create locker history").to.be
+        .fulfilled;
+      const user = await createLockedUser({
+        provider: sdk.provider,
+        lockerW,
+        govTokenMint: governedLocker.govTokenMint,
+      });
+      const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+      const { escrowHistory, tx: createEscrowHistoryTX } =
+        await sdk.snapshots.createEscrowHistory({
+          locker: lockerW.locker,
+          escrow,
+          era: 0,
+        });
+      await expectTX(createEscrowHistoryTX, "create escrow history").to.be
+        .fulfilled;
+
+      const syncTX = await sdk.snapshots.sync({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        era: 0,
+      });
+      await expectTXTable(syncTX, "snapshots").to.be.fulfilled;
+
+      const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+        lockerHistory
+      );
+      const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+        escrowHistory
+      );
+      invariant(lockerHistoryData && escrowHistoryData);
+      for (const history of [lockerHistoryData, escrowHistoryData]) {
+        expect(history.periodSeconds).to.bignumber.eq(new BN(periodSeconds));
+        expect(history.originTs).to.bignumber.eq(new BN(originTs));
+        expect(history.materializedPeriods).to.eq(1);
+      }
+
+      // the lockup counts from period 1, and lasts longer than the era
+      const escrowBalances = calculateEscrowVeBalances(escrowHistoryData);
+      expect(escrowBalances[0]).to.bignumber.zero;
+      expect(escrowBalances[1]).to.bignumber.above(new u64(0));
+      expect(escrowBalances[255]).to.bignumber.above(new u64(0));
+      expect(escrowBalances[1]).to.bignumber.above(escrowBalances[255]);
+      expectTotalsMatch(calculateLockerVeBalances(lockerHistoryData), [
+        escrowBalances,
+      ]);
+    });
+  }
+
+  it("rejects syncs into histories created before the config", async () => {
+    // a history of a future era keeps the default geometry
+    const era = calculateEra(new Date()) + 1;
+    const { lockerHistory, tx: createLockerHistoryTX } =
+      await sdk.snapshots.createLockerHistory({
+        locker: lockerW.locker,
+        era,
+      });
+    await expectTX(createLockerHistoryTX, "create locker history").to.be
+      .fulfilled;
+    await createSnapshotConfig({
+      sdk,
+      governedLocker,
+      periodSeconds: 86_400 * 7,
+      originTs: Math.floor(Date.now() / 1_000),
+    });
+
+    const user = await createLockedUser({
+      provider: sdk.provider,
+      lockerW,
+      govTokenMint: governedLocker.govTokenMint,
+    });
+    const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+    const { escrowHistory, tx: createEscrowHistoryTX } =
+      await sdk.snapshots.createEscrowHistory({
+        locker: lockerW.locker,
+        escrow,
+        era,
+      });
+    await expectTX(createEscrowHistoryTX, "create escrow history").to.be
+      .fulfilled;
+
+    const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+      escrowHistory
+    );
+    invariant(lockerHistoryData && escrowHistoryData);
+    expect(lockerHistoryData.periodSeconds).to.bignumber.zero;
+    expect(escrowHistoryData.periodSeconds).to.bignumber.eq(
+      new BN(86_400 * 7)
+    );
+
+    const syncTX = await sdk.snapshots.sync({
+      locker: lockerW.locker,
+      owner: user.publicKey,
+      era,
+    });
+    await expectTX(syncTX, "sync mismatched geometries").to.be.rejected;
+  });
+
+  it("rejects histories of eras past their retention window", async () => {
+    // eras last 256 seconds, so era 0 has ended and era 2 is the current one
+    await createSnapshotConfig({
+      sdk,
+      governedLocker,
+      periodSeconds: 1,
+      originTs: Math.floor(Date.now() / 1_000) - 600,
+    });
+    const user = await createLockedUser({
+      provider: sdk.provider,
+      lockerW,
+      govTokenMint: governedLocker.govTokenMint,
+    });
+    const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+
+    const { tx: expiredLockerHistoryTX } =
+      await sdk.snapshots.createLockerHistory({
+        locker: lockerW.locker,
+        era: 0,
+      });
+    await expectTX(expiredLockerHistoryTX, "expired locker history").to.be
+      .rejected;
+    const { tx: expiredEscrowHistoryTX } =
+      await sdk.snapshots.createEscrowHistory({
+        locker: lockerW.locker,
+        escrow,
+        era: 0,
+      });
+    await expectTX(expiredEscrowHistoryTX, "expired escrow history").to.be
+      .rejected;
+    const { tx: expiredCompactEscrowHistoryTX } =
+      await sdk.snapshots.createCompactEscrowHistory({
+        locker: lockerW.locker,
+        escrow,
+        era: 0,
+      });
+    await expectTX(expiredCompactEscrowHistoryTX, "expired compact").to.be
+      .rejected;
+    const expiredEscrowHistoriesTX =
+      await sdk.snapshots.createEscrowHistories({
+        locker: lockerW.locker,
+        escrows: [escrow],
+        firstEra: 0,
+      });
+    await expectTX(expiredEscrowHistoriesTX, "expired escrow histories").to.be
+      .rejected;
+
+    // a longer retention window has not passed yet
+    const { tx: lockerHistoryTX } = await sdk.snapshots.createLockerHistory({
+      locker: lockerW.locker,
+      era: 0,
+      retentionSeconds: 3_600,
+    });
+    await expectTX(lockerHistoryTX, "retained locker history").to.be.fulfilled;
+    const expiredSyncTX = await sdk.snapshots.syncInitIfNeeded({
+      locker: lockerW.locker,
+      owner: user.publicKey,
+      era: 0,
+    });
+    await expectTX(expiredSyncTX, "sync expired escrow history").to.be.rejected;
+
+    const syncTX = await sdk.snapshots.syncInitIfNeeded({
+      locker: lockerW.locker,
+      owner: user.publicKey,
+      era: 2,
+    });
+    await expectTX(syncTX, "sync into current era").to.be.fulfilled;
+  });
+});
+
+describe("Locker History Shards", () => {
+  const sdk = makeSDK();
+
+  const era = 0;
+  const numShards = 2;
+
+  let shortLocker: ShortPeriodLocker;
+  let lockerW: LockerWrapper;
+
+  beforeEach(async () => {
+    shortLocker = await createShortPeriodLocker({ sdk, periodSeconds: 2 });
+    ({ lockerW } = shortLocker);
+
+    for (let index = 0; index < numShards; index++) {
+      const { tx: createShardTX } =
+        await sdk.snapshots.createLockerHistoryShard({
+          locker: lockerW.locker,
+          era,
+          index,
+        });
+      await expectTX(createShardTX, "create shard").to.be.fulfilled;
+    }
+  });
+
+  it("merges escrows synced into different shards", async () => {
+    // new escrows are placed into the shard keyed by their address
+    const shardUsers: Signer[] = [];
+    while (shardUsers.length < numShards) {
+      const user = await createLockedUser({
+        provider: sdk.provider,
+        lockerW,
+        govTokenMint: shortLocker.govTokenMint,
+      });
+      const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+      const index = escrow.toBuffer().readUInt16LE(0) % numShards;
+      if (shardUsers[index]) {
+        continue;
+      }
+      shardUsers[index] = user;
+      const { tx: createEscrowHistoryTX } =
+        await sdk.snapshots.createEscrowHistory({
+          locker: lockerW.locker,
+          escrow,
+          era,
+        });
+      await expectTX(createEscrowHistoryTX, "create escrow history").to.be
+        .fulfilled;
+    }
+
+    const escrowBalances: BN[][] = [];
+    for (const [index, user] of shardUsers.entries()) {
+      const wrongShardTX = await sdk.snapshots.syncSharded({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        era,
+        index: (index + 1) % numShards,
+      });
+      await expectTX(wrongShardTX, "sync into another shard").to.be.rejected;
+
+      const syncTX = await sdk.snapshots.syncSharded({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        era,
+        index,
+      });
+      await expectTXTable(syncTX, "sync sharded").to.be.fulfilled;
+
+      const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+      const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+      const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+        escrowHistory
+      );
+      invariant(escrowHistoryData);
+      expect(escrowHistoryData.shard).to.eq(index + 1);
+      escrowBalances.push(calculateEscrowVeBalances(escrowHistoryData));
+    }
+
+    // wait for the period after the lockups to be materialized
+    const period = calculateShortPeriod(shortLocker) + 1;
+    const { periodSeconds, originTs } = shortLocker;
+    await sleep(
+      (originTs + (period + 1) * periodSeconds) * 1_000 - Date.now() + 1_000
+    );
+    const query = { period: { era, period } };
+
+    const mergeFirstTX = await sdk.snapshots.mergeLockerHistoryShard({
+      locker: lockerW.locker,
+      era,
+      index: 0,
+    });
+    await expectTX(mergeFirstTX, "merge first shard").to.be.fulfilled;
+    const partialBalance = await sdk.snapshots.getLockerBalance({
+      locker: lockerW.locker,
+      era,
+      query,
+    });
+    // the other shard has not been merged
+    expect(partialBalance.isFinal).to.be.false;
+    const firstBalance = escrowBalances[0]?.[period];
+    invariant(firstBalance);
+    expectTotalsMatch([partialBalance.veBalance], [[firstBalance]]);
+
+    // merging again has no effect
+    const mergeAgainTX = await sdk.snapshots.mergeLockerHistoryShard({
+      locker: lockerW.locker,
+      era,
+      index: 0,
+    });
+    await expectTX(mergeAgainTX, "merge first shard again").to.be.fulfilled;
+    expect(
+      await sdk.snapshots.getLockerBalance({
+        locker: lockerW.locker,
+        era,
+        query,
+      })
+    ).to.deep.eq(partialBalance);
+
+    const mergeSecondTX = await sdk.snapshots.mergeLockerHistoryShard({
+      locker: lockerW.locker,
+      era,
+      index: 1,
+    });
+    await expectTX(mergeSecondTX, "merge second shard").to.be.fulfilled;
+    const lockerBalance = await sdk.snapshots.getLockerBalance({
+      locker: lockerW.locker,
+      era,
+      query,
+    });
+    expect(lockerBalance.isFinal).to.be.true;
+    expect(lockerBalance.veCount).to.bignumber.eq(new BN(numShards));
+
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    invariant(lockerHistoryData);
+    expect(lockerHistoryData.numShards).to.eq(numShards);
+    expect(lockerHistoryData.mergedShards[period]).to.eq(numShards);
+    expectTotalsMatch(
+      lockerHistoryData.veBalances.slice(0, period + 1),
+      escrowBalances.map((balances) => balances.slice(0, period + 1))
+    );
+    expect(lockerBalance.veBalance).to.bignumber.eq(
+      lockerHistoryData.veBalances[period]
+    );
+  });
+});
+
+describe("Govern Proposal Snapshots", () => {
+  const sdk = makeSDK();
+  const tribecaSDK = TribecaSDK.load({ provider: sdk.provider });
+
+  /**
+   * Era 0 of the snapshot config starts just before the test.
+   */
+  const era = 0;
+
+  let shortLocker: ShortPeriodLocker;
+  let lockerW: LockerWrapper;
+  let governorW: GovernorWrapper;
+  let user: Signer;
+
+  beforeEach(async () => {
+    // short periods, so the period of a proposal is final within the test
+    shortLocker = await createShortPeriodLocker({ sdk, periodSeconds: 2 });
+    ({ lockerW, governorW } = shortLocker);
+
+    user = await createLockedUser({
+      provider: sdk.provider,
+      lockerW,
+      govTokenMint: shortLocker.govTokenMint,
+    });
+    const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+    const { tx: createEscrowHistoryTX } =
+      await sdk.snapshots.createEscrowHistory({
+        locker: lockerW.locker,
+        escrow,
+        era,
+      });
+    await expectTX(createEscrowHistoryTX, "create escrow history").to.be
+      .fulfilled;
+    // started periods are immutable, so the lockup must be synced before the
+    // period of the proposal
+    const syncTX = await sdk.snapshots.sync({
+      locker: lockerW.locker,
+      owner: user.publicKey,
+      era,
+    });
+    await expectTXTable(syncTX, "sync").to.be.fulfilled;
+  });
+
+  it("records the snapshot total and the governor's quorum", async () => {
+    const { periodSeconds, originTs } = shortLocker;
+    // the lockup must have started before the period of the proposal
+    await sleep(periodSeconds * 1_000);
+    const { proposal, tx: createProposalTX } = await governorW.createProposal({
+      instructions: DUMMY_INSTRUCTIONS,
+    });
+    await expectTX(createProposalTX, "create proposal").to.be.fulfilled;
+    const proposalData =
+      await tribecaSDK.programs.Govern.account.proposal.fetch(proposal);
+    const period = Math.floor(
+      (proposalData.createdAt.toNumber() - originTs) / periodSeconds
+    );
+
+    // the period of the proposal has not been materialized yet
+    const { snapshot, tx: earlySnapshotTX } =
+      await sdk.snapshots.createGovernProposalSnapshot({
+        locker: lockerW.locker,
+        proposal,
+        era,
+      });
+    await expectTX(earlySnapshotTX, "snapshot before final").to.be.rejected;
+
+    await sleep((periodSeconds + 1) * 1_000);
+    const syncTX = await sdk.snapshots.sync({
+      locker: lockerW.locker,
+      owner: user.publicKey,
+      era,
+    });
+    await expectTXTable(syncTX, "snapshots").to.be.fulfilled;
+
+    const { tx: snapshotTX } =
+      await sdk.snapshots.createGovernProposalSnapshot({
+        locker: lockerW.locker,
+        proposal,
+        era,
+      });
+    await expectTX(snapshotTX, "snapshot").to.be.fulfilled;
+
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    const snapshotData = await sdk.snapshots.fetchGovernProposalSnapshot(
+      snapshot
+    );
+    invariant(lockerHistoryData && snapshotData);
+    expect(snapshotData.era).to.eq(era);
+    expect(snapshotData.period).to.eq(period);
+    expect(snapshotData.totalVotingPower).to.bignumber.eq(
+      lockerHistoryData.veBalances[period]
+    );
+    expect(snapshotData.totalVotingPower).to.bignumber.above(new u64(0));
+    // the governor's quorum is an absolute number of votes
+    expect(snapshotData.quorumVotes).to.bignumber.eq(proposalData.quorumVotes);
+
+    const { receipt, tx: receiptTX } =
+      await sdk.snapshots.createVoteWeightReceipt({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        proposal,
+        era,
+      });
+    await expectTX(receiptTX, "vote weight receipt").to.be.fulfilled;
+    const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+    const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+      escrowHistory
+    );
+    const receiptData = await sdk.snapshots.fetchVoteWeightReceipt(receipt);
+    invariant(escrowHistoryData && receiptData);
+    const escrowBalances = calculateEscrowVeBalances(escrowHistoryData);
+    expect(receiptData.votingPower).to.bignumber.eq(escrowBalances[period]);
+    expectTotalsMatch(
+      [snapshotData.totalVotingPower],
+      [[receiptData.votingPower]]
+    );
+  });
+});
+
+describe("Fee Distributor", () => {
+  const sdk = makeSDK();
+
+  const era = 0;
+
+  let shortLocker: ShortPeriodLocker;
+  let lockerW: LockerWrapper;
+  let users: Signer[];
+
+  beforeEach(async () => {
+    shortLocker = await createShortPeriodLocker({ sdk, periodSeconds: 2 });
+    ({ lockerW } = shortLocker);
+
+    users = [];
+    for (const duration of [
+      DEFAULT_LOCKER_PARAMS.maxStakeDuration,
+      DEFAULT_LOCKER_PARAMS.maxStakeDuration.divn(2),
+    ]) {
+      const user = await createLockedUser({
+        provider: sdk.provider,
+        lockerW,
+        govTokenMint: shortLocker.govTokenMint,
+        duration,
+      });
+      const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+      const { tx: createEscrowHistoryTX } =
+        await sdk.snapshots.createEscrowHistory({
+          locker: lockerW.locker,
+          escrow,
+          era,
+        });
+      await expectTX(createEscrowHistoryTX, "create escrow history").to.be
+        .fulfilled;
+      // started periods are immutable, so lockups must be synced before the
+      // periods that they count for
+      const syncTX = await sdk.snapshots.sync({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        era,
+      });
+      await expectTXTable(syncTX, "sync").to.be.fulfilled;
+      users.push(user);
+    }
+  });
+
+  it("splits the rewards of a period by veToken balance", async () => {
+    const rewards = new u64(1_000_000_000);
+    const [mint, source] = await createMintAndVault(sdk.provider, rewards);
+    // far enough ahead that the period has not started once it is funded
+    const period = calculateShortPeriod(shortLocker) + 3;
+
+    const { tx: createDistributorTX } = await sdk.snapshots.createDistributor({
+      locker: lockerW.locker,
+      mint,
+      claimSeconds: 600,
+    });
+    await expectTX(createDistributorTX, "create distributor").to.be.fulfilled;
+    const { distributorPeriod, tx: createPeriodTX } =
+      await sdk.snapshots.createDistributorPeriod({
+        locker: lockerW.locker,
+        mint,
+        era,
+        period,
+      });
+    await expectTX(createPeriodTX, "create distributor period").to.be
+      .fulfilled;
+    const fundTX = await sdk.snapshots.fundDistributorPeriod({
+      locker: lockerW.locker,
+      mint,
+      era,
+      period,
+      source,
+      amount: rewards,
+    });
+    await expectTX(fundTX, "fund distributor period").to.be.fulfilled;
+
+    const destinations: PublicKey[] = [];
+    for (const user of users) {
+      const { address, instruction } = await getOrCreateATA({
+        provider: sdk.provider,
+        mint,
+        owner: user.publicKey,
+      });
+      await expectTX(sdk.provider.newTX([instruction]), "create ATA").to.be
+        .fulfilled;
+      destinations.push(address);
+    }
+
+    // the period has not started
+    const [firstUser] = users;
+    const [firstDestination] = destinations;
+    invariant(firstUser && firstDestination);
+    const { tx: earlyClaimTX } = await sdk.snapshots.claim({
+      locker: lockerW.locker,
+      mint,
+      era,
+      period,
+      owner: firstUser.publicKey,
+      destination: firstDestination,
+    });
+    await expectTX(earlyClaimTX, "claim before period").to.be.rejected;
+
+    // wait for the next period to start, so the period is materialized
+    const { periodSeconds, originTs } = shortLocker;
+    await sleep(
+      (originTs + (period + 1) * periodSeconds) * 1_000 - Date.now() + 1_000
+    );
+    for (const user of users) {
+      const syncTX = await sdk.snapshots.sync({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        era,
+      });
+      await expectTXTable(syncTX, "sync").to.be.fulfilled;
+    }
+
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    invariant(lockerHistoryData);
+    const lockerBalance = lockerHistoryData.veBalances[period];
+    invariant(lockerBalance);
+
+    const amounts: BN[] = [];
+    for (const [user, destination] of zip(users, destinations)) {
+      invariant(user && destination);
+      const { claimReceipt, tx: claimTX } = await sdk.snapshots.claim({
+        locker: lockerW.locker,
+        mint,
+        era,
+        period,
+        owner: user.publicKey,
+        destination,
+      });
+      await expectTX(claimTX, "claim").to.be.fulfilled;
+
+      const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+      const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+      const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+        escrowHistory
+      );
+      const receiptData = await sdk.snapshots.fetchClaimReceipt(claimReceipt);
+      invariant(escrowHistoryData && receiptData);
+      const escrowBalance =
+        calculateEscrowVeBalances(escrowHistoryData)[period];
+      invariant(escrowBalance);
+      const amount = rewards.mul(escrowBalance).div(lockerBalance);
+      expect(amount).to.bignumber.above(new u64(0));
+      expect(receiptData.amount).to.bignumber.eq(amount);
+      const destinationData = await getTokenAccount(sdk.provider, destination);
+      expect(destinationData.amount).to.bignumber.eq(amount);
+      amounts.push(amount);
+
+      // an escrow can only claim once
+      const { tx: claimAgainTX } = await sdk.snapshots.claim({
+        locker: lockerW.locker,
+        mint,
+        era,
+        period,
+        owner: user.publicKey,
+        destination,
+      });
+      await expectTX(claimAgainTX, "claim again").to.be.rejected;
+    }
+    // the users hold all of the locked tokens, so the rewards are all
+    // claimed up to rounding
+    const claimed = amounts.reduce((acc, amount) => acc.add(amount));
+    expect(claimed).to.bignumber.at.most(rewards);
+    expect(claimed).to.bignumber.at.least(rewards.subn(users.length));
+
+    const distributorPeriodData = await sdk.snapshots.fetchDistributorPeriod(
+      distributorPeriod
+    );
+    invariant(distributorPeriodData);
+    expect(distributorPeriodData.claimedRewards).to.bignumber.eq(claimed);
+  });
+
+  it("claws back unclaimed rewards after the claim deadline", async () => {
+    const rewards = new u64(1_000_000_000);
+    const [mint, source] = await createMintAndVault(sdk.provider, rewards);
+    const period = calculateShortPeriod(shortLocker) + 3;
+    const claimSeconds = 4;
+
+    const { distributor, tx: createDistributorTX } =
+      await sdk.snapshots.createDistributor({
+        locker: lockerW.locker,
+        mint,
+        claimSeconds,
+      });
+    await expectTX(createDistributorTX, "create distributor").to.be.fulfilled;
+    const { distributorPeriod, tx: createPeriodTX } =
+      await sdk.snapshots.createDistributorPeriod({
+        locker: lockerW.locker,
+        mint,
+        era,
+        period,
+      });
+    await expectTX(createPeriodTX, "create distributor period").to.be
+      .fulfilled;
+    await expectTX(
+      await sdk.snapshots.fundDistributorPeriod({
+        locker: lockerW.locker,
+        mint,
+        era,
+        period,
+        source,
+        amount: rewards,
+      }),
+      "fund distributor period"
+    ).to.be.fulfilled;
+
+    const [claimant, absentee] = users;
+    invariant(claimant && absentee);
+    const destinations: PublicKey[] = [];
+    for (const user of [claimant, absentee]) {
+      const { address, instruction } = await getOrCreateATA({
+        provider: sdk.provider,
+        mint,
+        owner: user.publicKey,
+      });
+      await expectTX(sdk.provider.newTX([instruction]), "create ATA").to.be
+        .fulfilled;
+      destinations.push(address);
+    }
+    const [claimantDestination, absenteeDestination] = destinations;
+    invariant(claimantDestination && absenteeDestination);
+
+    const { periodSeconds, originTs } = shortLocker;
+    const periodEndTs = originTs + (period + 1) * periodSeconds;
+    await sleep(periodEndTs * 1_000 - Date.now() + 1_000);
+    for (const user of users) {
+      const syncTX = await sdk.snapshots.sync({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        era,
+      });
+      await expectTXTable(syncTX, "sync").to.be.fulfilled;
+    }
+    const { tx: claimTX } = await sdk.snapshots.claim({
+      locker: lockerW.locker,
+      mint,
+      era,
+      period,
+      owner: claimant.publicKey,
+      destination: claimantDestination,
+    });
+    await expectTX(claimTX, "claim").to.be.fulfilled;
+    const claimed = (await getTokenAccount(sdk.provider, claimantDestination))
+      .amount;
+    expect(claimed).to.bignumber.above(new u64(0));
+    expect(claimed).to.bignumber.below(rewards);
+
+    // unclaimed rewards cannot be taken back while claims are open
+    const clawbackTX = await sdk.snapshots.clawbackDistributorPeriod({
+      locker: lockerW.locker,
+      mint,
+      era,
+      period,
+      destination: source,
+    });
+    await expectTX(clawbackTX, "clawback before deadline").to.be.rejected;
+
+    await sleep((periodEndTs + claimSeconds) * 1_000 - Date.now() + 1_000);
+    const { tx: lateClaimTX } = await sdk.snapshots.claim({
+      locker: lockerW.locker,
+      mint,
+      era,
+      period,
+      owner: absentee.publicKey,
+      destination: absenteeDestination,
+    });
+    await expectTX(lateClaimTX, "claim after deadline").to.be.rejected;
+
+    // only the distributor authority may claw back
+    const stranger = Keypair.generate();
+    const [vault] = await findDistributorVaultAddress(distributor);
+    const strangerClawbackTX = sdk.provider.newTX(
+      [
+        sdk.programs.Snapshots.instruction.clawbackDistributorPeriod({
+          accounts: {
+            distributor,
+            distributorPeriod,
+            vault,
+            authority: stranger.publicKey,
+            destination: source,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          },
+        }),
+      ],
+      [stranger]
+    );
+    await expectTX(strangerClawbackTX, "clawback by stranger").to.be.rejected;
+
+    await expectTX(
+      await sdk.snapshots.clawbackDistributorPeriod({
+        locker: lockerW.locker,
+        mint,
+        era,
+        period,
+        destination: source,
+      }),
+      "clawback"
+    ).to.be.fulfilled;
+    const unclaimed = rewards.sub(claimed);
+    const sourceData = await getTokenAccount(sdk.provider, source);
+    expect(sourceData.amount).to.bignumber.eq(unclaimed);
+    const distributorPeriodData = await sdk.snapshots.fetchDistributorPeriod(
+      distributorPeriod
+    );
+    invariant(distributorPeriodData);
+    expect(distributorPeriodData.claimedRewards).to.bignumber.eq(claimed);
+    expect(distributorPeriodData.clawedBackRewards).to.bignumber.eq(unclaimed);
+
+    // the rewards of a period can only be taken back once
+    await expectTX(
+      await sdk.snapshots.clawbackDistributorPeriod({
+        locker: lockerW.locker,
+        mint,
+        era,
+        period,
+        destination: source,
+      }),
+      "clawback again"
+    ).to.be.fulfilled;
+    const sourceDataAfter = await getTokenAccount(sdk.provider, source);
+    expect(sourceDataAfter.amount).to.bignumber.eq(unclaimed);
+  });
+});
+
+describe("Snapshot Airdrops", () => {
+  const sdk = makeSDK();
+
+  const era = 0;
+
+  let shortLocker: ShortPeriodLocker;
+  let lockerW: LockerWrapper;
+  let users: Signer[];
+
+  beforeEach(async () => {
+    shortLocker = await createShortPeriodLocker({ sdk, periodSeconds: 2 });
+    ({ lockerW } = shortLocker);
+
+    users = [];
+    for (const duration of [
+      DEFAULT_LOCKER_PARAMS.maxStakeDuration,
+      DEFAULT_LOCKER_PARAMS.maxStakeDuration.divn(2),
+    ]) {
+      const user = await createLockedUser({
+        provider: sdk.provider,
+        lockerW,
+        govTokenMint: shortLocker.govTokenMint,
+        duration,
+      });
+      const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+      const { tx: createEscrowHistoryTX } =
+        await sdk.snapshots.createEscrowHistory({
+          locker: lockerW.locker,
+          escrow,
+          era,
+        });
+      await expectTX(createEscrowHistoryTX, "create escrow history").to.be
+        .fulfilled;
+      // started periods are immutable, so lockups must be synced before the
+      // periods that they count for
+      const syncTX = await sdk.snapshots.sync({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        era,
+      });
+      await expectTXTable(syncTX, "sync").to.be.fulfilled;
+      users.push(user);
+    }
+  });
+
+  /**
+   * Waits for a period to be final, and returns the claims of the users
+   * weighted by their balances at the period.
+   */
+  const snapshotClaims = async (
+    period: number,
+    amount: BN
+  ): Promise<AirdropClaim[]> => {
+    const { periodSeconds, originTs } = shortLocker;
+    await sleep(
+      (originTs + (period + 1) * periodSeconds) * 1_000 - Date.now() + 1_000
+    );
+    for (const user of users) {
+      const syncTX = await sdk.snapshots.sync({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        era,
+      });
+      await expectTXTable(syncTX, "sync").to.be.fulfilled;
+    }
+
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    invariant(lockerHistoryData);
+    const lockerBalance = lockerHistoryData.veBalances[period];
+    invariant(lockerBalance);
+    const claims: AirdropClaim[] = [];
+    for (const [index, user] of users.entries()) {
+      const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+      const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+      const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+        escrowHistory
+      );
+      invariant(escrowHistoryData);
+      const escrowBalance =
+        calculateEscrowVeBalances(escrowHistoryData)[period];
+      invariant(escrowBalance);
+      claims.push({
+        index,
+        claimant: user.publicKey,
+        amount: amount.mul(escrowBalance).div(lockerBalance),
+      });
+    }
+    return claims;
+  };
+
+  const createATA = async (mint: PublicKey, owner: PublicKey) => {
+    const { address, instruction } = await getOrCreateATA({
+      provider: sdk.provider,
+      mint,
+      owner,
+    });
+    await expectTX(sdk.provider.newTX([instruction]), "create ATA").to.be
+      .fulfilled;
+    return address;
+  };
+
+  it("checks the airdrop snapshot against the LockerHistory", async () => {
+    const amount = new u64(1_000_000_000);
+    const [mint, source] = await createMintAndVault(sdk.provider, amount);
+    // the lockups started before the next period
+    const period = calculateShortPeriod(shortLocker) + 1;
+    const claims = await snapshotClaims(period, amount);
+    const tree = new AirdropMerkleTree(claims);
+    const claimDeadline = Math.floor(Date.now() / 1_000) + 60;
+    const airdropArgs = {
+      locker: lockerW.locker,
+      era,
+      mint,
+      source,
+      root: tree.root,
+      maxTotalClaim: amount,
+      maxNumNodes: claims.length,
+      claimDeadline,
+    };
+
+    // the next period is not final
+    const { tx: earlyTX } = await sdk.snapshots.createSnapshotAirdrop({
+      ...airdropArgs,
+      period: period + 1,
+    });
+    await expectTX(earlyTX, "airdrop of a period that is not final").to.be
+      .rejected;
+
+    // the LockerHistory must be of the era of the airdrop
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const { lockerHistory: nextLockerHistory, tx: createNextHistoryTX } =
+      await sdk.snapshots.createLockerHistory({
+        locker: lockerW.locker,
+        era: era + 1,
+      });
+    await expectTX(createNextHistoryTX, "create next locker history").to.be
+      .fulfilled;
+    const { tx: otherEraTX } = await sdk.snapshots.createSnapshotAirdrop({
+      ...airdropArgs,
+      period,
+    });
+    const lockerHistoryMeta = otherEraTX.instructions[0]?.keys.find(
+      ({ pubkey }) => pubkey.equals(lockerHistory)
+    );
+    invariant(lockerHistoryMeta);
+    lockerHistoryMeta.pubkey = nextLockerHistory;
+    await expectTX(otherEraTX, "airdrop with another era").to.be.rejected;
+
+    // the deadline must be in the future
+    const { tx: expiredTX } = await sdk.snapshots.createSnapshotAirdrop({
+      ...airdropArgs,
+      period,
+      claimDeadline: Math.floor(Date.now() / 1_000) - 1,
+    });
+    await expectTX(expiredTX, "airdrop past its deadline").to.be.rejected;
+
+    const { airdrop, tx: createTX } = await sdk.snapshots.createSnapshotAirdrop(
+      { ...airdropArgs, period }
+    );
+    await expectTX(createTX, "create airdrop").to.be.fulfilled;
+
+    const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    const airdropData = await sdk.snapshots.fetchSnapshotAirdrop(airdrop);
+    invariant(lockerHistoryData && airdropData);
+    expect(airdropData.locker).to.eqAddress(lockerW.locker);
+    expect(airdropData.era).to.eq(era);
+    expect(airdropData.period).to.eq(period);
+    expect(airdropData.root).to.deep.eq([...tree.root]);
+    expect(airdropData.lockerVeBalance).to.bignumber.eq(
+      lockerHistoryData.veBalances[period]
+    );
+    expect(airdropData.maxTotalClaim).to.bignumber.eq(amount);
+    const vaultData = await getTokenAccount(sdk.provider, airdropData.vault);
+    expect(vaultData.amount).to.bignumber.eq(amount);
+  });
+
+  it("claims with proofs until the deadline, then claws back", async () => {
+    const amount = new u64(1_000_000_000);
+    const [mint, source] = await createMintAndVault(sdk.provider, amount);
+    const period = calculateShortPeriod(shortLocker) + 1;
+    // half of the tokens go to the users
+    const userClaims = await snapshotClaims(period, amount.divn(2));
+    const [firstClaim, secondClaim] = userClaims;
+    const [firstUser, secondUser] = users;
+    invariant(firstClaim && secondClaim && firstUser && secondUser);
+
+    // a claim that is never made, and one past the declared number of claims
+    const stranger = Keypair.generate();
+    const unclaimed: AirdropClaim = {
+      index: 2,
+      claimant: stranger.publicKey,
+      amount: amount.sub(firstClaim.amount).sub(secondClaim.amount),
+    };
+    const outOfRange: AirdropClaim = {
+      index: 3,
+      claimant: secondUser.publicKey,
+      amount: new u64(1),
+    };
+    const tree = new AirdropMerkleTree([
+      ...userClaims,
+      unclaimed,
+      outOfRange,
+    ]);
+
+    const claimDeadline = Math.floor(Date.now() / 1_000) + 30;
+    const { airdrop, tx: createTX } = await sdk.snapshots.createSnapshotAirdrop(
+      {
+        locker: lockerW.locker,
+        era,
+        period,
+        mint,
+        source,
+        root: tree.root,
+        maxTotalClaim: amount,
+        maxNumNodes: 3,
+        claimDeadline,
+      }
+    );
+    await expectTX(createTX, "create airdrop").to.be.fulfilled;
+
+    const { tx: bitmapTX } = await sdk.snapshots.createSnapshotAirdropBitmap({
+      airdrop,
+      index: 0,
+    });
+    await expectTX(bitmapTX, "create bitmap").to.be.fulfilled;
+    // no claim is tracked by the second page
+    const { tx: pastBitmapTX } =
+      await sdk.snapshots.createSnapshotAirdropBitmap({
+        airdrop,
+        index: SNAPSHOT_AIRDROP_BITMAP_CLAIMS,
+      });
+    await expectTX(pastBitmapTX, "create bitmap past the claims").to.be
+      .rejected;
+
+    const firstDestination = await createATA(mint, firstUser.publicKey);
+    const secondDestination = await createATA(mint, secondUser.publicKey);
+    const strangerDestination = await createATA(mint, stranger.publicKey);
+
+    // a proof only verifies the claim it was made for
+    const firstProof = tree.getProof(firstClaim);
+    const root = tree.root;
+    expect(verifyAirdropProof(firstProof, root, firstClaim)).to.be.true;
+    expect(verifyAirdropProof(firstProof, root, secondClaim)).to.be.false;
+    const wrongAmountTX = await sdk.snapshots.claimSnapshotAirdrop({
+      airdrop,
+      claim: { ...firstClaim, amount: firstClaim.amount.addn(1) },
+      proof: firstProof,
+      destination: firstDestination,
+    });
+    wrongAmountTX.addSigners(firstUser);
+    await expectTX(wrongAmountTX, "claim more than the tree").to.be.rejected;
+    const wrongProofTX = await sdk.snapshots.claimSnapshotAirdrop({
+      airdrop,
+      claim: secondClaim,
+      proof: firstProof,
+      destination: secondDestination,
+    });
+    wrongProofTX.addSigners(secondUser);
+    await expectTX(wrongProofTX, "claim with another proof").to.be.rejected;
+
+    for (const [claim, user, destination] of [
+      [firstClaim, firstUser, firstDestination],
+      [secondClaim, secondUser, secondDestination],
+    ] as const) {
+      const claimTX = await sdk.snapshots.claimSnapshotAirdrop({
+        airdrop,
+        claim,
+        proof: tree.getProof(claim),
+        destination,
+      });
+      claimTX.addSigners(user);
+      await expectTX(claimTX, "claim").to.be.fulfilled;
+      const destinationData = await getTokenAccount(sdk.provider, destination);
+      expect(destinationData.amount).to.bignumber.eq(claim.amount);
+
+      // the bitmap records the claim
+      const claimAgainTX = await sdk.snapshots.claimSnapshotAirdrop({
+        airdrop,
+        claim,
+        proof: tree.getProof(claim),
+        destination,
+      });
+      claimAgainTX.addSigners(user);
+      await expectTX(claimAgainTX, "claim again").to.be.rejected;
+    }
+
+    // the claim is in the tree, but past the number of claims of the airdrop
+    const outOfRangeProof = tree.getProof(outOfRange);
+    expect(verifyAirdropProof(outOfRangeProof, root, outOfRange)).to.be.true;
+    const outOfRangeTX = await sdk.snapshots.claimSnapshotAirdrop({
+      airdrop,
+      claim: outOfRange,
+      proof: outOfRangeProof,
+      destination: secondDestination,
+    });
+    outOfRangeTX.addSigners(secondUser);
+    await expectTX(outOfRangeTX, "claim out of range").to.be.rejected;
+
+    const airdropData = await sdk.snapshots.fetchSnapshotAirdrop(airdrop);
+    invariant(airdropData);
+    expect(airdropData.numNodesClaimed).to.bignumber.eq(new BN(2));
+    expect(airdropData.totalAmountClaimed).to.bignumber.eq(
+      firstClaim.amount.add(secondClaim.amount)
+    );
+
+    // unclaimed tokens cannot be taken back before the deadline
+    const earlyClawbackTX = await sdk.snapshots.clawbackSnapshotAirdrop({
+      airdrop,
+      destination: source,
+    });
+    await expectTX(earlyClawbackTX, "clawback before deadline").to.be.rejected;
+
+    await sleep((claimDeadline + 2) * 1_000 - Date.now());
+    const lateClaimTX = await sdk.snapshots.claimSnapshotAirdrop({
+      airdrop,
+      claim: unclaimed,
+      proof: tree.getProof(unclaimed),
+      destination: strangerDestination,
+    });
+    lateClaimTX.addSigners(stranger);
+    await expectTX(lateClaimTX, "claim after deadline").to.be.rejected;
+
+    const clawbackTX = await sdk.snapshots.clawbackSnapshotAirdrop({
+      airdrop,
+      destination: source,
+    });
+    await expectTX(clawbackTX, "clawback").to.be.fulfilled;
+    const sourceData = await getTokenAccount(sdk.provider, source);
+    expect(sourceData.amount).to.bignumber.eq(unclaimed.amount);
+    const vaultData = await getTokenAccount(sdk.provider, airdropData.vault);
+    expect(vaultData.amount).to.bignumber.zero;
+  });
+});
+
+describe("Cumulative Balances", () => {
+  const sdk = makeSDK();
+
+  const era = 0;
+
+  let shortLocker: ShortPeriodLocker;
+  let lockerW: LockerWrapper;
+  let escrows: PublicKey[];
+
+  beforeEach(async () => {
+    shortLocker = await createShortPeriodLocker({ sdk, periodSeconds: 2 });
+    ({ lockerW } = shortLocker);
+
+    escrows = [];
+    for (let i = 0; i < 2; i++) {
+      const user = await createLockedUser({
+        provider: sdk.provider,
+        lockerW,
+        govTokenMint: shortLocker.govTokenMint,
+      });
+      const [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+      const { tx: createEscrowHistoryTX } =
+        await sdk.snapshots.createEscrowHistory({
+          locker: lockerW.locker,
+          escrow,
+          era,
+        });
+      await expectTX(createEscrowHistoryTX, "create escrow history").to.be
+        .fulfilled;
+      const syncTX = await sdk.snapshots.sync({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        era,
+      });
+      await expectTXTable(syncTX, "sync").to.be.fulfilled;
+      escrows.push(escrow);
+    }
+  });
+
+  /**
+   * Checks that each recorded sum is the sum of the balances up to its period.
+   */
+  const expectSumsMatch = (
+    sums: BN[],
+    numPeriods: number,
+    veBalances: BN[]
+  ) => {
+    let sum = new BN(0);
+    for (let period = 0; period < numPeriods; period++) {
+      const veBalance = veBalances[period];
+      invariant(veBalance);
+      sum = sum.add(veBalance);
+      expect(sums[period], `period ${period}`).to.bignumber.eq(sum);
+    }
+  };
+
+  it("records the sums of the final periods", async () => {
+    const [escrow, otherEscrow] = escrows;
+    invariant(escrow && otherEscrow);
+    const [lockerHistory] = await findLockerHistoryAddress(lockerW.locker, era);
+    const [escrowHistory] = await findEscrowHistoryAddress(escrow, era);
+
+    const { cumulativeBalances: lockerCumulativeBalances, tx: createLockerTX } =
+      await sdk.snapshots.createLockerCumulativeBalances({
+        locker: lockerW.locker,
+        era,
+      });
+    await expectTX(createLockerTX, "create locker cumulative balances").to.be
+      .fulfilled;
+
+    // the escrow history must be of the escrow
+    const { tx: createMismatchedTX } =
+      await sdk.snapshots.createEscrowCumulativeBalances({
+        locker: lockerW.locker,
+        escrow: otherEscrow,
+        escrowHistory,
+        era,
+      });
+    await expectTX(createMismatchedTX, "create with another escrow").to.be
+      .rejected;
+
+    const { cumulativeBalances: escrowCumulativeBalances, tx: createEscrowTX } =
+      await sdk.snapshots.createEscrowCumulativeBalances({
+        locker: lockerW.locker,
+        escrow,
+        escrowHistory,
+        era,
+      });
+    await expectTX(createEscrowTX, "create escrow cumulative balances").to.be
+      .fulfilled;
+
+    // wait for a few periods to end, then materialize them
+    const { periodSeconds, originTs } = shortLocker;
+    const lastPeriod = calculateShortPeriod(shortLocker) + 2;
+    await sleep(
+      (originTs + (lastPeriod + 1) * periodSeconds) * 1_000 - Date.now() + 1_000
+    );
+    await expectTX(
+      await sdk.snapshots.checkpointLockerHistory({
+        locker: lockerW.locker,
+        era,
+      }),
+      "checkpoint locker history"
+    ).to.be.fulfilled;
+
+    // the sums of another history cannot be extended
+    const mismatchedCheckpointTX = sdk.provider.newTX([
+      sdk.programs.Snapshots.instruction.checkpointLockerCumulativeBalances({
+        accounts: {
+          lockerHistory,
+          cumulativeBalances: escrowCumulativeBalances,
+        },
+      }),
+    ]);
+    await expectTX(mismatchedCheckpointTX, "checkpoint another history").to.be
+      .rejected;
+
+    await expectTX(
+      await sdk.snapshots.checkpointLockerCumulativeBalances({
+        locker: lockerW.locker,
+        era,
+      }),
+      "checkpoint locker cumulative balances"
+    ).to.be.fulfilled;
+    await expectTX(
+      await sdk.snapshots.checkpointEscrowCumulativeBalances({
+        locker: lockerW.locker,
+        escrow,
+        escrowHistory,
+        era,
+      }),
+      "checkpoint escrow cumulative balances"
+    ).to.be.fulfilled;
+
+    const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+      lockerHistory
+    );
+    const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+      escrowHistory
+    );
+    const lockerSumsData = await sdk.snapshots.fetchCumulativeBalances(
+      lockerCumulativeBalances
+    );
+    const escrowSumsData = await sdk.snapshots.fetchCumulativeBalances(
+      escrowCumulativeBalances
+    );
+    invariant(
+      lockerHistoryData && escrowHistoryData && lockerSumsData && escrowSumsData
+    );
+    expect(lockerSumsData.history).to.eqAddress(lockerHistory);
+    expect(escrowSumsData.history).to.eqAddress(escrowHistory);
+    expect(lockerSumsData.era).to.eq(era);
+
+    // without shards, every materialized period is final
+    expect(lockerSumsData.numPeriods).to.eq(
+      lockerHistoryData.materializedPeriods
+    );
+    expect(lockerSumsData.numPeriods).to.be.above(lastPeriod);
+    expectSumsMatch(
+      lockerSumsData.veBalanceSums,
+      lockerSumsData.numPeriods,
+      lockerHistoryData.veBalances
+    );
+    expect(escrowSumsData.numPeriods).to.eq(lockerSumsData.numPeriods);
+    expectSumsMatch(
+      escrowSumsData.veBalanceSums,
+      escrowSumsData.numPeriods,
+      calculateEscrowVeBalances(escrowHistoryData)
+    );
+    const lastSum = escrowSumsData.veBalanceSums[lastPeriod];
+    invariant(lastSum);
+    expect(lastSum).to.bignumber.above(new BN(0));
+
+    // each history has a single CumulativeBalances
+    const { tx: createAgainTX } =
+      await sdk.snapshots.createLockerCumulativeBalances({
+        locker: lockerW.locker,
+        era,
+      });
+    await expectTX(createAgainTX, "create again").to.be.rejected;
+  });
+});
+
+describe("Average Views", () => {
+  const sdk = makeSDK();
+
+  const eras = [0, 1];
+
+  let shortLocker: ShortPeriodLocker;
+  let lockerW: LockerWrapper;
+  let escrow: PublicKey;
+  let escrowHistories: PublicKey[];
+
+  beforeEach(async () => {
+    // era 0 ends shortly after the escrow is synced
+    const periodSeconds = 1;
+    shortLocker = await createShortPeriodLocker({
+      sdk,
+      periodSeconds,
+      originTs:
+        Math.floor(Date.now() / 1_000) - (ERA_NUM_PERIODS - 20) * periodSeconds,
+    });
+    ({ lockerW } = shortLocker);
+    const { tx: createLockerHistoryTX } =
+      await sdk.snapshots.createLockerHistory({
+        locker: lockerW.locker,
+        era: 1,
+      });
+    await expectTX(createLockerHistoryTX, "create locker history").to.be
+      .fulfilled;
+
+    const user = await createLockedUser({
+      provider: sdk.provider,
+      lockerW,
+      govTokenMint: shortLocker.govTokenMint,
+    });
+    [escrow] = await findEscrowAddress(lockerW.locker, user.publicKey);
+    escrowHistories = [];
+    for (const era of eras) {
+      const { escrowHistory, tx: createEscrowHistoryTX } =
+        await sdk.snapshots.createEscrowHistory({
+          locker: lockerW.locker,
+          escrow,
+          era,
+        });
+      await expectTX(createEscrowHistoryTX, "create escrow history").to.be
+        .fulfilled;
+      const syncTX = await sdk.snapshots.sync({
+        locker: lockerW.locker,
+        owner: user.publicKey,
+        era,
+      });
+      await expectTXTable(syncTX, "sync").to.be.fulfilled;
+      escrowHistories.push(escrowHistory);
+    }
+  });
+
+  const average = (values: BN[]): BN =>
+    values
+      .reduce((acc, value) => acc.add(value), new BN(0))
+      .divn(values.length);
+
+  it("averages balances over ranges that cross an era boundary", async () => {
+    // the last two periods of era 0 and the first two of era 1
+    const periods: [number, number][] = [
+      [0, ERA_NUM_PERIODS - 2],
+      [0, ERA_NUM_PERIODS - 1],
+      [1, 0],
+      [1, 1],
+    ];
+    const query: PeriodRangeQuery = {
+      start: { period: { era: 0, period: ERA_NUM_PERIODS - 2 } },
+      end: { period: { era: 1, period: 1 } },
+    };
+
+    // wait for the range to end, then materialize the totals
+    const { periodSeconds, originTs } = shortLocker;
+    await sleep(
+      (originTs + (ERA_NUM_PERIODS + 2) * periodSeconds) * 1_000 -
+        Date.now() +
+        1_000
+    );
+    const escrowBalances: BN[] = [];
+    const lockerBalances: BN[] = [];
+    const lockerCounts: BN[] = [];
+    for (const [era, escrowHistory] of escrowHistories.entries()) {
+      await expectTX(
+        await sdk.snapshots.checkpointLockerHistory({
+          locker: lockerW.locker,
+          era,
+        }),
+        "checkpoint locker history"
+      ).to.be.fulfilled;
+      const [lockerHistory] = await findLockerHistoryAddress(
+        lockerW.locker,
+        era
+      );
+      const lockerHistoryData = await sdk.snapshots.fetchLockerHistory(
+        lockerHistory
+      );
+      const escrowHistoryData = await sdk.snapshots.fetchEscrowHistory(
+        escrowHistory
+      );
+      invariant(lockerHistoryData && escrowHistoryData);
+      const eraEscrowBalances = calculateEscrowVeBalances(escrowHistoryData);
+      for (const [periodEra, period] of periods) {
+        if (periodEra !== era) {
+          continue;
+        }
+        const escrowBalance = eraEscrowBalances[period];
+        const lockerBalance = lockerHistoryData.veBalances[period];
+        const lockerCount = lockerHistoryData.veCounts[period];
+        invariant(escrowBalance && lockerBalance && lockerCount);
+        escrowBalances.push(escrowBalance);
+        lockerBalances.push(lockerBalance);
+        lockerCounts.push(lockerCount);
+      }
+    }
+
+    const escrowBalance = await sdk.snapshots.getAverageEscrowBalance({
+      escrowHistories,
+      query,
+    });
+    expect(escrowBalance).to.bignumber.eq(average(escrowBalances));
+    expect(escrowBalance).to.bignumber.above(new BN(0));
+
+    const lockerBalance = await sdk.snapshots.getAverageLockerBalance({
+      locker: lockerW.locker,
+      firstEra: 0,
+      numEras: eras.length,
+      query,
+    });
+    expect(lockerBalance.veBalance).to.bignumber.eq(average(lockerBalances));
+    expect(lockerBalance.veCount).to.bignumber.eq(average(lockerCounts));
+    expect(lockerBalance.isFinal).to.be.true;
+
+    const share = await sdk.snapshots.getAverageShare({
+      locker: lockerW.locker,
+      escrow,
+      escrowHistories,
+      firstEra: 0,
+      query,
+    });
+    expect(share.escrowVeBalance).to.bignumber.eq(escrowBalance);
+    expect(share.lockerVeBalance).to.bignumber.eq(lockerBalance.veBalance);
+    expect(share.isFinal).to.be.true;
+
+    // the views read the same balances from cumulative sums
+    const cumulativeBalances: PublicKey[] = [];
+    for (const [era, escrowHistory] of escrowHistories.entries()) {
+      const { cumulativeBalances: lockerSums, tx: createLockerSumsTX } =
+        await sdk.snapshots.createLockerCumulativeBalances({
+          locker: lockerW.locker,
+          era,
+        });
+      await expectTX(createLockerSumsTX, "create locker sums").to.be.fulfilled;
+      const { cumulativeBalances: escrowSums, tx: createEscrowSumsTX } =
+        await sdk.snapshots.createEscrowCumulativeBalances({
+          locker: lockerW.locker,
+          escrow,
+          escrowHistory,
+          era,
+        });
+      await expectTX(createEscrowSumsTX, "create escrow sums").to.be.fulfilled;
+      cumulativeBalances.push(lockerSums, escrowSums);
+    }
+    expect(
+      await sdk.snapshots.getAverageEscrowBalance({
+        escrowHistories,
+        query,
+        cumulativeBalances,
+      })
+    ).to.bignumber.eq(escrowBalance);
+    expect(
+      await sdk.snapshots.getAverageLockerBalance({
+        locker: lockerW.locker,
+        firstEra: 0,
+        numEras: eras.length,
+        query,
+        cumulativeBalances,
+      })
+    ).to.deep.eq(lockerBalance);
+    expect(
+      await sdk.snapshots.getAverageShare({
+        locker: lockerW.locker,
+        escrow,
+        escrowHistories,
+        firstEra: 0,
+        query,
+        cumulativeBalances,
+      })
+    ).to.deep.eq(share);
+  });
+
+  it("rejects missing histories and inverted ranges", async () => {
+    const [firstEscrowHistory, secondEscrowHistory] = escrowHistories;
+    invariant(firstEscrowHistory && secondEscrowHistory);
+    const query: PeriodRangeQuery = {
+      start: { period: { era: 0, period: ERA_NUM_PERIODS - 2 } },
+      end: { period: { era: 1, period: 1 } },
+    };
+
+    // each era of the range needs its histories
+    await expect(
+      sdk.snapshots.getAverageEscrowBalance({
+        escrowHistories: [firstEscrowHistory],
+        query,
+      })
+    ).to.be.rejected;
+    await expect(
+      sdk.snapshots.getAverageLockerBalance({
+        locker: lockerW.locker,
+        firstEra: 0,
+        numEras: 1,
+        query,
+      })
+    ).to.be.rejected;
+    await expect(
+      sdk.snapshots.getAverageShare({
+        locker: lockerW.locker,
+        escrow,
+        escrowHistories: [firstEscrowHistory],
+        firstEra: 0,
+        query,
+      })
+    ).to.be.rejected;
+
+    // the histories must be given in the order of their eras
+    await expect(
+      sdk.snapshots.getAverageEscrowBalance({
+        escrowHistories: [secondEscrowHistory, firstEscrowHistory],
+        query,
+      })
+    ).to.be.rejected;
+
+    // a CumulativeBalances does not stand in for a missing history
+    const [firstEscrowSums] = await findCumulativeBalancesAddress(
+      firstEscrowHistory
+    );
+    const { tx: createEscrowSumsTX } =
+      await sdk.snapshots.createEscrowCumulativeBalances({
+        locker: lockerW.locker,
+        escrow,
+        escrowHistory: firstEscrowHistory,
+        era: 0,
+      });
+    await expectTX(createEscrowSumsTX, "create escrow sums").to.be.fulfilled;
+    await expect(
+      sdk.snapshots.getAverageEscrowBalance({
+        escrowHistories: [firstEscrowHistory],
+        query,
+        cumulativeBalances: [firstEscrowSums],
+      })
+    ).to.be.rejected;
+
+    // the range may not end before it starts
+    const invertedQuery: PeriodRangeQuery = {
+      start: { period: { era: 1, period: 1 } },
+      end: { period: { era: 1, period: 0 } },
+    };
+    await expect(
+      sdk.snapshots.getAverageEscrowBalance({
+        escrowHistories: [secondEscrowHistory],
+        query: invertedQuery,
+      })
+    ).to.be.rejected;
+    await expect(
+      sdk.snapshots.getAverageShare({
+        locker: lockerW.locker,
+        escrow,
+        escrowHistories: [secondEscrowHistory],
+        firstEra: 1,
+        query: invertedQuery,
+      })
+    ).to.be.rejected;
+    const { originTs } = shortLocker;
+    await expect(
+      sdk.snapshots.getAverageEscrowBalance({
+        escrowHistories,
+        query: {
+          start: { timestamp: { ts: new BN(originTs + ERA_NUM_PERIODS) } },
+          end: { timestamp: { ts: new BN(originTs) } },
+        },
+      })
+    ).to.be.rejected;
+
+    // a range of a single period is the balance of that period
+    const period = Math.min(
+      calculateShortPeriod(shortLocker),
+      ERA_NUM_PERIODS - 1
     );
-    expect(escrowHistoryData2.veBalances).to.not.deep.eq(
-      escrowHistoryData.veBalances
+    const periodQuery = { period: { era: 0, period } };
+    expect(
+      await sdk.snapshots.getAverageEscrowBalance({
+        escrowHistories: [firstEscrowHistory],
+        query: { start: periodQuery, end: periodQuery },
+      })
+    ).to.bignumber.eq(
+      await sdk.snapshots.getEscrowBalance({
+        escrowHistory: firstEscrowHistory,
+        query: periodQuery,
+      })
     );
   });
 });
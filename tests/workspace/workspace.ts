@@ -1,10 +1,14 @@
+import type { SmartWalletWrapper } from "@gokiprotocol/client";
+import { GokiSDK } from "@gokiprotocol/client";
 import * as anchor from "@project-serum/anchor";
-import { AnchorProvider } from "@project-serum/anchor";
+import type { Coder, Event } from "@project-serum/anchor";
+import { AnchorProvider, EventParser } from "@project-serum/anchor";
 import { makeSaberProvider } from "@saberhq/anchor-contrib";
 import { chaiSolana, expectTX } from "@saberhq/chai-solana";
 import type { Provider } from "@saberhq/solana-contrib";
 import { TransactionEnvelope } from "@saberhq/solana-contrib";
 import {
+  createMint,
   getOrCreateATA,
   SPLToken,
   TOKEN_PROGRAM_ID,
@@ -16,6 +20,16 @@ import {
   LAMPORTS_PER_SOL,
   TransactionInstruction,
 } from "@solana/web3.js";
+import type BN from "bn.js";
+import type {
+  GovernorWrapper,
+  LockerWrapper,
+} from "@tribecahq/tribeca-sdk";
+import {
+  createLocker,
+  DEFAULT_LOCKER_PARAMS,
+  TribecaSDK,
+} from "@tribecahq/tribeca-sdk";
 import chai from "chai";
 
 import type { SnapshotsPrograms } from "../../src";
@@ -77,3 +91,222 @@ export const createUser = async (
 
   return user;
 };
+
+/**
+ * Confirms a transaction and parses the events that a program emitted in it.
+ */
+export const confirmEvents = async (
+  tx: TransactionEnvelope,
+  program: { programId: PublicKey; coder: Coder }
+): Promise<Event[]> => {
+  const receipt = await tx.confirm();
+  const events: Event[] = [];
+  new EventParser(program.programId, program.coder).parseLogs(
+    receipt.response.meta?.logMessages ?? [],
+    (event) => events.push(event)
+  );
+  return events;
+};
+
+/**
+ * Proposes instructions to a smart wallet with a threshold of one, signed by
+ * the provider wallet, and returns the transaction that executes them.
+ */
+export const proposeBySmartWallet = async ({
+  smartWalletWrapper,
+  instructions,
+}: {
+  smartWalletWrapper: SmartWalletWrapper;
+  instructions: TransactionInstruction[];
+}): Promise<TransactionEnvelope> => {
+  const { tx, transactionKey } = await smartWalletWrapper.newTransaction({
+    instructions,
+  });
+  await expectTX(tx, "propose smart wallet transaction").to.be.fulfilled;
+  return await smartWalletWrapper.executeTransaction({
+    transactionKey,
+  });
+};
+
+/**
+ * Proposes and executes instructions through a smart wallet with a
+ * threshold of one, signed by the provider wallet.
+ */
+export const executeBySmartWallet = async (args: {
+  smartWalletWrapper: SmartWalletWrapper;
+  instructions: TransactionInstruction[];
+}): Promise<void> => {
+  const executeTX = await proposeBySmartWallet(args);
+  await expectTX(executeTX, "execute smart wallet transaction").to.be
+    .fulfilled;
+};
+
+/**
+ * A locker whose governor's smart wallet is owned by the provider wallet.
+ */
+export interface GovernedLocker {
+  govTokenMint: PublicKey;
+  lockerW: LockerWrapper;
+  governorW: GovernorWrapper;
+  smartWalletW: SmartWalletWrapper;
+}
+
+/**
+ * Creates a locker and its governor, without any history.
+ */
+export const createGovernedLocker = async ({
+  sdk,
+}: {
+  sdk: SnapshotsSDK;
+}): Promise<GovernedLocker> => {
+  const gokiSDK = GokiSDK.load({ provider: sdk.provider });
+  const tribecaSDK = TribecaSDK.load({ provider: sdk.provider });
+  const govTokenMint = await createMint(sdk.provider);
+
+  const { createTXs, lockerWrapper, governorWrapper } = await createLocker({
+    sdk: tribecaSDK,
+    gokiSDK,
+    govTokenMint,
+    owners: [sdk.provider.wallet.publicKey],
+    lockerParams: {
+      proposalActivationMinVotes: INITIAL_MINT_AMOUNT,
+    },
+  });
+  for (const { tx: createTX } of createTXs) {
+    await expectTX(createTX).to.be.fulfilled;
+  }
+
+  const governorData = await governorWrapper.data();
+  return {
+    govTokenMint,
+    lockerW: lockerWrapper,
+    governorW: governorWrapper,
+    smartWalletW: await gokiSDK.loadSmartWallet(governorData.smartWallet),
+  };
+};
+
+/**
+ * Creates the SnapshotConfig of a {@link GovernedLocker} through its
+ * smart wallet.
+ */
+export const createSnapshotConfig = async ({
+  sdk,
+  governedLocker: { lockerW, governorW, smartWalletW },
+  periodSeconds,
+  originTs,
+}: {
+  sdk: SnapshotsSDK;
+  governedLocker: GovernedLocker;
+  periodSeconds: number;
+  originTs: number;
+}): Promise<PublicKey> => {
+  const { snapshotConfig, instruction } =
+    await sdk.snapshots.createSnapshotConfig({
+      locker: lockerW.locker,
+      governor: governorW.governorKey,
+      smartWallet: smartWalletW.key,
+      periodSeconds,
+      originTs,
+    });
+  await executeBySmartWallet({
+    smartWalletWrapper: smartWalletW,
+    instructions: [instruction],
+  });
+  return snapshotConfig;
+};
+
+/**
+ * A locker whose SnapshotConfig has short periods, so that periods become
+ * final within a test.
+ */
+export interface ShortPeriodLocker {
+  govTokenMint: PublicKey;
+  lockerW: LockerWrapper;
+  governorW: GovernorWrapper;
+  periodSeconds: number;
+  /**
+   * Unix timestamp at which era 0 starts.
+   */
+  originTs: number;
+}
+
+/**
+ * Creates a locker with a SnapshotConfig of short periods, and the
+ * LockerHistory of era 0.
+ */
+export const createShortPeriodLocker = async ({
+  sdk,
+  periodSeconds,
+  originTs = Math.floor(Date.now() / 1_000) - 10,
+}: {
+  sdk: SnapshotsSDK;
+  periodSeconds: number;
+  /**
+   * Unix timestamp at which era 0 starts. Defaults to just before the locker
+   * is created.
+   */
+  originTs?: number;
+}): Promise<ShortPeriodLocker> => {
+  const governedLocker = await createGovernedLocker({ sdk });
+  const { govTokenMint, lockerW, governorW } = governedLocker;
+
+  await createSnapshotConfig({
+    sdk,
+    governedLocker,
+    periodSeconds,
+    originTs,
+  });
+
+  const { tx: createLockerHistoryTX } =
+    await sdk.snapshots.createLockerHistory({
+      locker: lockerW.locker,
+      era: 0,
+    });
+  await expectTX(createLockerHistoryTX, "create locker history").to.be
+    .fulfilled;
+
+  return {
+    govTokenMint,
+    lockerW,
+    governorW,
+    periodSeconds,
+    originTs,
+  };
+};
+
+/**
+ * Calculates the period of era 0 of a {@link ShortPeriodLocker} that
+ * contains the current time.
+ */
+export const calculateShortPeriod = ({
+  periodSeconds,
+  originTs,
+}: ShortPeriodLocker): number =>
+  Math.floor((Date.now() / 1_000 - originTs) / periodSeconds);
+
+/**
+ * Creates a user who locks their tokens.
+ */
+export const createLockedUser = async ({
+  provider,
+  lockerW,
+  govTokenMint,
+  amount = INITIAL_MINT_AMOUNT,
+  duration = DEFAULT_LOCKER_PARAMS.maxStakeDuration,
+}: {
+  provider: Provider;
+  lockerW: LockerWrapper;
+  govTokenMint: PublicKey;
+  amount?: u64;
+  duration?: BN;
+}): Promise<Signer> => {
+  const user = await createUser(provider, govTokenMint);
+  const lockTx = await lockerW.lockTokens({
+    amount,
+    duration,
+    authority: user.publicKey,
+  });
+  lockTx.addSigners(user);
+  await expectTX(lockTx, "lock tokens").to.be.fulfilled;
+  return user;
+};